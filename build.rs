@@ -1,6 +1,7 @@
-use librojo::cli;
-
+#[cfg(feature = "bundle-plugin")]
 fn main() {
+    use librojo::cli;
+
     let out_dir = std::env::var_os("OUT_DIR").unwrap();
     let dest_path = std::path::PathBuf::from(&out_dir).join("MCPStudioPlugin.rbxm");
     eprintln!("Rebuilding plugin: {dest_path:?}");
@@ -19,3 +20,7 @@ fn main() {
     options.run().unwrap();
     println!("cargo:rerun-if-changed=plugin");
 }
+
+// Without the plugin there is nothing to build, `--install` explains how to get it
+#[cfg(not(feature = "bundle-plugin"))]
+fn main() {}
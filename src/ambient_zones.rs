@@ -0,0 +1,84 @@
+// The LocalScript `setup_ambient_sound` puts in StarterPlayerScripts to play each zone's
+// sound while the player is inside it. The zones are parts in one folder, which the script
+// finds through its `Zones` ObjectValue.
+
+/// Reverbs SoundService.AmbientReverb takes
+pub const REVERB_TYPES: &[&str] = &[
+    "NoReverb",
+    "GenericReverb",
+    "PaddedCell",
+    "Room",
+    "Bathroom",
+    "LivingRoom",
+    "StoneRoom",
+    "Auditorium",
+    "ConcertHall",
+    "Cave",
+    "Arena",
+    "Hangar",
+    "CarpettedHallway",
+    "Hallway",
+    "StoneCorridor",
+    "Alley",
+    "Forest",
+    "City",
+    "Mountains",
+    "Quarry",
+    "Plain",
+    "ParkingLot",
+    "SewerPipe",
+    "UnderWater",
+];
+
+pub const TRIGGER_SOURCE: &str = r#"-- Fades in the Sound of each ambient zone while the player is inside the zone's part and
+-- fades it out again when they leave. The Zones ObjectValue points at the folder of zone
+-- parts, each zone's Volume attribute is how loud it gets and FadeTime on this script is
+-- how long the fades take.
+
+local Players = game:GetService("Players")
+local TweenService = game:GetService("TweenService")
+
+local CHECK_INTERVAL = 0.25
+
+local folder = (script:WaitForChild("Zones") :: ObjectValue).Value
+local fade = TweenInfo.new(script:GetAttribute("FadeTime") or 1.5)
+local player = Players.LocalPlayer
+local inside: { [Instance]: boolean } = {}
+
+local function contains(part: BasePart, point: Vector3): boolean
+	local offset = part.CFrame:PointToObjectSpace(point)
+	local half = part.Size / 2
+	return math.abs(offset.X) <= half.X and math.abs(offset.Y) <= half.Y and math.abs(offset.Z) <= half.Z
+end
+
+while folder do
+	local character = player.Character
+	local root = character and character:FindFirstChild("HumanoidRootPart") :: BasePart?
+	for _, zone in folder:GetChildren() do
+		local sound = zone:FindFirstChildOfClass("Sound")
+		if not zone:IsA("BasePart") or not sound then
+			continue
+		end
+		local isInside = root ~= nil and contains(zone, root.Position)
+		if isInside == (inside[zone] == true) then
+			continue
+		end
+		inside[zone] = isInside
+		if isInside then
+			if not sound.IsPlaying then
+				sound:Play()
+			end
+			TweenService:Create(sound, fade, { Volume = zone:GetAttribute("Volume") or 0.5 }):Play()
+		else
+			local tween = TweenService:Create(sound, fade, { Volume = 0 })
+			tween.Completed:Connect(function(state)
+				if state == Enum.PlaybackState.Completed and not inside[zone] then
+					sound:Stop()
+				end
+			end)
+			tween:Play()
+		end
+	end
+	task.wait(CHECK_INTERVAL)
+end
+"#;
@@ -0,0 +1,229 @@
+// Classes from the Roblox API dump that `Instance.new` can create, i.e. those without the
+// `NotCreatable` or `Service` tags. Checked before a command is queued so a misspelt class
+// fails straight away instead of after a round trip to Studio.
+
+const CREATABLE_CLASSES: &[&str] = &[
+    "Accessory",
+    "Actor",
+    "AlignOrientation",
+    "AlignPosition",
+    "AngularVelocity",
+    "Animation",
+    "AnimationController",
+    "Animator",
+    "ArcHandles",
+    "Atmosphere",
+    "Attachment",
+    "AudioAnalyzer",
+    "AudioChorus",
+    "AudioCompressor",
+    "AudioDeviceInput",
+    "AudioDeviceOutput",
+    "AudioDistortion",
+    "AudioEcho",
+    "AudioEmitter",
+    "AudioEqualizer",
+    "AudioFader",
+    "AudioFlanger",
+    "AudioListener",
+    "AudioPitchShifter",
+    "AudioPlayer",
+    "AudioReverb",
+    "AudioTremolo",
+    "BallSocketConstraint",
+    "Beam",
+    "BillboardGui",
+    "BindableEvent",
+    "BindableFunction",
+    "BlockMesh",
+    "BloomEffect",
+    "BlurEffect",
+    "BodyAngularVelocity",
+    "BodyColors",
+    "BodyForce",
+    "BodyGyro",
+    "BodyPosition",
+    "BodyThrust",
+    "BodyVelocity",
+    "Bone",
+    "BoolValue",
+    "BoxHandleAdornment",
+    "BrickColorValue",
+    "Camera",
+    "CanvasGroup",
+    "CFrameValue",
+    "CharacterMesh",
+    "ChorusSoundEffect",
+    "ClickDetector",
+    "Clouds",
+    "Color3Value",
+    "ColorCorrectionEffect",
+    "CompressorSoundEffect",
+    "ConeHandleAdornment",
+    "Configuration",
+    "CornerWedgePart",
+    "CylinderHandleAdornment",
+    "CylinderMesh",
+    "CylindricalConstraint",
+    "Decal",
+    "DepthOfFieldEffect",
+    "Dialog",
+    "DialogChoice",
+    "DistortionSoundEffect",
+    "DoubleConstrainedValue",
+    "DragDetector",
+    "EchoSoundEffect",
+    "EqualizerSoundEffect",
+    "Explosion",
+    "FaceControls",
+    "FileMesh",
+    "Fire",
+    "FlangeSoundEffect",
+    "Folder",
+    "ForceField",
+    "Frame",
+    "Glue",
+    "Handles",
+    "Hat",
+    "Highlight",
+    "HingeConstraint",
+    "Humanoid",
+    "HumanoidDescription",
+    "ImageButton",
+    "ImageHandleAdornment",
+    "ImageLabel",
+    "IntConstrainedValue",
+    "IntersectOperation",
+    "IntValue",
+    "Keyframe",
+    "KeyframeSequence",
+    "LinearVelocity",
+    "LineForce",
+    "LineHandleAdornment",
+    "LocalizationTable",
+    "LocalScript",
+    "ManualGlue",
+    "ManualWeld",
+    "MaterialVariant",
+    "MeshPart",
+    "Model",
+    "ModuleScript",
+    "Motor",
+    "Motor6D",
+    "NegateOperation",
+    "NoCollisionConstraint",
+    "NumberValue",
+    "ObjectValue",
+    "Pants",
+    "Part",
+    "ParticleEmitter",
+    "Path2D",
+    "PathfindingLink",
+    "PathfindingModifier",
+    "PitchShiftSoundEffect",
+    "PlaneConstraint",
+    "PointLight",
+    "Pose",
+    "PrismaticConstraint",
+    "ProximityPrompt",
+    "RayValue",
+    "RemoteEvent",
+    "RemoteFunction",
+    "ReverbSoundEffect",
+    "RigidConstraint",
+    "RocketPropulsion",
+    "RodConstraint",
+    "RopeConstraint",
+    "ScreenGui",
+    "Script",
+    "ScrollingFrame",
+    "Seat",
+    "SelectionBox",
+    "SelectionSphere",
+    "Shirt",
+    "ShirtGraphic",
+    "SkateboardPlatform",
+    "Sky",
+    "Smoke",
+    "Snap",
+    "Sound",
+    "SoundGroup",
+    "Sparkles",
+    "SpawnLocation",
+    "SpecialMesh",
+    "SphereHandleAdornment",
+    "SpotLight",
+    "SpringConstraint",
+    "StringValue",
+    "SunRaysEffect",
+    "SurfaceAppearance",
+    "SurfaceGui",
+    "SurfaceLight",
+    "SurfaceSelection",
+    "Team",
+    "TerrainRegion",
+    "TextBox",
+    "TextButton",
+    "TextChannel",
+    "TextChatCommand",
+    "TextLabel",
+    "Texture",
+    "Tool",
+    "Torque",
+    "TorsionSpringConstraint",
+    "Trail",
+    "TremoloSoundEffect",
+    "TrussPart",
+    "UIAspectRatioConstraint",
+    "UICorner",
+    "UIDragDetector",
+    "UIFlexItem",
+    "UIGradient",
+    "UIGridLayout",
+    "UIListLayout",
+    "UIPadding",
+    "UIPageLayout",
+    "UIScale",
+    "UISizeConstraint",
+    "UIStroke",
+    "UITableLayout",
+    "UITextSizeConstraint",
+    "UnionOperation",
+    "UniversalConstraint",
+    "UnreliableRemoteEvent",
+    "Vector3Value",
+    "VectorForce",
+    "VehicleSeat",
+    "VelocityMotor",
+    "VideoFrame",
+    "ViewportFrame",
+    "WedgePart",
+    "Weld",
+    "WeldConstraint",
+    "Wire",
+    "WireframeHandleAdornment",
+    "WorldModel",
+    "WrapLayer",
+    "WrapTarget",
+];
+
+/// Checks that `Instance.new` can create `class_name`, suggesting the intended class for
+/// near misses like the wrong case or a missing suffix
+pub fn check_creatable(class_name: &str) -> Result<(), String> {
+    if CREATABLE_CLASSES.contains(&class_name) {
+        return Ok(());
+    }
+    let lower = class_name.to_lowercase();
+    let suggestion = CREATABLE_CLASSES
+        .iter()
+        .find(|class| class.to_lowercase() == lower)
+        .or_else(|| {
+            CREATABLE_CLASSES
+                .iter()
+                .find(|class| class.to_lowercase().starts_with(&lower))
+        });
+    Err(match suggestion {
+        Some(class) => format!("{class_name} is not a creatable class, did you mean {class}?"),
+        None => format!("{class_name} is not a creatable class"),
+    })
+}
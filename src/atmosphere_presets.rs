@@ -0,0 +1,85 @@
+// Looks `set_atmosphere` can apply by name. A preset sets every Atmosphere and fog
+// property, and values passed alongside it override the preset's.
+
+pub struct AtmospherePreset {
+    pub name: &'static str,
+    pub density: f64,
+    pub offset: f64,
+    pub color: &'static str,
+    pub decay: &'static str,
+    pub glare: f64,
+    pub haze: f64,
+    pub fog_start: f64,
+    pub fog_end: f64,
+    pub fog_color: &'static str,
+}
+
+pub const ATMOSPHERE_PRESETS: &[AtmospherePreset] = &[
+    AtmospherePreset {
+        name: "clear_day",
+        density: 0.25,
+        offset: 0.25,
+        color: "#C7C7C7",
+        decay: "#6A707D",
+        glare: 0.0,
+        haze: 0.0,
+        fog_start: 0.0,
+        fog_end: 100_000.0,
+        fog_color: "#C0C0C0",
+    },
+    AtmospherePreset {
+        name: "overcast",
+        density: 0.45,
+        offset: 0.1,
+        color: "#A5A9AE",
+        decay: "#7C8087",
+        glare: 0.0,
+        haze: 1.5,
+        fog_start: 0.0,
+        fog_end: 100_000.0,
+        fog_color: "#A5A9AE",
+    },
+    AtmospherePreset {
+        name: "dense_fog",
+        density: 0.7,
+        offset: 0.0,
+        color: "#BEBEBE",
+        decay: "#9EA4AA",
+        glare: 0.0,
+        haze: 3.0,
+        fog_start: 0.0,
+        fog_end: 150.0,
+        fog_color: "#BEBEBE",
+    },
+    AtmospherePreset {
+        name: "sunset_haze",
+        density: 0.35,
+        offset: 0.5,
+        color: "#FFB27A",
+        decay: "#A0505A",
+        glare: 2.0,
+        haze: 2.5,
+        fog_start: 0.0,
+        fog_end: 100_000.0,
+        fog_color: "#FFB27A",
+    },
+];
+
+/// Finds a preset by name, ignoring case and taking spaces for underscores, so
+/// "Sunset haze" finds `sunset_haze`
+pub fn find(name: &str) -> Result<&'static AtmospherePreset, String> {
+    let key = name.trim().to_ascii_lowercase().replace(' ', "_");
+    ATMOSPHERE_PRESETS
+        .iter()
+        .find(|preset| preset.name == key)
+        .ok_or_else(|| {
+            let names: Vec<_> = ATMOSPHERE_PRESETS
+                .iter()
+                .map(|preset| preset.name)
+                .collect();
+            format!(
+                "There is no {name} atmosphere, use one of {}",
+                names.join(", ")
+            )
+        })
+}
@@ -0,0 +1,132 @@
+use crate::config::{config_dir, Config};
+use axum::extract::Request;
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use color_eyre::eyre::{OptionExt, Result, WrapErr};
+use std::fs;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Header the Studio plugin and proxying instances put the shared secret in
+pub const AUTH_HEADER: &str = "x-mcp-token";
+const TOKEN_FILE_NAME: &str = "auth_token";
+
+/// Loads the shared secret from the config directory, generating and saving a new one
+/// on first use so every instance on this machine agrees on the same token.
+pub fn load_or_create_token() -> Result<String> {
+    let dir = config_dir().ok_or_eyre("Could not find a config directory for the auth token")?;
+    let path = dir.join(TOKEN_FILE_NAME);
+    if let Ok(token) = fs::read_to_string(&path) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+    let token = Uuid::new_v4().simple().to_string();
+    fs::create_dir_all(&dir)
+        .and_then(|_| write_secret(&path, &token))
+        .wrap_err_with(|| format!("Could not save auth token to {}", path.display()))?;
+    tracing::info!("Generated a new plugin auth token in {}", path.display());
+    Ok(token)
+}
+
+/// Writes the token to a file only the current user can read
+fn write_secret(path: &Path, token: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // The mode only applies to new files, an existing one left empty is rewritten here
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(token.as_bytes())
+}
+
+/// Whether `host` only accepts connections from this machine
+pub fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<IpAddr>()
+            .is_ok_and(|address| address.is_loopback())
+}
+
+/// The token a request carries, in the plugin's header or as the bearer token MCP
+/// clients send
+fn provided_token(request: &Request) -> Option<&str> {
+    let headers = request.headers();
+    if let Some(token) = headers.get(AUTH_HEADER) {
+        return token.to_str().ok();
+    }
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// Rejects plugin and MCP transport requests that don't carry the configured token.
+/// Passes everything through when authentication is disabled.
+pub async fn require_token(
+    Extension(config): Extension<Arc<Config>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(expected) = &config.auth_token else {
+        return next.run(request).await;
+    };
+    let provided = provided_token(&request);
+    if provided.is_some_and(|provided| tokens_match(provided, expected)) {
+        next.run(request).await
+    } else {
+        tracing::warn!(
+            "Rejected unauthenticated request to {}",
+            request.uri().path()
+        );
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
+/// Compares tokens in the same time wherever they first differ, so response times don't
+/// give the token away a byte at a time
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_match_only_when_equal() {
+        assert!(tokens_match("abc123", "abc123"));
+        assert!(!tokens_match("abc124", "abc123"));
+        assert!(!tokens_match("abc12", "abc123"));
+        assert!(!tokens_match("", "abc123"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secret_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("auth-token-{}", Uuid::new_v4()));
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        write_secret(&path, "secret").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "secret");
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,67 @@
+// Searching script sources for `search_code`. The plugin only sends the sources and the
+// matching happens here, since Luau has no regular expressions.
+
+use crate::tool_results::{CodeMatch, SearchCodeResult};
+use regex::Regex;
+use serde::Deserialize;
+
+/// Reply of the plugin's `GetScriptSources`
+#[derive(Debug, Deserialize)]
+pub struct ScriptSources {
+    pub scripts: Vec<ScriptSource>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptSource {
+    pub path: String,
+    pub class_name: String,
+    pub source: String,
+    /// Checksum of `source`, so edits made on the server can be written back only to
+    /// scripts that haven't changed since
+    pub checksum: String,
+}
+
+/// Finds the lines of `scripts` that match `pattern`, with up to `context_lines` lines
+/// around each. Matches past `max_results` are only counted.
+pub fn search(
+    scripts: &[ScriptSource],
+    pattern: &Regex,
+    context_lines: usize,
+    max_results: usize,
+) -> SearchCodeResult {
+    let mut matches = Vec::new();
+    let mut total = 0;
+    for script in scripts {
+        let lines: Vec<&str> = script.source.lines().collect();
+        for (index, line) in lines.iter().enumerate() {
+            let Some(found) = pattern.find(line) else {
+                continue;
+            };
+            total += 1;
+            if matches.len() >= max_results {
+                continue;
+            }
+            let after_end = (index + 1 + context_lines).min(lines.len());
+            matches.push(CodeMatch {
+                path: script.path.clone(),
+                line: index + 1,
+                column: line[..found.start()].chars().count() + 1,
+                text: line.to_string(),
+                before: to_strings(&lines[index.saturating_sub(context_lines)..index]),
+                after: to_strings(&lines[index + 1..after_end]),
+            });
+        }
+    }
+    SearchCodeResult {
+        success: true,
+        scripts_searched: scripts.len(),
+        total,
+        truncated: total > matches.len(),
+        matches,
+    }
+}
+
+fn to_strings(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// How urgently a command should reach Studio. Higher priorities are handed to the
+/// plugin first, commands of equal priority keep their queue order.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// Queue of commands waiting for the plugin, ordered by priority and then by arrival
+pub struct CommandQueue<T> {
+    entries: BTreeMap<(Reverse<Priority>, i64), T>,
+    next_back: i64,
+    next_front: i64,
+}
+
+impl<T> CommandQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            next_back: 0,
+            next_front: -1,
+        }
+    }
+
+    pub fn push_back(&mut self, priority: Priority, item: T) {
+        self.entries
+            .insert((Reverse(priority), self.next_back), item);
+        self.next_back += 1;
+    }
+
+    /// Puts a command ahead of everything else of the same priority, e.g. when it has
+    /// to be retried after the plugin connection dropped.
+    pub fn push_front(&mut self, priority: Priority, item: T) {
+        self.entries
+            .insert((Reverse(priority), self.next_front), item);
+        self.next_front -= 1;
+    }
+
+    /// Takes the most urgent command accepted by `filter`
+    pub fn pop_first(&mut self, filter: impl Fn(&T) -> bool) -> Option<T> {
+        let key = *self.entries.iter().find(|(_, item)| filter(item))?.0;
+        self.entries.remove(&key)
+    }
+
+    /// The most urgent command accepted by `filter`, left in the queue
+    pub fn first(&self, filter: impl Fn(&T) -> bool) -> Option<&T> {
+        self.entries.values().find(|item| filter(item))
+    }
+
+    /// Commands in the order they will be handed out
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.entries.values()
+    }
+
+    pub fn retain(&mut self, filter: impl Fn(&T) -> bool) {
+        self.entries.retain(|_, item| filter(item));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
@@ -0,0 +1,103 @@
+use rmcp::model::CompletionInfo;
+use serde::Deserialize;
+
+/// Materials the terrain tools accept
+pub const TERRAIN_MATERIALS: &[&str] = &[
+    "Grass",
+    "Sand",
+    "Rock",
+    "Snow",
+    "Mud",
+    "Ground",
+    "Slate",
+    "Concrete",
+    "Brick",
+    "Cobblestone",
+    "Ice",
+    "Salt",
+    "Sandstone",
+    "Limestone",
+    "Asphalt",
+    "LeafyGrass",
+    "Pavement",
+    "Water",
+];
+
+/// Modes `sculpt_terrain` accepts
+pub const SCULPT_MODES: &[&str] = &["add", "subtract", "paint", "smooth"];
+
+/// Values a prompt argument is completed from
+#[derive(Clone, Copy)]
+pub enum Completion {
+    /// Dotted instance path like `workspace.Map.Spawn`, as the tools take them
+    InstancePath,
+    TerrainMaterial,
+    SculptMode,
+}
+
+/// Reply of the plugin's `ListInstancePaths`, each path split into instance names
+#[derive(Debug, Deserialize)]
+pub struct InstancePaths {
+    pub paths: Vec<Vec<String>>,
+    /// Set when the place had more instances than were asked for
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Candidates starting with what the user typed so far, ignoring case
+pub fn matching<S: AsRef<str>>(
+    candidates: impl IntoIterator<Item = S>,
+    typed: &str,
+) -> CompletionInfo {
+    let typed = typed.to_lowercase();
+    let values: Vec<String> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.as_ref().to_lowercase().starts_with(&typed))
+        .map(|candidate| candidate.as_ref().to_string())
+        .collect();
+    let total = values.len();
+    let has_more = total > CompletionInfo::MAX_VALUES;
+    CompletionInfo {
+        values: values
+            .into_iter()
+            .take(CompletionInfo::MAX_VALUES)
+            .collect(),
+        total: Some(total as u32),
+        has_more: Some(has_more),
+    }
+}
+
+/// Completes the last segment of an instance path. Only instances one level below what
+/// was typed are offered, so typing `workspace.` lists the children of workspace rather
+/// than everything in it.
+pub fn instance_paths(tree: &[Vec<String>], typed: &str, separator: char) -> CompletionInfo {
+    let depth = typed.matches(separator).count() + 1;
+    let candidates = tree
+        .iter()
+        .filter(|path| path.len() == depth)
+        .map(|path| join_path(path, separator));
+    matching(candidates, typed)
+}
+
+/// Joins instance names into a path. Resource paths percent-encode each name the way the
+/// plugin's `HttpService:UrlEncode` does.
+fn join_path(path: &[String], separator: char) -> String {
+    let names: Vec<String> = if separator == '/' {
+        path.iter().map(|name| percent_encode(name)).collect()
+    } else {
+        path.to_vec()
+    };
+    names.join(&separator.to_string())
+}
+
+fn percent_encode(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
@@ -0,0 +1,213 @@
+use crate::command_queue::Priority;
+use crate::rbx_studio_server::STUDIO_PLUGIN_PORT;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use tokio::time::Duration;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Server settings loaded from `config.toml`. Every field is optional in the file.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Port the Studio plugin connects to
+    pub port: u16,
+    /// Address the plugin endpoint binds to, `0.0.0.0` lets Studio on other machines connect
+    pub host: String,
+    /// PEM certificate chain, serves the plugin endpoint over TLS together with `tls_key`
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key for `tls_cert`
+    pub tls_key: Option<PathBuf>,
+    /// How long a plugin long poll waits for a command before returning empty
+    pub long_poll_timeout_secs: u64,
+    /// How long a tool call waits for Studio to reply before failing
+    pub tool_timeout_secs: u64,
+    /// Per-tool overrides of `tool_timeout_secs`, keyed by tool name
+    pub tool_timeouts: HashMap<String, u64>,
+    /// How long a plugin that took a command may go quiet before the command is
+    /// offered again or failed
+    pub lease_timeout_secs: u64,
+    /// Commands allowed to wait for Studio at once, further tool calls fail right away
+    pub max_queue_depth: usize,
+    /// How long the result of a tool call made with an idempotency key is kept for retries
+    pub idempotency_window_secs: u64,
+    /// Tool results longer than this many bytes are returned a page at a time, 0 returns
+    /// them whole. Results with structured content are always returned whole.
+    pub max_result_bytes: usize,
+    /// Keep queued commands in a journal next to the config file so they survive a restart
+    pub persist_queue: bool,
+    /// Per-tool overrides of the queue priority, `low`, `normal` or `high`
+    pub tool_priorities: HashMap<String, Priority>,
+    /// Tools exposed to MCP clients, all tools are exposed when unset
+    pub enabled_tools: Option<Vec<String>>,
+    /// Tracing filter used when `RUST_LOG` isn't set
+    pub log_level: String,
+    /// Require the plugin to present a shared secret, generated on first run if
+    /// `auth_token` isn't set
+    pub require_auth: bool,
+    /// Shared secret the plugin must send, setting it turns on `require_auth`
+    pub auth_token: Option<String>,
+    /// selene executable `lint_scripts` runs, looked up on `PATH` unless it's a full path
+    pub selene_path: PathBuf,
+    /// selene standard library the scripts are linted against
+    pub selene_std: String,
+    /// StyLua executable `format_scripts` runs, looked up on `PATH` unless it's a full path
+    pub stylua_path: PathBuf,
+    /// Directory `save_scene` snapshots are kept in, `scenes` next to the config file
+    /// when unset
+    pub scenes_dir: Option<PathBuf>,
+    /// Revisions kept of each saved scene, the oldest are deleted past this, 0 keeps them all
+    pub max_scene_revisions: usize,
+    /// Save the workspace as a revision of the `safety-snapshot` scene before
+    /// `clear_workspace`, `load_scene` with `clear_existing` and `delete_instances` run
+    pub safety_snapshots: bool,
+    /// Only let `search_marketplace` and `insert_model` pick assets from verified creators
+    /// unless a call turns it off
+    pub marketplace_verified_only: bool,
+    /// How long marketplace search results are reused for the same query and filters, so
+    /// repeated inserts pick the same asset. 0 searches every time.
+    pub marketplace_cache_secs: u64,
+    /// Open Cloud API key with the asset read and write permissions, `upload_asset` is
+    /// unavailable without it
+    pub open_cloud_api_key: Option<String>,
+    /// User that owns uploaded assets, set either this or `open_cloud_group_id`
+    pub open_cloud_user_id: Option<u64>,
+    /// Group that owns uploaded assets
+    pub open_cloud_group_id: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            port: STUDIO_PLUGIN_PORT,
+            host: "127.0.0.1".to_string(),
+            tls_cert: None,
+            tls_key: None,
+            long_poll_timeout_secs: 15,
+            tool_timeout_secs: 300,
+            tool_timeouts: HashMap::new(),
+            lease_timeout_secs: 45,
+            max_queue_depth: 64,
+            idempotency_window_secs: 600,
+            max_result_bytes: 64 * 1024,
+            persist_queue: false,
+            tool_priorities: HashMap::new(),
+            enabled_tools: None,
+            log_level: "info".to_string(),
+            require_auth: false,
+            auth_token: None,
+            selene_path: PathBuf::from("selene"),
+            selene_std: "roblox".to_string(),
+            stylua_path: PathBuf::from("stylua"),
+            scenes_dir: None,
+            max_scene_revisions: 20,
+            safety_snapshots: true,
+            marketplace_verified_only: true,
+            marketplace_cache_secs: 1800,
+            open_cloud_api_key: None,
+            open_cloud_user_id: None,
+            open_cloud_group_id: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config from `path`, or from the default location if no path is given.
+    /// A missing default config file isn't an error, defaults are used instead.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_config_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+        if !required && !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Could not read config file {}", path.display()))?;
+        let config: Self = toml::from_str(&contents)
+            .map_err(|e| eyre!("Invalid config file {}: {e}", path.display()))?;
+        if config.tls_cert.is_some() != config.tls_key.is_some() {
+            return Err(eyre!("tls_cert and tls_key must be set together"));
+        }
+        if config.open_cloud_user_id.is_some() && config.open_cloud_group_id.is_some() {
+            return Err(eyre!(
+                "Only one of open_cloud_user_id and open_cloud_group_id can be set"
+            ));
+        }
+        Ok(config)
+    }
+
+    pub fn scheme(&self) -> &'static str {
+        if self.tls_cert.is_some() {
+            "https"
+        } else {
+            "http"
+        }
+    }
+
+    /// Base URL other instances on this machine use to reach the plugin endpoint
+    pub fn local_url(&self, port: u16) -> String {
+        format!("{}://127.0.0.1:{port}", self.scheme())
+    }
+
+    pub fn long_poll_timeout(&self) -> Duration {
+        Duration::from_secs(self.long_poll_timeout_secs)
+    }
+
+    pub fn lease_timeout(&self) -> Duration {
+        Duration::from_secs(self.lease_timeout_secs)
+    }
+
+    pub fn marketplace_cache(&self) -> Duration {
+        Duration::from_secs(self.marketplace_cache_secs)
+    }
+
+    pub fn idempotency_window(&self) -> Duration {
+        Duration::from_secs(self.idempotency_window_secs)
+    }
+
+    pub fn tool_timeout(&self, tool: &str) -> Duration {
+        Duration::from_secs(
+            self.tool_timeouts
+                .get(tool)
+                .copied()
+                .unwrap_or(self.tool_timeout_secs),
+        )
+    }
+
+    pub fn tool_priority(&self, tool: &str, default: Priority) -> Priority {
+        self.tool_priorities.get(tool).copied().unwrap_or(default)
+    }
+
+    pub fn is_tool_enabled(&self, name: &str) -> bool {
+        self.enabled_tools
+            .as_ref()
+            .is_none_or(|tools| tools.iter().any(|tool| tool == name))
+    }
+}
+
+/// Directory holding `config.toml` and other files the server keeps between runs
+pub fn config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        let home = env::var_os("HOME")?;
+        Some(Path::new(&home).join("Library/Application Support/RobloxStudioMCP"))
+    } else if cfg!(target_os = "windows") {
+        let app_data = env::var_os("APPDATA")?;
+        Some(Path::new(&app_data).join("RobloxStudioMCP"))
+    } else {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("rbx-studio-mcp"))
+    }
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+}
@@ -0,0 +1,87 @@
+// The script `setup_day_night_cycle` inserts. Like the weather driver it reads its settings
+// from attributes, here on the script itself, so the cycle can be tuned in Studio.
+
+pub const CYCLE_SOURCE: &str = r#"-- Moves Lighting.ClockTime through the day and tints the outdoor light at dawn and dusk.
+-- Lights in instances tagged with LightTag are on between LightsOnAt and LightsOffAt.
+-- DayMinutes, DawnColor, DayColor, DuskColor and NightColor tune the rest.
+
+local CollectionService = game:GetService("CollectionService")
+local Lighting = game:GetService("Lighting")
+local RunService = game:GetService("RunService")
+
+-- Hours each color is reached at, the color between two of them is blended
+local KEYFRAMES = {
+	{ hour = 0, color = "NightColor" },
+	{ hour = 5, color = "NightColor" },
+	{ hour = 6, color = "DawnColor" },
+	{ hour = 8, color = "DayColor" },
+	{ hour = 16.5, color = "DayColor" },
+	{ hour = 18, color = "DuskColor" },
+	{ hour = 19.5, color = "NightColor" },
+	{ hour = 24, color = "NightColor" },
+}
+
+local function outdoorColor(hour: number): Color3
+	for index = 2, #KEYFRAMES do
+		local from, to = KEYFRAMES[index - 1], KEYFRAMES[index]
+		if hour <= to.hour then
+			local alpha = (hour - from.hour) / (to.hour - from.hour)
+			local fromColor = script:GetAttribute(from.color) or Lighting.OutdoorAmbient
+			local toColor = script:GetAttribute(to.color) or Lighting.OutdoorAmbient
+			return fromColor:Lerp(toColor, alpha)
+		end
+	end
+	return script:GetAttribute("NightColor") or Lighting.OutdoorAmbient
+end
+
+local function lightsOn(hour: number): boolean
+	local on = script:GetAttribute("LightsOnAt") or 18
+	local off = script:GetAttribute("LightsOffAt") or 6
+	if on > off then
+		return hour >= on or hour < off
+	end
+	return hour >= on and hour < off
+end
+
+local function setLights(enabled: boolean)
+	local tag = script:GetAttribute("LightTag")
+	if not tag or tag == "" then
+		return
+	end
+	for _, instance in CollectionService:GetTagged(tag) do
+		if instance:IsA("Light") then
+			instance.Enabled = enabled
+		end
+		for _, descendant in instance:GetDescendants() do
+			if descendant:IsA("Light") then
+				descendant.Enabled = enabled
+			end
+		end
+	end
+end
+
+Lighting.ClockTime = script:GetAttribute("StartTime") or Lighting.ClockTime
+local lit = lightsOn(Lighting.ClockTime)
+setLights(lit)
+
+local tag = script:GetAttribute("LightTag")
+if tag and tag ~= "" then
+	-- Lights added while the game runs start in the right state
+	CollectionService:GetInstanceAddedSignal(tag):Connect(function()
+		setLights(lit)
+	end)
+end
+
+RunService.Heartbeat:Connect(function(deltaTime)
+	local minutes = math.max(script:GetAttribute("DayMinutes") or 12, 0.1)
+	local hour = (Lighting.ClockTime + deltaTime * 24 / (minutes * 60)) % 24
+	Lighting.ClockTime = hour
+	Lighting.OutdoorAmbient = outdoorColor(hour)
+
+	local shouldLight = lightsOn(hour)
+	if shouldLight ~= lit then
+		lit = shouldLight
+		setLights(lit)
+	end
+end)
+"#;
@@ -0,0 +1,52 @@
+// Formatting for `format_scripts`. The sources come from the plugin and are formatted one
+// at a time with the StyLua command line tool, so a script it can't parse only fails
+// itself.
+
+use color_eyre::eyre::{Result, WrapErr};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::Duration;
+
+/// How long StyLua may take over one script
+const STYLUA_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Formats `source` with StyLua. Fails if StyLua can't be run at all, the inner error is
+/// why it rejected this script, usually a syntax error.
+pub async fn format(stylua: &Path, source: &str) -> Result<Result<String, String>> {
+    let mut child = Command::new(stylua)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .wrap_err_with(|| {
+            format!(
+                "Couldn't run {}. Install StyLua from https://github.com/JohnnyMorganz/StyLua or set stylua_path in config.toml",
+                stylua.display()
+            )
+        })?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = source.as_bytes().to_vec();
+    // Written while the output is read so a large script can't fill both pipes
+    let write = async move {
+        let written = stdin.write_all(&input).await;
+        drop(stdin);
+        written
+    };
+    let run = async { tokio::join!(write, child.wait_with_output()) };
+    let Ok((written, output)) = tokio::time::timeout(STYLUA_TIMEOUT, run).await else {
+        return Ok(Err(format!(
+            "StyLua didn't finish within {STYLUA_TIMEOUT:?}"
+        )));
+    };
+    let output = output?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(Err(stderr.trim().to_string()));
+    }
+    written?;
+    Ok(String::from_utf8(output.stdout).map_err(|e| e.to_string()))
+}
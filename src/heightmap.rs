@@ -0,0 +1,81 @@
+// Heightmap images for `import_heightmap`. Images are decoded and resampled here, so the
+// plugin only gets one height per terrain column and never handles image formats.
+
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use std::fmt::Write;
+
+/// Decodes a PNG or EXR image into heights from 0 at the bottom of the region to 1 at the
+/// top, resampled to `columns` by `rows`. Image x runs along world X and image y along Z.
+pub fn decode(bytes: &[u8], columns: usize, rows: usize) -> Result<Vec<f32>> {
+    let image = image::load_from_memory(bytes)
+        .wrap_err("Couldn't decode the heightmap, it must be a PNG or EXR image")?
+        .to_luma32f();
+    let width = image.width() as usize;
+    if width == 0 || image.height() == 0 {
+        return Err(eyre!("The heightmap image is empty"));
+    }
+    let mut pixels: Vec<f32> = image
+        .into_raw()
+        .into_iter()
+        .map(|value| if value.is_finite() { value } else { 0.0 })
+        .collect();
+
+    // EXR heightmaps usually hold heights in world units rather than 0 to 1
+    let low = pixels.iter().copied().fold(f32::INFINITY, f32::min);
+    let high = pixels.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if low < 0.0 || high > 1.0 {
+        let range = (high - low).max(f32::EPSILON);
+        for pixel in &mut pixels {
+            *pixel = (*pixel - low) / range;
+        }
+    }
+
+    let resized_rows: Vec<Vec<f32>> = pixels
+        .chunks(width)
+        .map(|row| resample_line(row, columns))
+        .collect();
+    let mut heights = vec![0.0; columns * rows];
+    for column in 0..columns {
+        let line: Vec<f32> = resized_rows.iter().map(|row| row[column]).collect();
+        for (row, value) in resample_line(&line, rows).into_iter().enumerate() {
+            heights[row * columns + column] = value;
+        }
+    }
+    Ok(heights)
+}
+
+/// Resamples a line of values to `len`, averaging when shrinking and interpolating when
+/// growing
+fn resample_line(line: &[f32], len: usize) -> Vec<f32> {
+    let count = line.len();
+    (0..len)
+        .map(|index| {
+            if count >= len {
+                let start = index * count / len;
+                let end = ((index + 1) * count / len).max(start + 1);
+                return line[start..end].iter().sum::<f32>() / (end - start) as f32;
+            }
+            // The first and last columns sit on the edges of the image
+            let position = if len > 1 {
+                index as f32 * (count - 1) as f32 / (len - 1) as f32
+            } else {
+                0.0
+            };
+            let below = position.floor() as usize;
+            let above = (below + 1).min(count - 1);
+            let t = position - below as f32;
+            line[below] * (1.0 - t) + line[above] * t
+        })
+        .collect()
+}
+
+/// Packs heights as four hex digits each, 0000 at the bottom of the region and ffff at
+/// the top, which the plugin reads back with `string.sub`
+pub fn encode(heights: &[f32]) -> String {
+    let mut encoded = String::with_capacity(heights.len() * 4);
+    for height in heights {
+        let value = (height.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16;
+        let _ = write!(encoded, "{value:04x}");
+    }
+    encoded
+}
@@ -130,8 +130,24 @@ pub fn install_to_config(
     Ok(name.to_string())
 }
 
+/// The Studio plugin built into this binary
+#[cfg(feature = "bundle-plugin")]
+fn plugin_bytes() -> Result<&'static [u8]> {
+    Ok(include_bytes!(concat!(
+        env!("OUT_DIR"),
+        "/MCPStudioPlugin.rbxm"
+    )))
+}
+
+#[cfg(not(feature = "bundle-plugin"))]
+fn plugin_bytes() -> Result<&'static [u8]> {
+    Err(eyre!(
+        "This build doesn't include the Studio plugin. Rebuild with the bundle-plugin feature, or build plugin/ with `rojo build plugin -o MCPStudioPlugin.rbxm` and copy it to your Studio Plugins folder"
+    ))
+}
+
 async fn install_internal() -> Result<String> {
-    let plugin_bytes = include_bytes!(concat!(env!("OUT_DIR"), "/MCPStudioPlugin.rbxm"));
+    let plugin_bytes = plugin_bytes()?;
     let studio = RobloxStudio::locate()?;
     let plugins = studio.plugins_path();
     if let Err(err) = fs::create_dir(plugins) {
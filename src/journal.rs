@@ -0,0 +1,77 @@
+use crate::config::config_dir;
+use crate::rbx_studio_server::{PackedState, ToolArguments};
+use color_eyre::eyre::{OptionExt, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::time::Duration;
+
+const JOURNAL_FILE_NAME: &str = "queue.json";
+/// How often the queue is compared against the journal on disk
+const JOURNAL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Commands that hadn't finished when the journal was written
+#[derive(Deserialize, Serialize)]
+pub struct Journal {
+    /// Commands no plugin had taken yet
+    pub queued: Vec<ToolArguments>,
+    /// Commands a plugin took but hadn't answered
+    pub running: Vec<ToolArguments>,
+}
+
+pub fn journal_path() -> Result<PathBuf> {
+    let dir = config_dir().ok_or_eyre("Could not find a config directory for the queue journal")?;
+    Ok(dir.join(JOURNAL_FILE_NAME))
+}
+
+/// Reads the journal left by the previous run, if there is one.
+pub async fn load(path: &Path) -> Result<Option<Journal>> {
+    let contents = match fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e).wrap_err_with(|| format!("Could not read {}", path.display()));
+        }
+    };
+    let journal = serde_json::from_str(&contents)
+        .wrap_err_with(|| format!("Invalid queue journal {}", path.display()))?;
+    Ok(Some(journal))
+}
+
+async fn save(path: &Path, contents: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    // Write next to the journal and rename so a crash never leaves half a file behind
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, contents).await?;
+    fs::rename(&temp, path).await?;
+    Ok(())
+}
+
+/// Keeps the journal at `path` in step with the queue until the server exits.
+pub async fn journal_loop(state: PackedState, path: PathBuf) {
+    let mut last_written = None;
+    let mut interval = tokio::time::interval(JOURNAL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let journal = state.call(|state| state.journal()).await;
+        let contents = match serde_json::to_string(&journal) {
+            Ok(contents) => contents,
+            Err(e) => {
+                tracing::error!("Failed to serialize the queue journal: {e}");
+                continue;
+            }
+        };
+        if last_written.as_ref() == Some(&contents) {
+            continue;
+        }
+        match save(&path, &contents).await {
+            Ok(()) => last_written = Some(contents),
+            Err(e) => tracing::error!(
+                "Failed to write the queue journal {}: {e:?}",
+                path.display()
+            ),
+        }
+    }
+}
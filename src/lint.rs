@@ -0,0 +1,146 @@
+// Linting for `lint_scripts`. The sources come from the plugin and are checked with the
+// selene command line tool, which has no library interface.
+
+use crate::code_search::ScriptSource;
+use crate::tool_results::{LintDiagnostic, LintScriptsResult, ScriptDiagnostics};
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+/// How long selene may take over all the scripts of a place
+const SELENE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// One line of selene's `json2` output
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum Output {
+    Diagnostic(Diagnostic),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct Diagnostic {
+    severity: String,
+    code: String,
+    message: String,
+    primary_label: Label,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    filename: String,
+    span: Span,
+}
+
+/// Lines and columns count from 0
+#[derive(Debug, Deserialize)]
+struct Span {
+    start_line: u32,
+    start_column: u32,
+    end_line: u32,
+    end_column: u32,
+}
+
+/// Runs selene over `scripts` with the standard library `std`, grouping what it reports
+/// by script path
+pub async fn lint(selene: &Path, std: &str, scripts: &[ScriptSource]) -> Result<LintScriptsResult> {
+    // Scripts are written under numbered names since instance paths aren't file names
+    let dir = std::env::temp_dir().join(format!("rbx-studio-mcp-lint-{}", Uuid::new_v4()));
+    tokio::fs::create_dir_all(&dir).await?;
+    let result = lint_in(&dir, selene, std, scripts).await;
+    if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+        tracing::warn!("Couldn't remove {}: {e}", dir.display());
+    }
+    result
+}
+
+async fn lint_in(
+    dir: &Path,
+    selene: &Path,
+    std: &str,
+    scripts: &[ScriptSource],
+) -> Result<LintScriptsResult> {
+    tokio::fs::write(dir.join("selene.toml"), format!("std = {std:?}\n")).await?;
+    for (index, script) in scripts.iter().enumerate() {
+        tokio::fs::write(dir.join(format!("{index}.luau")), &script.source).await?;
+    }
+
+    let child = Command::new(selene)
+        .args(["--display-style", "json2", "--no-summary", "."])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .wrap_err_with(|| {
+            format!(
+                "Couldn't run {}. Install selene from https://github.com/Kampfkarren/selene or set selene_path in config.toml",
+                selene.display()
+            )
+        })?;
+    let output = tokio::time::timeout(SELENE_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| eyre!("selene didn't finish within {SELENE_TIMEOUT:?}"))??;
+
+    let mut grouped: BTreeMap<usize, Vec<LintDiagnostic>> = BTreeMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(Output::Diagnostic(diagnostic)) = serde_json::from_str(line) else {
+            continue;
+        };
+        let Some(index) = script_index(&diagnostic.primary_label.filename) else {
+            continue;
+        };
+        let span = &diagnostic.primary_label.span;
+        grouped.entry(index).or_default().push(LintDiagnostic {
+            severity: diagnostic.severity.to_lowercase(),
+            code: diagnostic.code,
+            message: diagnostic.message,
+            line: span.start_line + 1,
+            column: span.start_column + 1,
+            end_line: span.end_line + 1,
+            end_column: span.end_column + 1,
+        });
+    }
+    // selene exits with an error when it finds problems, so only a silent failure counts
+    if grouped.is_empty() && !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre!("selene failed: {}", stderr.trim()));
+    }
+
+    let count = |severity: &str| {
+        grouped
+            .values()
+            .flatten()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+    Ok(LintScriptsResult {
+        success: true,
+        scripts_linted: scripts.len(),
+        errors: count("error"),
+        warnings: count("warning"),
+        scripts: grouped
+            .into_iter()
+            .filter_map(|(index, diagnostics)| {
+                let script = scripts.get(index)?;
+                Some(ScriptDiagnostics {
+                    path: script.path.clone(),
+                    diagnostics,
+                })
+            })
+            .collect(),
+    })
+}
+
+/// Index of the script a file written by `lint_in` holds
+fn script_index(filename: &str) -> Option<usize> {
+    let name = Path::new(filename).file_name()?.to_str()?;
+    name.strip_suffix(".luau")?.parse().ok()
+}
@@ -0,0 +1,306 @@
+// A small Luau tokenizer for the tools that read script sources on the server. It knows
+// enough of the syntax to skip comments and strings and find names, not to parse.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Name(String),
+    /// String literal, or a piece of an interpolated string between its expressions.
+    /// `None` when it has escapes or interpolation so its value isn't known.
+    Str(Option<String>),
+    Number(String),
+    Symbol(char),
+}
+
+/// Where a token is in the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    /// Line the token starts on, counting from 1
+    pub line: u32,
+    /// Byte offsets of the token
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Words that can't be used as names
+pub const KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "if", "in", "local",
+    "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// Whether `name` can be used as a variable or field name without quoting
+pub fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&name)
+}
+
+/// Splits Luau source into tokens. Comments are dropped and operators come out one
+/// character at a time. The expressions in an interpolated string come out as tokens
+/// between the pieces of the string around them.
+pub fn tokenize(source: &str) -> Vec<(Token, Span)> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut index = 0;
+    // Braces open in the code, and how many were open where each interpolated string
+    // that is still going started an expression
+    let mut depth = 0;
+    let mut interpolations: Vec<usize> = Vec::new();
+    while index < bytes.len() {
+        let start = index;
+        let start_line = line;
+        let token = match bytes[index] {
+            b'\n' => {
+                line += 1;
+                index += 1;
+                None
+            }
+            byte if byte.is_ascii_whitespace() => {
+                index += 1;
+                None
+            }
+            b'-' if bytes.get(index + 1) == Some(&b'-') => {
+                index += 2;
+                if let Some(level) = long_bracket(bytes, index) {
+                    index = long_bracket_end(bytes, index, level, &mut line).1;
+                } else {
+                    while index < bytes.len() && bytes[index] != b'\n' {
+                        index += 1;
+                    }
+                }
+                None
+            }
+            b'[' if long_bracket(bytes, index).is_some() => {
+                let level = long_bracket(bytes, index).unwrap_or_default();
+                let (content, end) = long_bracket_end(bytes, index, level, &mut line);
+                index = end;
+                Some(Token::Str(Some(content)))
+            }
+            quote @ (b'"' | b'\'' | b'`') => {
+                let (token, end, opens) = string(bytes, index + 1, quote, false, &mut line);
+                index = end;
+                if opens {
+                    interpolations.push(depth);
+                }
+                Some(token)
+            }
+            b'}' if interpolations.last() == Some(&depth) => {
+                interpolations.pop();
+                let (token, end, opens) = string(bytes, index + 1, b'`', true, &mut line);
+                index = end;
+                if opens {
+                    interpolations.push(depth);
+                }
+                Some(token)
+            }
+            byte if byte.is_ascii_alphabetic() || byte == b'_' => {
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'_')
+                {
+                    index += 1;
+                }
+                Some(Token::Name(source[start..index].to_string()))
+            }
+            byte if byte.is_ascii_digit() => {
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'.')
+                {
+                    index += 1;
+                }
+                Some(Token::Number(source[start..index].to_string()))
+            }
+            byte => {
+                match byte {
+                    b'{' => depth += 1,
+                    b'}' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                index += 1;
+                Some(Token::Symbol(byte as char))
+            }
+        };
+        if let Some(token) = token {
+            let span = Span {
+                line: start_line,
+                start,
+                end: index.min(bytes.len()),
+            };
+            tokens.push((token, span));
+        }
+    }
+    tokens
+}
+
+/// Reads a string from `start`, just past its opening quote or the `}` ending an
+/// interpolated expression, to its closing quote or the `{` starting the next
+/// expression. Returns its token, the index past it and whether an expression starts
+/// there.
+fn string(
+    bytes: &[u8],
+    start: usize,
+    quote: u8,
+    interpolated: bool,
+    line: &mut u32,
+) -> (Token, usize, bool) {
+    let mut index = start;
+    let mut known = !interpolated;
+    // A string left open ends at the line break, like Luau reports it
+    while index < bytes.len() && bytes[index] != quote && bytes[index] != b'\n' {
+        match bytes[index] {
+            b'\\' => {
+                known = false;
+                if bytes.get(index + 1) == Some(&b'\n') {
+                    *line += 1;
+                }
+                index += 1;
+            }
+            b'{' if quote == b'`' => return (Token::Str(None), index + 1, true),
+            _ => {}
+        }
+        index += 1;
+    }
+    let content = String::from_utf8_lossy(&bytes[start..index.min(bytes.len())]);
+    let value = known.then(|| content.into_owned());
+    if bytes.get(index) == Some(&quote) {
+        index += 1;
+    }
+    (Token::Str(value), index, false)
+}
+
+/// Level of a long bracket like `[==[` starting at `index`, the number of `=`
+fn long_bracket(bytes: &[u8], index: usize) -> Option<usize> {
+    if bytes.get(index) != Some(&b'[') {
+        return None;
+    }
+    let level = bytes[index + 1..]
+        .iter()
+        .take_while(|&&byte| byte == b'=')
+        .count();
+    (bytes.get(index + 1 + level) == Some(&b'[')).then_some(level)
+}
+
+/// Content of the long bracket string or comment opening at `index`, and the index just
+/// past its closing bracket
+fn long_bracket_end(bytes: &[u8], index: usize, level: usize, line: &mut u32) -> (String, usize) {
+    let content_start = index + level + 2;
+    let close = format!("]{}]", "=".repeat(level));
+    let mut end = content_start;
+    while end < bytes.len() && !bytes[end..].starts_with(close.as_bytes()) {
+        if bytes[end] == b'\n' {
+            *line += 1;
+        }
+        end += 1;
+    }
+    let content = String::from_utf8_lossy(&bytes[content_start.min(end)..end]).into_owned();
+    (content, (end + close.len()).min(bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        tokenize(source)
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    fn name(name: &str) -> Token {
+        Token::Name(name.to_string())
+    }
+
+    fn string(value: &str) -> Token {
+        Token::Str(Some(value.to_string()))
+    }
+
+    #[test]
+    fn long_strings_keep_inner_brackets() {
+        assert_eq!(
+            tokens("x = [==[a ]] b ]=] c]==] y"),
+            [
+                name("x"),
+                Token::Symbol('='),
+                string("a ]] b ]=] c"),
+                name("y")
+            ]
+        );
+        assert_eq!(tokens("[[line\nbreak]]"), [string("line\nbreak")]);
+    }
+
+    #[test]
+    fn long_comments_end_at_their_own_level() {
+        assert_eq!(tokens("--[==[ a ]] still comment ]==] b"), [name("b")]);
+        // Comments don't nest, the first closing bracket ends the outer one
+        assert_eq!(
+            tokens("--[[ a --[[ b ]] c ]]"),
+            [name("c"), Token::Symbol(']'), Token::Symbol(']')]
+        );
+        assert_eq!(tokens("--[= not long\nd"), [name("d")]);
+    }
+
+    #[test]
+    fn comments_and_strings_count_lines() {
+        let spans: Vec<u32> = tokenize("--[[\n\n]] a [[\n]] b\n-- c\nd")
+            .into_iter()
+            .map(|(_, span)| span.line)
+            .collect();
+        assert_eq!(spans, [3, 3, 4, 6]);
+    }
+
+    #[test]
+    fn escapes_leave_the_value_unknown() {
+        assert_eq!(
+            tokens(r#"a "x\"y" b 'it\'s' c "plain""#),
+            [
+                name("a"),
+                Token::Str(None),
+                name("b"),
+                Token::Str(None),
+                name("c"),
+                string("plain")
+            ]
+        );
+        assert_eq!(tokens(r#""end\\" d"#), [Token::Str(None), name("d")]);
+        assert_eq!(tokens("`raw` `\\{n}`"), [string("raw"), Token::Str(None)]);
+    }
+
+    #[test]
+    fn unfinished_strings_end_at_the_line() {
+        assert_eq!(tokens("\"open\nnext"), [string("open"), name("next")]);
+        assert_eq!(tokens("[[never closed"), [string("never closed")]);
+    }
+
+    #[test]
+    fn interpolated_expressions_are_tokens() {
+        assert_eq!(
+            tokens("`n = {count + 1}!` x"),
+            [
+                Token::Str(None),
+                name("count"),
+                Token::Symbol('+'),
+                Token::Number("1".to_string()),
+                Token::Str(None),
+                name("x"),
+            ]
+        );
+        // Braces of a table in the expression don't end it, nor do nested strings
+        assert_eq!(
+            tokens("`{ {a = 1} } {`in {b}`}` c"),
+            [
+                Token::Str(None),
+                Token::Symbol('{'),
+                name("a"),
+                Token::Symbol('='),
+                Token::Number("1".to_string()),
+                Token::Symbol('}'),
+                Token::Str(None),
+                Token::Str(None),
+                name("b"),
+                Token::Str(None),
+                Token::Str(None),
+                name("c"),
+            ]
+        );
+    }
+}
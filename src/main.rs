@@ -1,16 +1,60 @@
-use axum::routing::{get, post};
+use axum::extract::DefaultBodyLimit;
+use axum::middleware;
+use axum::routing::{delete, get, post};
+use axum::Extension;
 use clap::Parser;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
+use config::Config;
 use rbx_studio_server::*;
 use rmcp::ServiceExt;
 use std::io;
-use std::net::Ipv4Addr;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tls::TlsListener;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
 use tracing_subscriber::{self, EnvFilter};
+mod ambient_zones;
+mod api_dump;
+mod atmosphere_presets;
+mod auth;
+mod code_search;
+mod command_queue;
+mod completion;
+mod config;
+mod day_night;
 mod error;
+mod format;
+mod heightmap;
 mod install;
+mod journal;
+mod lint;
+mod luau_tokens;
+mod marketplace;
+mod mesh_files;
+mod model_files;
+mod module_graph;
+mod open_cloud;
+mod particle_presets;
+mod post_effect_presets;
+mod prompts;
 mod rbx_studio_server;
+mod rename;
+mod scenes;
+mod script_patch;
+mod script_templates;
+mod sky_presets;
+mod sse;
+mod streamable_http;
+mod structures;
+mod terrain_export;
+mod terrain_stamps;
+mod terrain_tiles;
+mod test_runner;
+mod tls;
+mod tool_results;
+mod viewport_capture;
+mod weather;
 
 /// Simple MCP proxy for Roblox Studio
 /// Run without arguments to install the plugin
@@ -20,64 +64,212 @@ struct Args {
     /// Run as MCP server on stdio
     #[arg(short, long)]
     stdio: bool,
+
+    /// Serve MCP over SSE (`/sse` and `/message`) on the plugin HTTP port
+    #[arg(long)]
+    sse: bool,
+
+    /// Serve MCP over Streamable HTTP (`/mcp`) on the plugin HTTP port
+    #[arg(long)]
+    http: bool,
+
+    /// Port the Studio plugin connects to, overrides the config file
+    #[arg(short, long, env = "ROBLOX_MCP_PORT")]
+    port: Option<u16>,
+
+    /// Path to a `config.toml`, defaults to the one in the user config directory
+    #[arg(short, long, env = "ROBLOX_MCP_CONFIG")]
+    config: Option<PathBuf>,
+}
+
+/// How many ports after the requested one to try when it is taken by another program
+const PORT_FALLBACK_ATTEMPTS: u16 = 10;
+
+/// Binds the plugin port, moving on to the next ports if the requested one is taken by
+/// something other than this server. Returns `None` for the listener when another
+/// instance already owns the port, in which case this one proxies through it.
+async fn bind_plugin_port(config: &Config) -> Result<(Option<TcpListener>, u16)> {
+    let port = config.port;
+    for candidate in port..=port.saturating_add(PORT_FALLBACK_ATTEMPTS) {
+        match TcpListener::bind((config.host.as_str(), candidate)).await {
+            Ok(listener) => return Ok((Some(listener), candidate)),
+            Err(_) if is_mcp_server(config, candidate).await => return Ok((None, candidate)),
+            Err(e) => tracing::warn!("Port {candidate} is unavailable: {e}"),
+        }
+    }
+    Err(eyre!(
+        "No free port found between {port} and {}",
+        port.saturating_add(PORT_FALLBACK_ATTEMPTS)
+    ))
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
+    let args = Args::parse();
+    let mut config = Config::load(args.config.as_deref())?;
+    if let Some(port) = args.port {
+        config.port = port;
+    }
+
     tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.log_level)),
+        )
         .with_writer(io::stderr)
         .with_target(false)
         .with_thread_ids(true)
         .init();
 
-    let args = Args::parse();
-    if !args.stdio {
+    if !args.stdio && !args.sse && !args.http {
         return install::install().await;
     }
 
     tracing::debug!("Debug MCP tracing enabled");
 
-    let server_state = Arc::new(Mutex::new(AppState::new()));
+    if config.require_auth && config.auth_token.is_none() {
+        config.auth_token = Some(auth::load_or_create_token()?);
+    }
+    if config.auth_token.is_some() {
+        tracing::info!("Studio plugin requests must present the configured auth token");
+    } else if (args.sse || args.http) && !auth::is_loopback(&config.host) {
+        return Err(eyre!(
+            "Serving MCP over HTTP on {} needs require_auth or auth_token, anyone on the network could call tools otherwise",
+            config.host
+        ));
+    }
+
+    let server_state = PackedState::spawn(AppState::new());
 
     let (close_tx, close_rx) = tokio::sync::oneshot::channel();
+    let http_shutdown = CancellationToken::new();
 
-    let listener =
-        tokio::net::TcpListener::bind((Ipv4Addr::new(127, 0, 0, 1), STUDIO_PLUGIN_PORT)).await;
+    let tls_acceptor = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => Some(tls::load_acceptor(cert, key)?),
+        _ => None,
+    };
+
+    let (listener, port) = bind_plugin_port(&config).await?;
+    if port != config.port {
+        tracing::warn!(
+            "Port {} is taken, the Studio plugin must connect to {port}",
+            config.port
+        );
+        config.port = port;
+    }
+    let config = Arc::new(config);
+    let base_url = format!("{}://{}:{port}", config.scheme(), config.host);
 
-    let server_state_clone = Arc::clone(&server_state);
-    let server_handle = if let Ok(listener) = listener {
-        let app = axum::Router::new()
+    let server_state_clone = server_state.clone();
+    let server_handle = if let Some(listener) = listener {
+        if config.persist_queue {
+            let path = journal::journal_path()?;
+            match journal::load(&path).await {
+                Ok(Some(journal)) => {
+                    server_state
+                        .call(|state| state.restore_journal(journal))
+                        .await
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Ignoring the queue journal: {e:?}"),
+            }
+            tokio::spawn(journal::journal_loop(server_state.clone(), path));
+        }
+        let mut app = axum::Router::new()
             .route("/request", get(request_handler))
             .route("/response", post(response_handler))
+            .route("/progress", post(progress_handler))
+            .route("/changes", post(changes_handler))
+            .route("/log", post(log_handler))
             .route("/proxy", post(proxy_handler))
+            .route("/ws", get(ws_handler))
+            .route("/register", post(register_handler))
+            .route("/sessions", get(sessions_handler))
+            .route("/queue", get(queue_handler))
+            .route("/queue/{id}", delete(cancel_handler))
+            .route("/chunk/{id}/{index}", get(chunk_handler))
+            .route(
+                "/capture",
+                post(capture_upload_handler)
+                    .layer(DefaultBodyLimit::max(viewport_capture::MAX_CAPTURE_BYTES)),
+            )
+            .route("/capture/{id}", get(capture_download_handler))
+            .route(
+                "/scene/{name}",
+                get(scene_download_handler)
+                    .post(scene_upload_handler)
+                    .layer(DefaultBodyLimit::max(MAX_SCENE_BYTES)),
+            )
+            .route_layer(middleware::from_fn(auth::require_token))
+            .route("/health", get(health_handler))
+            .layer(Extension(Arc::clone(&config)))
             .with_state(server_state_clone);
-        tracing::info!("This MCP instance is HTTP server listening on {STUDIO_PLUGIN_PORT}");
+        let mut mcp = axum::Router::new();
+        if args.sse {
+            mcp = mcp.merge(sse::router(
+                server_state.clone(),
+                Arc::clone(&config),
+                http_shutdown.clone(),
+            ));
+            tracing::info!("Serving MCP over SSE at {base_url}/sse");
+        }
+        if args.http {
+            mcp = mcp.merge(streamable_http::router(
+                server_state.clone(),
+                Arc::clone(&config),
+                http_shutdown.clone(),
+            ));
+            tracing::info!("Serving MCP over Streamable HTTP at {base_url}/mcp");
+        }
+        // MCP clients can run any tool, so they need the token as much as the plugin does
+        app = app.merge(
+            mcp.route_layer(middleware::from_fn(auth::require_token))
+                .layer(Extension(Arc::clone(&config))),
+        );
+        tracing::info!("This MCP instance is HTTP server listening on {base_url}");
         tokio::spawn(async {
-            axum::serve(listener, app)
-                .with_graceful_shutdown(async move {
-                    _ = close_rx.await;
-                })
-                .await
-                .unwrap();
+            let shutdown = async move {
+                _ = close_rx.await;
+            };
+            match tls_acceptor {
+                Some(acceptor) => axum::serve(TlsListener::new(listener, acceptor), app)
+                    .with_graceful_shutdown(shutdown)
+                    .await
+                    .unwrap(),
+                None => axum::serve(listener, app)
+                    .with_graceful_shutdown(shutdown)
+                    .await
+                    .unwrap(),
+            }
         })
     } else {
+        if args.sse || args.http {
+            return Err(eyre!(
+                "Port {port} is busy, HTTP transports can't be served by this instance"
+            ));
+        }
         tracing::info!("This MCP instance will use proxy since port is busy");
+        server_state.call(|state| state.set_proxied()).await;
+        let config = Arc::clone(&config);
         tokio::spawn(async move {
-            dud_proxy_loop(server_state_clone, close_rx).await;
+            dud_proxy_loop(server_state_clone, config, close_rx).await;
         })
     };
 
-    // Create an instance of our counter router
-    let service = RBXStudioServer::new(Arc::clone(&server_state))
-        .serve(rmcp::transport::stdio())
-        .await
-        .inspect_err(|e| {
-            tracing::error!("serving error: {:?}", e);
-        })?;
-    service.waiting().await?;
+    if args.stdio {
+        // Create an instance of our counter router
+        let service = RBXStudioServer::new(server_state.clone(), Arc::clone(&config))
+            .serve(rmcp::transport::stdio())
+            .await
+            .inspect_err(|e| {
+                tracing::error!("serving error: {:?}", e);
+            })?;
+        service.waiting().await?;
+    } else {
+        tokio::signal::ctrl_c().await?;
+    }
 
+    http_shutdown.cancel();
     close_tx.send(()).ok();
     tracing::info!("Waiting for web server to gracefully shutdown");
     server_handle.await.ok();
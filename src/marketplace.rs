@@ -0,0 +1,149 @@
+// `search_marketplace` candidates come from the plugin, which can only see each asset's
+// name and creator. Favorites and thumbnails are filled in here from Roblox's public web
+// APIs, which Studio plugins aren't allowed to call. Both are best effort: an asset keeps
+// what the plugin found if Roblox can't be reached. Plugins can't search audio at all, so
+// `insert_audio` searches the Creator Store here too.
+
+use crate::tool_results::MarketplaceAsset;
+use color_eyre::eyre::Result;
+use futures::future::join_all;
+use serde::Deserialize;
+use std::time::Duration;
+
+const THUMBNAILS_URL: &str = "https://thumbnails.roblox.com/v1/assets";
+const FAVORITES_URL: &str = "https://catalog.roblox.com/v1/favorites/assets";
+const STORE_URL: &str = "https://create.roblox.com/store/asset";
+const STORE_SEARCH_URL: &str = "https://apis.roblox.com/toolbox-service/v2/assets:search";
+const THUMBNAIL_SIZE: &str = "420x420";
+/// How long the lookups may hold up the search results
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize)]
+struct Thumbnails {
+    data: Vec<Thumbnail>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Thumbnail {
+    target_id: u64,
+    state: String,
+    image_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoreSearch {
+    #[serde(default)]
+    creator_store_assets: Vec<StoreAsset>,
+}
+
+#[derive(Deserialize)]
+struct StoreAsset {
+    asset: StoreAssetInfo,
+}
+
+#[derive(Deserialize)]
+struct StoreAssetInfo {
+    id: u64,
+    name: String,
+}
+
+/// ID and name of the first Creator Store audio asset for `query`, `None` if there are no
+/// results
+pub async fn search_audio(query: &str) -> Result<Option<(u64, String)>> {
+    let client = reqwest::Client::builder().timeout(LOOKUP_TIMEOUT).build()?;
+    let url = reqwest::Url::parse_with_params(
+        STORE_SEARCH_URL,
+        [
+            ("searchCategoryType", "Audio"),
+            ("query", query),
+            ("maxPageSize", "1"),
+        ],
+    )?;
+    let found: StoreSearch = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(found
+        .creator_store_assets
+        .into_iter()
+        .next()
+        .map(|found| (found.asset.id, found.asset.name)))
+}
+
+/// Page of the asset on the Creator Store
+pub fn store_url(asset_id: u64) -> String {
+    format!("{STORE_URL}/{asset_id}")
+}
+
+/// Fills in the favorites and thumbnail of each asset
+pub async fn enrich(assets: &mut [MarketplaceAsset]) {
+    if assets.is_empty() {
+        return;
+    }
+    let client = match reqwest::Client::builder().timeout(LOOKUP_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::debug!("No HTTP client for marketplace lookups: {e}");
+            return;
+        }
+    };
+    let ids: Vec<u64> = assets.iter().map(|asset| asset.asset_id).collect();
+    let (thumbnails, favorites) = tokio::join!(
+        thumbnails(&client, &ids),
+        join_all(ids.iter().map(|&id| favorites(&client, id)))
+    );
+    for (asset, favorites) in assets.iter_mut().zip(favorites) {
+        asset.favorites = favorites;
+        asset.thumbnail_url = thumbnails
+            .iter()
+            .find(|thumbnail| {
+                thumbnail.target_id == asset.asset_id && thumbnail.state == "Completed"
+            })
+            .and_then(|thumbnail| thumbnail.image_url.clone());
+    }
+}
+
+async fn thumbnails(client: &reqwest::Client, ids: &[u64]) -> Vec<Thumbnail> {
+    let ids = ids.iter().map(u64::to_string).collect::<Vec<_>>().join(",");
+    let url = format!("{THUMBNAILS_URL}?assetIds={ids}&size={THUMBNAIL_SIZE}&format=Png");
+    let request = client.get(url);
+    let fetched = async {
+        request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Thumbnails>()
+            .await
+    };
+    match fetched.await {
+        Ok(thumbnails) => thumbnails.data,
+        Err(e) => {
+            tracing::debug!("Could not fetch marketplace thumbnails: {e}");
+            Vec::new()
+        }
+    }
+}
+
+async fn favorites(client: &reqwest::Client, id: u64) -> Option<u64> {
+    let request = client.get(format!("{FAVORITES_URL}/{id}/count"));
+    let fetched = async {
+        request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<u64>()
+            .await
+    };
+    match fetched.await {
+        Ok(count) => Some(count),
+        Err(e) => {
+            tracing::debug!("Could not fetch favorites of asset {id}: {e}");
+            None
+        }
+    }
+}
@@ -0,0 +1,226 @@
+// 3D files read by `import_3d_file`. Open Cloud takes FBX and glTF as they are, but not
+// OBJ, so OBJ files are read here into one triangle mesh. That mesh is either sent to the
+// plugin to build an EditableMesh from, or written out as a binary glTF to upload.
+
+use color_eyre::eyre::{eyre, Result};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Most vertices and triangles Studio lets one EditableMesh have
+pub const MAX_EDITABLE_VERTICES: usize = 60_000;
+pub const MAX_EDITABLE_TRIANGLES: usize = 20_000;
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+const GLTF_FLOAT: u32 = 5126;
+const GLTF_UNSIGNED_INT: u32 = 5125;
+const GLTF_ARRAY_BUFFER: u32 = 34962;
+const GLTF_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// A triangle mesh with one position, and UV when the file has them, per vertex. UVs
+/// start at the top left of the texture like Roblox and glTF expect.
+#[derive(Debug, Serialize)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+/// Looks up an OBJ index, which counts from 1 or back from the end when negative
+fn obj_index(index: &str, count: usize, line: usize) -> Result<usize> {
+    let index: i64 = index
+        .parse()
+        .map_err(|_| eyre!("Line {line}: invalid index {index}"))?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    usize::try_from(resolved)
+        .ok()
+        .filter(|&resolved| resolved < count)
+        .ok_or_else(|| eyre!("Line {line}: index {index} is out of range"))
+}
+
+fn floats<const N: usize>(values: &mut std::str::SplitWhitespace, line: usize) -> Result<[f32; N]> {
+    let mut parsed = [0.0; N];
+    for value in &mut parsed {
+        *value = values
+            .next()
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| eyre!("Line {line}: expected {N} numbers"))?;
+    }
+    Ok(parsed)
+}
+
+/// Reads the vertices, texture coordinates and faces of a Wavefront OBJ file. Polygons
+/// are split into triangles, and normals, materials and groups are ignored.
+pub fn read_obj(text: &str) -> Result<Mesh> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut mesh = Mesh {
+        positions: Vec::new(),
+        uvs: Vec::new(),
+        triangles: Vec::new(),
+    };
+    // Each distinct position and UV pair a face uses becomes one vertex
+    let mut vertices: HashMap<(usize, Option<usize>), u32> = HashMap::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let number = number + 1;
+        let mut values = line.split_whitespace();
+        match values.next() {
+            Some("v") => positions.push(floats::<3>(&mut values, number)?),
+            Some("vt") => {
+                let [u, v] = floats::<2>(&mut values, number)?;
+                uvs.push([u, 1.0 - v]);
+            }
+            Some("f") => {
+                let mut corners = Vec::new();
+                for corner in values {
+                    let mut indices = corner.split('/');
+                    let position =
+                        obj_index(indices.next().unwrap_or(""), positions.len(), number)?;
+                    let uv = match indices.next() {
+                        Some(uv) if !uv.is_empty() => Some(obj_index(uv, uvs.len(), number)?),
+                        _ => None,
+                    };
+                    let next = mesh.positions.len() as u32;
+                    let vertex = *vertices.entry((position, uv)).or_insert_with(|| {
+                        mesh.positions.push(positions[position]);
+                        mesh.uvs.push(uv.map_or([0.0, 0.0], |uv| uvs[uv]));
+                        next
+                    });
+                    corners.push(vertex);
+                }
+                if corners.len() < 3 {
+                    return Err(eyre!("Line {number}: a face needs at least 3 vertices"));
+                }
+                for pair in corners[1..].windows(2) {
+                    mesh.triangles.push([corners[0], pair[0], pair[1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if mesh.triangles.is_empty() {
+        return Err(eyre!("The OBJ file has no faces"));
+    }
+    if uvs.is_empty() {
+        mesh.uvs.clear();
+    }
+    Ok(mesh)
+}
+
+impl Mesh {
+    /// Whether Studio can build an EditableMesh this large
+    pub fn fits_editable_mesh(&self) -> bool {
+        self.positions.len() <= MAX_EDITABLE_VERTICES
+            && self.triangles.len() <= MAX_EDITABLE_TRIANGLES
+    }
+
+    /// The mesh as a binary glTF file holding a single node named `name`
+    pub fn to_glb(&self, name: &str) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let mut views = Vec::new();
+        let mut accessors = Vec::new();
+        let mut attributes = serde_json::Map::new();
+
+        let mut low = [f32::INFINITY; 3];
+        let mut high = [f32::NEG_INFINITY; 3];
+        for position in &self.positions {
+            for axis in 0..3 {
+                low[axis] = low[axis].min(position[axis]);
+                high[axis] = high[axis].max(position[axis]);
+            }
+        }
+        let floats: Vec<f32> = self.positions.iter().flatten().copied().collect();
+        views.push(buffer_view(&mut buffer, &floats, GLTF_ARRAY_BUFFER));
+        accessors.push(json!({
+            "bufferView": 0,
+            "componentType": GLTF_FLOAT,
+            "count": self.positions.len(),
+            "type": "VEC3",
+            "min": low,
+            "max": high,
+        }));
+        attributes.insert("POSITION".to_string(), 0.into());
+
+        if !self.uvs.is_empty() {
+            let floats: Vec<f32> = self.uvs.iter().flatten().copied().collect();
+            views.push(buffer_view(&mut buffer, &floats, GLTF_ARRAY_BUFFER));
+            accessors.push(json!({
+                "bufferView": views.len() - 1,
+                "componentType": GLTF_FLOAT,
+                "count": self.uvs.len(),
+                "type": "VEC2",
+            }));
+            attributes.insert("TEXCOORD_0".to_string(), (accessors.len() - 1).into());
+        }
+
+        let indices: Vec<u32> = self.triangles.iter().flatten().copied().collect();
+        let index_bytes: Vec<u8> = indices
+            .iter()
+            .flat_map(|index| index.to_le_bytes())
+            .collect();
+        views.push(json!({
+            "buffer": 0,
+            "byteOffset": buffer.len(),
+            "byteLength": index_bytes.len(),
+            "target": GLTF_ELEMENT_ARRAY_BUFFER,
+        }));
+        buffer.extend_from_slice(&index_bytes);
+        accessors.push(json!({
+            "bufferView": views.len() - 1,
+            "componentType": GLTF_UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+
+        let document = json!({
+            "asset": { "version": "2.0", "generator": "rbx-studio-mcp" },
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [{ "mesh": 0, "name": name }],
+            "meshes": [{
+                "name": name,
+                "primitives": [{ "attributes": attributes, "indices": accessors.len() - 1 }],
+            }],
+            "buffers": [{ "byteLength": buffer.len() }],
+            "bufferViews": views,
+            "accessors": accessors,
+        });
+
+        // Chunks are padded to 4 bytes, the JSON with spaces and the binary with zeros
+        let mut document = document.to_string().into_bytes();
+        document.resize(document.len().next_multiple_of(4), b' ');
+        buffer.resize(buffer.len().next_multiple_of(4), 0);
+        let length = 12 + 8 + document.len() + 8 + buffer.len();
+        let mut glb = Vec::with_capacity(length);
+        for word in [GLB_MAGIC, GLB_VERSION, length as u32] {
+            glb.extend_from_slice(&word.to_le_bytes());
+        }
+        for (chunk_type, chunk) in [(GLB_JSON_CHUNK, &document), (GLB_BIN_CHUNK, &buffer)] {
+            glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            glb.extend_from_slice(&chunk_type.to_le_bytes());
+            glb.extend_from_slice(chunk);
+        }
+        glb
+    }
+}
+
+/// Appends `floats` to `buffer` and returns the buffer view describing them
+fn buffer_view(buffer: &mut Vec<u8>, floats: &[f32], target: u32) -> serde_json::Value {
+    let offset = buffer.len();
+    buffer.extend(floats.iter().flat_map(|value| value.to_le_bytes()));
+    json!({
+        "buffer": 0,
+        "byteOffset": offset,
+        "byteLength": buffer.len() - offset,
+        "target": target,
+    })
+}
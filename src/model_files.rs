@@ -0,0 +1,383 @@
+// Model files written by `export_scene` and read by `import_model_file`. Scene snapshots
+// from the plugin are rebuilt as an rbx-dom tree and saved as a binary `.rbxm` or an XML
+// `.rbxmx`, which Studio, Rojo and other tools open like any other model. Imported files
+// go the other way, decoded here into instances and typed property values the plugin can
+// recreate, since plugins can't open model files themselves.
+
+use crate::tool_results::ExportSceneResult;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use rbx_dom_weak::types::{CFrame, Color3, Enum, Matrix3, Ref, Variant, Vector3};
+use rbx_dom_weak::{ustr, InstanceBuilder, WeakDom};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Binary or XML, picked by the extension of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFormat {
+    Binary,
+    Xml,
+}
+
+impl ModelFormat {
+    /// `.rbxm` is binary and `.rbxmx` is XML, anything else isn't a model file
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "rbxm" => Some(Self::Binary),
+            "rbxmx" => Some(Self::Xml),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Binary => "rbxm",
+            Self::Xml => "rbxmx",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotVector3 {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+impl From<&SnapshotVector3> for Vector3 {
+    fn from(v: &SnapshotVector3) -> Self {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotCFrame {
+    position: SnapshotVector3,
+    /// Orientation in degrees, as returned by `CFrame:ToOrientation`
+    rotation: [f32; 3],
+}
+
+impl From<&SnapshotCFrame> for CFrame {
+    fn from(cframe: &SnapshotCFrame) -> Self {
+        // `CFrame.fromOrientation` turns about Z, then X, then Y
+        let [x, y, z] = cframe.rotation.map(f32::to_radians);
+        let rotation = multiply(multiply(rotate_y(y), rotate_x(x)), rotate_z(z));
+        let [r0, r1, r2] = rotation.map(|[a, b, c]| Vector3::new(a, b, c));
+        CFrame::new((&cframe.position).into(), Matrix3::new(r0, r1, r2))
+    }
+}
+
+type Rows = [[f32; 3]; 3];
+
+fn rotate_x(angle: f32) -> Rows {
+    let (sin, cos) = angle.sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, cos, -sin], [0.0, sin, cos]]
+}
+
+fn rotate_y(angle: f32) -> Rows {
+    let (sin, cos) = angle.sin_cos();
+    [[cos, 0.0, sin], [0.0, 1.0, 0.0], [-sin, 0.0, cos]]
+}
+
+fn rotate_z(angle: f32) -> Rows {
+    let (sin, cos) = angle.sin_cos();
+    [[cos, -sin, 0.0], [sin, cos, 0.0], [0.0, 0.0, 1.0]]
+}
+
+fn multiply(a: Rows, b: Rows) -> Rows {
+    let mut product = [[0.0; 3]; 3];
+    for (row, a_row) in product.iter_mut().zip(a) {
+        for (column, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| a_row[k] * b[k][column]).sum();
+        }
+    }
+    product
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotColor {
+    r: f32,
+    g: f32,
+    b: f32,
+}
+
+/// An instance of a scene snapshot, as serialized by the plugin's `SceneSnapshot`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnapshotInstance {
+    class_name: String,
+    name: String,
+    #[serde(default)]
+    children: Vec<SnapshotInstance>,
+    size: Option<SnapshotVector3>,
+    #[serde(rename = "CFrame")]
+    cframe: Option<SnapshotCFrame>,
+    color: Option<SnapshotColor>,
+    material: Option<String>,
+    transparency: Option<f32>,
+    anchored: Option<bool>,
+    can_collide: Option<bool>,
+    shape: Option<String>,
+    primary_part_name: Option<String>,
+}
+
+/// Reply of the plugin's `ExportScene`
+#[derive(Debug, Deserialize)]
+pub struct SceneObjects {
+    pub objects: Vec<SnapshotInstance>,
+}
+
+/// Value of item `name` of the Roblox enum `enum_name`, `None` if there's no such item
+fn enum_item(enum_name: &str, name: &str) -> Option<Enum> {
+    let database = rbx_reflection_database::get().ok()?;
+    let value = database.enums.get(enum_name)?.items.get(name)?;
+    Some(Enum::from_u32(*value))
+}
+
+/// Adds `instance` and its descendants under `parent` and returns how many were added
+fn insert(dom: &mut WeakDom, parent: Ref, instance: &SnapshotInstance) -> usize {
+    let mut builder = InstanceBuilder::new(instance.class_name.as_str()).with_name(&instance.name);
+    if let Some(size) = &instance.size {
+        builder.add_property("Size", Vector3::from(size));
+    }
+    if let Some(cframe) = &instance.cframe {
+        builder.add_property("CFrame", CFrame::from(cframe));
+    }
+    if let Some(color) = &instance.color {
+        builder.add_property("Color", Color3::new(color.r, color.g, color.b));
+    }
+    if let Some(material) = instance
+        .material
+        .as_deref()
+        .and_then(|name| enum_item("Material", name))
+    {
+        builder.add_property("Material", material);
+    }
+    if let Some(transparency) = instance.transparency {
+        builder.add_property("Transparency", transparency);
+    }
+    if let Some(anchored) = instance.anchored {
+        builder.add_property("Anchored", anchored);
+    }
+    if let Some(can_collide) = instance.can_collide {
+        builder.add_property("CanCollide", can_collide);
+    }
+    if let Some(shape) = instance
+        .shape
+        .as_deref()
+        .and_then(|name| enum_item("PartType", name))
+    {
+        builder.add_property("Shape", shape);
+    }
+
+    let referent = dom.insert(parent, builder);
+    let mut count = 1;
+    for child in &instance.children {
+        count += insert(dom, referent, child);
+    }
+
+    if let Some(primary_part_name) = &instance.primary_part_name {
+        let primary_part = dom.get_by_ref(referent).and_then(|model| {
+            model.children().iter().copied().find(|&child| {
+                dom.get_by_ref(child)
+                    .is_some_and(|child| &child.name == primary_part_name)
+            })
+        });
+        if let (Some(primary_part), Some(model)) = (primary_part, dom.get_by_ref_mut(referent)) {
+            model
+                .properties
+                .insert(ustr("PrimaryPart"), Variant::Ref(primary_part));
+        }
+    }
+    count
+}
+
+/// Writes `objects` to a model file at `path` in the format its extension names
+pub fn write(objects: &[SnapshotInstance], path: &Path) -> Result<ExportSceneResult> {
+    let format = ModelFormat::from_path(path)
+        .ok_or_else(|| eyre!("{} isn't an .rbxm or .rbxmx file", path.display()))?;
+    let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+    let root = dom.root_ref();
+    let instance_count = objects
+        .iter()
+        .map(|object| insert(&mut dom, root, object))
+        .sum();
+    let top_level = dom.root().children().to_vec();
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Couldn't create {}", dir.display()))?;
+    }
+    let file = File::create(path).wrap_err_with(|| format!("Couldn't write {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    match format {
+        ModelFormat::Binary => rbx_binary::to_writer(&mut writer, &dom, &top_level)?,
+        ModelFormat::Xml => rbx_xml::to_writer_default(&mut writer, &dom, &top_level)?,
+    }
+    let file = writer
+        .into_inner()
+        .map_err(|e| e.into_error())
+        .wrap_err_with(|| format!("Couldn't write {}", path.display()))?;
+    let bytes = file.metadata()?.len();
+
+    Ok(ExportSceneResult {
+        success: true,
+        path: path.display().to_string(),
+        format: format.extension().to_string(),
+        instance_count,
+        bytes,
+    })
+}
+
+/// Property value sent to the plugin, tagged with the Roblox type to build from it
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "value")]
+pub enum PropertyValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Vector2([f32; 2]),
+    Vector3([f32; 3]),
+    /// Position then the rotation matrix row by row, as taken by `CFrame.new`
+    CFrame([f32; 12]),
+    Color3([f32; 3]),
+    /// Channels from 0 to 255
+    Color3uint8([u8; 3]),
+    /// Value of the enum item
+    Enum(u32),
+    /// Scale and offset
+    UDim(f32, i32),
+    /// Scale and offset along X, then along Y
+    UDim2(f32, i32, f32, i32),
+    NumberRange([f32; 2]),
+    /// `Referent` of another instance of the same file
+    Ref(String),
+    Tags(Vec<String>),
+    Attributes(BTreeMap<String, PropertyValue>),
+}
+
+impl PropertyValue {
+    /// The value to send for `variant`, `None` for types the plugin can't set and for
+    /// references to instances outside the file
+    fn from_variant(variant: &Variant, imported: &HashSet<Ref>) -> Option<Self> {
+        Some(match variant {
+            Variant::Bool(value) => Self::Bool(*value),
+            Variant::Float32(value) => Self::Number(f64::from(*value)),
+            Variant::Float64(value) => Self::Number(*value),
+            Variant::Int32(value) => Self::Number(f64::from(*value)),
+            Variant::Int64(value) => Self::Number(*value as f64),
+            Variant::String(value) => Self::String(value.clone()),
+            Variant::ContentId(value) => Self::String(value.as_str().to_string()),
+            Variant::Content(value) => Self::String(value.as_uri()?.to_string()),
+            Variant::Vector2(v) => Self::Vector2([v.x, v.y]),
+            Variant::Vector3(v) => Self::Vector3([v.x, v.y, v.z]),
+            Variant::CFrame(cframe) => {
+                let (p, m) = (cframe.position, cframe.orientation);
+                Self::CFrame([
+                    p.x, p.y, p.z, m.x.x, m.x.y, m.x.z, m.y.x, m.y.y, m.y.z, m.z.x, m.z.y, m.z.z,
+                ])
+            }
+            Variant::Color3(c) => Self::Color3([c.r, c.g, c.b]),
+            Variant::Color3uint8(c) => Self::Color3uint8([c.r, c.g, c.b]),
+            Variant::Enum(value) => Self::Enum(value.to_u32()),
+            Variant::UDim(u) => Self::UDim(u.scale, u.offset),
+            Variant::UDim2(u) => Self::UDim2(u.x.scale, u.x.offset, u.y.scale, u.y.offset),
+            Variant::NumberRange(range) => Self::NumberRange([range.min, range.max]),
+            Variant::Ref(referent) if imported.contains(referent) => {
+                Self::Ref(referent.to_string())
+            }
+            Variant::Tags(tags) => Self::Tags(tags.iter().map(str::to_string).collect()),
+            Variant::Attributes(attributes) => Self::Attributes(
+                attributes
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((name.clone(), Self::from_variant(value, imported)?))
+                    })
+                    .collect(),
+            ),
+            _ => return None,
+        })
+    }
+}
+
+/// An instance read from a model file, as sent to the plugin's `ImportModelFile`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ModelInstance {
+    class_name: String,
+    name: String,
+    /// Lets `Ref` properties of other instances point at this one
+    referent: String,
+    properties: BTreeMap<String, PropertyValue>,
+    children: Vec<ModelInstance>,
+}
+
+/// The top level instances of a model file
+#[derive(Debug)]
+pub struct ModelTree {
+    pub instances: Vec<ModelInstance>,
+    /// Instances in the file, counting descendants
+    pub instance_count: usize,
+    /// Properties of types the plugin can't set, left at their defaults
+    pub unsupported_properties: usize,
+}
+
+fn convert(
+    dom: &WeakDom,
+    referent: Ref,
+    imported: &HashSet<Ref>,
+    tree: &mut ModelTree,
+) -> Option<ModelInstance> {
+    let instance = dom.get_by_ref(referent)?;
+    tree.instance_count += 1;
+    let mut properties = BTreeMap::new();
+    for (name, value) in &instance.properties {
+        if matches!(value, Variant::Ref(referent) if referent.is_none()) {
+            continue;
+        }
+        match PropertyValue::from_variant(value, imported) {
+            Some(value) => {
+                properties.insert(name.to_string(), value);
+            }
+            None => tree.unsupported_properties += 1,
+        }
+    }
+    let children = instance
+        .children()
+        .iter()
+        .filter_map(|&child| convert(dom, child, imported, tree))
+        .collect();
+    Some(ModelInstance {
+        class_name: instance.class.to_string(),
+        name: instance.name.clone(),
+        referent: referent.to_string(),
+        properties,
+        children,
+    })
+}
+
+/// Reads a binary or XML model file, told apart by the binary format's header
+pub fn read(contents: &[u8]) -> Result<ModelTree> {
+    let dom = if contents.starts_with(b"<roblox!") {
+        rbx_binary::from_reader(contents).wrap_err("Invalid .rbxm model")?
+    } else {
+        rbx_xml::from_reader_default(contents).wrap_err("Invalid .rbxmx model")?
+    };
+    let imported: HashSet<Ref> = dom
+        .descendants()
+        .map(|instance| instance.referent())
+        .collect();
+    let mut tree = ModelTree {
+        instances: Vec::new(),
+        instance_count: 0,
+        unsupported_properties: 0,
+    };
+    for &child in dom.root().children() {
+        if let Some(instance) = convert(&dom, child, &imported, &mut tree) {
+            tree.instances.push(instance);
+        }
+    }
+    Ok(tree)
+}
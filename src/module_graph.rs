@@ -0,0 +1,431 @@
+// Dependency graph for `get_module_dependencies`. `require` calls are read from the script
+// sources without running anything, so only targets spelled out as instance paths can be
+// followed: `script.Parent.Util`, `game:GetService("ReplicatedStorage").Shared`, locals
+// bound to such paths earlier in the script, and relative require-by-string paths.
+
+use crate::code_search::ScriptSource;
+use crate::luau_tokens::{self, Span, Token};
+use crate::tool_results::{ModuleDependenciesResult, RequireProblem};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+/// A `require` call and the instance path it loads, or the argument as written when
+/// that can't be worked out
+struct Require {
+    line: u32,
+    target: Result<Vec<String>, String>,
+}
+
+/// Builds the graph of which scripts require which modules
+pub fn graph(scripts: &[ScriptSource]) -> ModuleDependenciesResult {
+    let classes: HashMap<&str, &str> = scripts
+        .iter()
+        .map(|script| (script.path.as_str(), script.class_name.as_str()))
+        .collect();
+    let mut dependencies: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut missing = Vec::new();
+    let mut unresolved = Vec::new();
+    for script in scripts {
+        let segments: Vec<String> = script.path.split('.').map(str::to_string).collect();
+        let required = dependencies.entry(script.path.clone()).or_default();
+        for require in requires(&segments, &script.source) {
+            let problem = |target: String, reason: &str| RequireProblem {
+                path: script.path.clone(),
+                line: require.line,
+                target,
+                reason: reason.to_string(),
+            };
+            let target = match require.target {
+                Ok(target) => target.join("."),
+                Err(expression) => {
+                    let reason = if expression.chars().all(|c| c.is_ascii_digit()) {
+                        "Loads a published module by asset ID"
+                    } else {
+                        "The target isn't a fixed instance path"
+                    };
+                    unresolved.push(problem(expression, reason));
+                    continue;
+                }
+            };
+            match classes.get(target.as_str()) {
+                Some(&"ModuleScript") => {
+                    required.insert(target);
+                }
+                Some(_) => missing.push(problem(target, "Only ModuleScripts can be required")),
+                None => missing.push(problem(target, "There is no script at this path")),
+            }
+        }
+    }
+
+    let dependencies: BTreeMap<String, Vec<String>> = dependencies
+        .into_iter()
+        .map(|(path, required)| (path, required.into_iter().collect()))
+        .collect();
+    ModuleDependenciesResult {
+        success: true,
+        scripts_analyzed: scripts.len(),
+        cycles: cycles(&dependencies),
+        dependencies,
+        missing,
+        unresolved,
+    }
+}
+
+/// The `require` calls of a script at `script`, in the order they appear
+fn requires(script: &[String], source: &str) -> Vec<Require> {
+    let tokens = luau_tokens::tokenize(source);
+    let mut aliases: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let (token, span) = &tokens[index];
+        let after_dot = index > 0 && matches!(tokens[index - 1].0, Token::Symbol('.' | ':'));
+        match token {
+            // Later bindings replace earlier ones whatever their scope, which is right
+            // for the usual `local Shared = ReplicatedStorage.Shared` at the top
+            Token::Name(keyword) if keyword == "local" => {
+                if let (Some((Token::Name(name), _)), Some((Token::Symbol('='), _))) =
+                    (tokens.get(index + 1), tokens.get(index + 2))
+                {
+                    match path_expression(&tokens, index + 3, script, &aliases) {
+                        (Some(path), _) => aliases.insert(name.as_str(), path),
+                        (None, _) => aliases.remove(name.as_str()),
+                    };
+                }
+            }
+            Token::Name(name) if name == "require" && !after_dot => {
+                if let Some((Token::Symbol('('), _)) = tokens.get(index + 1) {
+                    let end = closing_paren(&tokens, index + 1);
+                    let argument = &tokens[index + 2..end];
+                    found.push(Require {
+                        line: span.line,
+                        target: require_target(argument, script, &aliases),
+                    });
+                    index = end;
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    found
+}
+
+fn require_target(
+    argument: &[(Token, Span)],
+    script: &[String],
+    aliases: &HashMap<&str, Vec<String>>,
+) -> Result<Vec<String>, String> {
+    if let [(Token::Str(Some(spec)), _)] = argument {
+        return string_target(script, spec).ok_or_else(|| format!("{spec:?}"));
+    }
+    match path_expression(argument, 0, script, aliases) {
+        (Some(path), end) if end == argument.len() => Ok(path),
+        _ => Err(expression_text(argument)),
+    }
+}
+
+/// Resolves a require-by-string path. `./` and `../` start from the script's parent and
+/// `@self/` from the script itself, other aliases depend on the project setup.
+fn string_target(script: &[String], spec: &str) -> Option<Vec<String>> {
+    let (mut path, rest) = match spec.strip_prefix("@self/") {
+        Some(rest) => (script.to_vec(), rest),
+        None if spec.starts_with("./") || spec.starts_with("../") => {
+            (script[..script.len().saturating_sub(1)].to_vec(), spec)
+        }
+        None => return None,
+    };
+    for part in rest.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                path.pop()?;
+            }
+            name => path.push(name.to_string()),
+        }
+    }
+    Some(path)
+}
+
+/// Follows an expression like `script.Parent:WaitForChild("Util")` from `start` for as
+/// long as it stays an instance path. Returns the path, if it starts from something
+/// known, and where the expression ends.
+fn path_expression(
+    tokens: &[(Token, Span)],
+    start: usize,
+    script: &[String],
+    aliases: &HashMap<&str, Vec<String>>,
+) -> (Option<Vec<String>>, usize) {
+    let mut path = match tokens.get(start) {
+        Some((Token::Name(name), _)) => match name.as_str() {
+            "script" => script.to_vec(),
+            "game" => Vec::new(),
+            "workspace" => vec!["Workspace".to_string()],
+            name => match aliases.get(name) {
+                Some(path) => path.clone(),
+                None => return (None, start),
+            },
+        },
+        _ => return (None, start),
+    };
+    let token = |index: usize| tokens.get(index).map(|(token, _)| token);
+    let mut index = start + 1;
+    loop {
+        match (token(index), token(index + 1)) {
+            (Some(Token::Symbol('.')), Some(Token::Name(name))) => {
+                if name == "Parent" {
+                    if path.pop().is_none() {
+                        return (None, index);
+                    }
+                } else {
+                    path.push(name.clone());
+                }
+                index += 2;
+            }
+            (Some(Token::Symbol('[')), Some(Token::Str(Some(name))))
+                if token(index + 2) == Some(&Token::Symbol(']')) =>
+            {
+                path.push(name.clone());
+                index += 3;
+            }
+            (Some(Token::Symbol(':')), Some(Token::Name(method))) => {
+                let (Some(Token::Symbol('(')), Some(Token::Str(Some(name)))) =
+                    (token(index + 2), token(index + 3))
+                else {
+                    break;
+                };
+                // WaitForChild can take a timeout, FindFirstChild's recursive flag makes
+                // the path unknowable
+                let end = match (token(index + 4), token(index + 5), token(index + 6)) {
+                    (Some(Token::Symbol(')')), _, _) => index + 5,
+                    (
+                        Some(Token::Symbol(',')),
+                        Some(Token::Number(_)),
+                        Some(Token::Symbol(')')),
+                    ) if method == "WaitForChild" => index + 7,
+                    _ => return (None, index),
+                };
+                match method.as_str() {
+                    "GetService" | "FindFirstChild" | "WaitForChild" => path.push(name.clone()),
+                    "FindFirstAncestor" => {
+                        let Some(ancestor) = path[..path.len().saturating_sub(1)]
+                            .iter()
+                            .rposition(|segment| segment == name)
+                        else {
+                            return (None, index);
+                        };
+                        path.truncate(ancestor + 1);
+                    }
+                    _ => return (None, index),
+                }
+                index = end;
+            }
+            _ => break,
+        }
+    }
+    (Some(path), index)
+}
+
+/// Index of the `)` matching the `(` at `open`, or the end of the tokens
+fn closing_paren(tokens: &[(Token, Span)], open: usize) -> usize {
+    let mut depth = 0;
+    for (index, (token, _)) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return index;
+                }
+            }
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+/// Writes tokens back out for reporting, close to how they appeared in the script
+fn expression_text(tokens: &[(Token, Span)]) -> String {
+    let mut text = String::new();
+    let mut previous_word = false;
+    for (token, _) in tokens {
+        let word = matches!(token, Token::Name(_) | Token::Number(_));
+        if word && previous_word {
+            text.push(' ');
+        }
+        match token {
+            Token::Name(name) | Token::Number(name) => text.push_str(name),
+            Token::Str(Some(value)) => text.push_str(&format!("{value:?}")),
+            Token::Str(None) => text.push_str("\"...\""),
+            Token::Symbol(symbol) => text.push(*symbol),
+        }
+        previous_word = word;
+    }
+    text
+}
+
+/// One cycle through each group of modules that require each other, starting and ending
+/// at the group's first module by path
+fn cycles(dependencies: &BTreeMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    strongly_connected(dependencies)
+        .into_iter()
+        .filter_map(|group| {
+            let first = group.iter().min()?;
+            let members: BTreeSet<&str> = group.iter().map(String::as_str).collect();
+            shortest_cycle(dependencies, first, &members)
+        })
+        .collect()
+}
+
+/// Shortest path from `start` back to itself through `members`, found breadth first
+fn shortest_cycle(
+    dependencies: &BTreeMap<String, Vec<String>>,
+    start: &str,
+    members: &BTreeSet<&str>,
+) -> Option<Vec<String>> {
+    let mut came_from: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for next in dependencies.get(node).into_iter().flatten() {
+            if next == start {
+                let mut cycle = vec![start.to_string()];
+                let mut current = node;
+                while current != start {
+                    cycle.push(current.to_string());
+                    current = came_from[current];
+                }
+                cycle.push(start.to_string());
+                cycle.reverse();
+                return Some(cycle);
+            }
+            if members.contains(next.as_str()) && !came_from.contains_key(next.as_str()) {
+                came_from.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Groups of modules that can reach each other, with Tarjan's algorithm run without
+/// recursion so deep chains can't overflow the stack. Modules that aren't part of a
+/// cycle are left out.
+fn strongly_connected(dependencies: &BTreeMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    let mut order: HashMap<&str, usize> = HashMap::new();
+    let mut low: HashMap<&str, usize> = HashMap::new();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut on_stack: BTreeSet<&str> = BTreeSet::new();
+    let mut groups = Vec::new();
+    let edges = |node: &str| {
+        dependencies
+            .get(node)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    };
+
+    for root in dependencies.keys() {
+        if order.contains_key(root.as_str()) {
+            continue;
+        }
+        // Each frame is a node and how many of its edges have been followed
+        let mut frames: Vec<(&str, usize)> = vec![(root, 0)];
+        while let Some(frame) = frames.last_mut() {
+            let node = frame.0;
+            if frame.1 == 0 && !order.contains_key(node) {
+                let position = order.len();
+                order.insert(node, position);
+                low.insert(node, position);
+                stack.push(node);
+                on_stack.insert(node);
+            }
+            if let Some(next) = edges(node).get(frame.1) {
+                frame.1 += 1;
+                if !order.contains_key(next.as_str()) {
+                    frames.push((next, 0));
+                } else if on_stack.contains(next.as_str()) {
+                    let lowest = low[node].min(order[next.as_str()]);
+                    low.insert(node, lowest);
+                }
+                continue;
+            }
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                let lowest = low[parent].min(low[node]);
+                low.insert(parent, lowest);
+            }
+            if low[node] == order[node] {
+                let mut group = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(member);
+                    group.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                let loops_to_itself = edges(node).iter().any(|next| next == node);
+                if group.len() > 1 || loops_to_itself {
+                    groups.push(group);
+                }
+            }
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(name: &str, source: &str) -> ScriptSource {
+        ScriptSource {
+            path: format!("ReplicatedStorage.{name}"),
+            class_name: "ModuleScript".to_string(),
+            source: source.to_string(),
+            checksum: String::new(),
+        }
+    }
+
+    fn paths(names: &[&str]) -> Vec<String> {
+        names
+            .iter()
+            .map(|name| format!("ReplicatedStorage.{name}"))
+            .collect()
+    }
+
+    #[test]
+    fn module_requiring_itself_is_a_cycle() {
+        let result = graph(&[module("A", "local A = require(script)\nreturn A")]);
+        assert_eq!(result.cycles, [paths(&["A", "A"])]);
+    }
+
+    #[test]
+    fn three_module_cycle_starts_at_first_path() {
+        let result = graph(&[
+            module("C", "return require(script.Parent.A)"),
+            module("A", "local B = require(script.Parent.B)"),
+            module("B", "local C = require(\"./C\")"),
+            module("D", "local A = require(script.Parent.A)"),
+        ]);
+        assert_eq!(result.cycles, [paths(&["A", "B", "C", "A"])]);
+        assert_eq!(result.dependencies["ReplicatedStorage.D"], paths(&["A"]));
+    }
+
+    #[test]
+    fn cycle_is_the_shortest_way_round() {
+        let result = graph(&[
+            module("A", "require(script.Parent.B)"),
+            module("B", "require(script.Parent.C)\nrequire(script.Parent.A)"),
+            module("C", "require(script.Parent.A)"),
+        ]);
+        assert_eq!(result.cycles, [paths(&["A", "B", "A"])]);
+    }
+
+    #[test]
+    fn chain_has_no_cycles() {
+        let result = graph(&[
+            module("A", "require(script.Parent.B)"),
+            module("B", "require(script.Parent.C)"),
+            module("C", "return {}"),
+        ]);
+        assert!(result.cycles.is_empty());
+    }
+}
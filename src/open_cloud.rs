@@ -0,0 +1,237 @@
+// `upload_asset` sends local files to Roblox through the Open Cloud Assets API with the
+// key in `open_cloud_api_key`. Uploads are long-running operations on Roblox's side, so
+// the operation is polled until it has an asset ID, and the asset until moderation has
+// decided on it.
+
+use crate::config::Config;
+use color_eyre::eyre::{eyre, OptionExt, Result, WrapErr};
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+const ASSETS_URL: &str = "https://apis.roblox.com/assets/v1";
+const API_KEY_HEADER: &str = "x-api-key";
+/// How often an upload operation or an asset under review is checked on
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How long Roblox may take to process an upload
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(120);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const MODERATION_PENDING: &str = "Reviewing";
+const SUPPORTED_EXTENSIONS: &str =
+    "fbx, gltf, glb, rbxm, rbxmx, mp3, ogg, wav, flac, png, jpg, jpeg, bmp, tga";
+
+/// The Open Cloud asset type and content type of a file, from its extension
+fn asset_type(path: &Path) -> Option<(&'static str, &'static str)> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "fbx" => ("Model", "model/fbx"),
+        "gltf" => ("Model", "model/gltf+json"),
+        "glb" => ("Model", "model/gltf-binary"),
+        "rbxm" => ("Model", "model/x-rbxm"),
+        "rbxmx" => ("Model", "model/x-rbxmx"),
+        "mp3" => ("Audio", "audio/mpeg"),
+        "ogg" => ("Audio", "audio/ogg"),
+        "wav" => ("Audio", "audio/wav"),
+        "flac" => ("Audio", "audio/flac"),
+        "png" => ("Decal", "image/png"),
+        "jpg" | "jpeg" => ("Decal", "image/jpeg"),
+        "bmp" => ("Decal", "image/bmp"),
+        "tga" => ("Decal", "image/tga"),
+        _ => return None,
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Operation {
+    path: String,
+    #[serde(default)]
+    done: bool,
+    response: Option<Asset>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Asset {
+    asset_id: String,
+    moderation_result: Option<ModerationResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModerationResult {
+    moderation_state: String,
+}
+
+pub struct Uploaded {
+    pub asset_id: u64,
+    pub asset_type: &'static str,
+    /// `Approved`, `Rejected` or `Reviewing` if moderation didn't decide in time
+    pub moderation_state: String,
+}
+
+/// Uploads the file at `path` as an asset named `name` owned by the configured user or
+/// group. With `wait_for_moderation` the asset is polled until moderation decides on it.
+pub async fn upload(
+    config: &Config,
+    path: &Path,
+    name: &str,
+    description: &str,
+    wait_for_moderation: bool,
+) -> Result<Uploaded> {
+    supported(path)?;
+    let contents = tokio::fs::read(path)
+        .await
+        .wrap_err_with(|| format!("Could not read {}", path.display()))?;
+    let file_name = path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .unwrap_or("asset");
+    upload_contents(
+        config,
+        file_name,
+        contents,
+        name,
+        description,
+        wait_for_moderation,
+    )
+    .await
+}
+
+/// The Open Cloud asset type and content type of `path`, or why it can't be uploaded
+fn supported(path: &Path) -> Result<(&'static str, &'static str)> {
+    asset_type(path).ok_or_else(|| {
+        eyre!(
+            "Can't upload {}, supported files are {SUPPORTED_EXTENSIONS}",
+            path.display()
+        )
+    })
+}
+
+/// Uploads `contents` like [`upload`], with `file_name` giving the type of file
+pub async fn upload_contents(
+    config: &Config,
+    file_name: &str,
+    contents: Vec<u8>,
+    name: &str,
+    description: &str,
+    wait_for_moderation: bool,
+) -> Result<Uploaded> {
+    let api_key = config
+        .open_cloud_api_key
+        .as_deref()
+        .ok_or_eyre("Set open_cloud_api_key in config.toml to upload assets")?;
+    let creator = match (config.open_cloud_user_id, config.open_cloud_group_id) {
+        (Some(user_id), _) => json!({ "userId": user_id.to_string() }),
+        (None, Some(group_id)) => json!({ "groupId": group_id.to_string() }),
+        (None, None) => {
+            return Err(eyre!(
+                "Set open_cloud_user_id or open_cloud_group_id in config.toml to upload assets"
+            ))
+        }
+    };
+    let (asset_type, content_type) = supported(Path::new(file_name))?;
+    let request = json!({
+        "assetType": asset_type,
+        "displayName": name,
+        "description": description,
+        "creationContext": { "creator": creator },
+    });
+
+    // reqwest is built without multipart support, and the form is only these two parts
+    let boundary = format!("rbx-studio-mcp-{}", uuid::Uuid::new_v4().simple());
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"request\"\r\n\
+         Content-Type: application/json\r\n\r\n{request}\r\n--{boundary}\r\n\
+         Content-Disposition: form-data; name=\"fileContent\"; filename=\"{file_name}\"\r\n\
+         Content-Type: {content_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(&contents);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    let response = client
+        .post(format!("{ASSETS_URL}/assets"))
+        .header(API_KEY_HEADER, api_key)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(body)
+        .send()
+        .await
+        .wrap_err("Could not reach Open Cloud")?;
+    let mut operation: Operation = read(response).await?;
+
+    let deadline = Instant::now() + OPERATION_TIMEOUT;
+    let mut asset = loop {
+        if let Some(asset) = operation.response.take().filter(|_| operation.done) {
+            break asset;
+        }
+        if Instant::now() >= deadline {
+            return Err(eyre!(
+                "Roblox didn't finish processing the upload within {} seconds",
+                OPERATION_TIMEOUT.as_secs()
+            ));
+        }
+        sleep(POLL_INTERVAL).await;
+        let response = client
+            .get(format!("{ASSETS_URL}/{}", operation.path))
+            .header(API_KEY_HEADER, api_key)
+            .send()
+            .await
+            .wrap_err("Could not reach Open Cloud")?;
+        operation = read(response).await?;
+    };
+    let asset_id = asset
+        .asset_id
+        .parse()
+        .wrap_err_with(|| format!("Invalid asset ID {}", asset.asset_id))?;
+
+    let mut moderation_state = moderation_of(&asset);
+    while wait_for_moderation && moderation_state == MODERATION_PENDING && Instant::now() < deadline
+    {
+        sleep(POLL_INTERVAL).await;
+        let response = client
+            .get(format!("{ASSETS_URL}/assets/{asset_id}"))
+            .header(API_KEY_HEADER, api_key)
+            .send()
+            .await
+            .wrap_err("Could not reach Open Cloud")?;
+        asset = read(response).await?;
+        moderation_state = moderation_of(&asset);
+    }
+
+    Ok(Uploaded {
+        asset_id,
+        asset_type,
+        moderation_state,
+    })
+}
+
+fn moderation_of(asset: &Asset) -> String {
+    asset
+        .moderation_result
+        .as_ref()
+        .map_or(MODERATION_PENDING, |result| &result.moderation_state)
+        .to_string()
+}
+
+/// The JSON body of an Open Cloud response, or its error message
+async fn read<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        let message = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|error| error["message"].as_str().map(str::to_string))
+            .unwrap_or(text);
+        return Err(eyre!("Open Cloud returned {status}: {message}"));
+    }
+    serde_json::from_str(&text).wrap_err("Unexpected response from Open Cloud")
+}
@@ -0,0 +1,99 @@
+// Emitters `create_particle_emitter` can start from by name. Values passed alongside a
+// preset override its own. The textures are the ones Studio ships for the old Fire, Smoke
+// and Sparkles effects.
+
+pub struct ParticlePreset {
+    pub name: &'static str,
+    pub texture: &'static str,
+    pub rate: f64,
+    pub lifetime: [f64; 2],
+    pub speed: [f64; 2],
+    pub spread_angle: f64,
+    pub acceleration: [f64; 3],
+    /// Keypoints as time and value
+    pub size: &'static [(f64, f64)],
+    pub transparency: &'static [(f64, f64)],
+    /// Keypoints as time and hex color
+    pub color: &'static [(f64, &'static str)],
+    pub light_emission: f64,
+    pub emission_direction: &'static str,
+    pub orientation: &'static str,
+}
+
+const FIRE_TEXTURE: &str = "rbxasset://textures/particles/fire_main.dds";
+const SMOKE_TEXTURE: &str = "rbxasset://textures/particles/smoke_main.dds";
+const SPARKLES_TEXTURE: &str = "rbxasset://textures/particles/sparkles_main.dds";
+
+pub const PARTICLE_PRESETS: &[ParticlePreset] = &[
+    ParticlePreset {
+        name: "fire",
+        texture: FIRE_TEXTURE,
+        rate: 40.0,
+        lifetime: [1.0, 1.5],
+        speed: [4.0, 6.0],
+        spread_angle: 15.0,
+        acceleration: [0.0, 4.0, 0.0],
+        size: &[(0.0, 2.0), (1.0, 0.5)],
+        transparency: &[(0.0, 0.2), (1.0, 1.0)],
+        color: &[(0.0, "#FFC447"), (1.0, "#FF3B14")],
+        light_emission: 1.0,
+        emission_direction: "Top",
+        orientation: "FacingCamera",
+    },
+    ParticlePreset {
+        name: "smoke",
+        texture: SMOKE_TEXTURE,
+        rate: 10.0,
+        lifetime: [4.0, 6.0],
+        speed: [2.0, 3.0],
+        spread_angle: 20.0,
+        acceleration: [0.0, 1.0, 0.0],
+        size: &[(0.0, 2.0), (1.0, 6.0)],
+        transparency: &[(0.0, 0.5), (1.0, 1.0)],
+        color: &[(0.0, "#6E6E6E"), (1.0, "#9C9C9C")],
+        light_emission: 0.0,
+        emission_direction: "Top",
+        orientation: "FacingCamera",
+    },
+    ParticlePreset {
+        name: "sparkles",
+        texture: SPARKLES_TEXTURE,
+        rate: 20.0,
+        lifetime: [0.5, 1.0],
+        speed: [2.0, 4.0],
+        spread_angle: 180.0,
+        acceleration: [0.0, -2.0, 0.0],
+        size: &[(0.0, 0.4), (1.0, 0.0)],
+        transparency: &[(0.0, 0.0), (1.0, 0.5)],
+        color: &[(0.0, "#FFF5B4"), (1.0, "#FFFFFF")],
+        light_emission: 1.0,
+        emission_direction: "Top",
+        orientation: "FacingCamera",
+    },
+    // Streaks falling from the bottom of a part held up over the area to wet
+    ParticlePreset {
+        name: "rain",
+        texture: SPARKLES_TEXTURE,
+        rate: 400.0,
+        lifetime: [1.0, 1.5],
+        speed: [60.0, 70.0],
+        spread_angle: 5.0,
+        acceleration: [0.0, 0.0, 0.0],
+        size: &[(0.0, 0.1), (1.0, 0.1)],
+        transparency: &[(0.0, 0.3), (1.0, 0.3)],
+        color: &[(0.0, "#AFC8DC"), (1.0, "#AFC8DC")],
+        light_emission: 0.0,
+        emission_direction: "Bottom",
+        orientation: "VelocityParallel",
+    },
+];
+
+pub fn find(name: &str) -> Result<&'static ParticlePreset, String> {
+    PARTICLE_PRESETS
+        .iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = PARTICLE_PRESETS.iter().map(|preset| preset.name).collect();
+            format!("There is no {name} preset, use one of {}", names.join(", "))
+        })
+}
@@ -0,0 +1,111 @@
+// Looks `set_post_effects` can apply by name. A preset covers all four effects: the ones
+// it has no settings for are turned off, and values passed alongside it override its own.
+
+pub struct BloomPreset {
+    pub intensity: f64,
+    pub size: f64,
+    pub threshold: f64,
+}
+
+pub struct DepthOfFieldPreset {
+    pub far_intensity: f64,
+    pub focus_distance: f64,
+    pub in_focus_radius: f64,
+    pub near_intensity: f64,
+}
+
+pub struct ColorCorrectionPreset {
+    pub brightness: f64,
+    pub contrast: f64,
+    pub saturation: f64,
+    pub tint_color: &'static str,
+}
+
+pub struct SunRaysPreset {
+    pub intensity: f64,
+    pub spread: f64,
+}
+
+pub struct PostEffectsPreset {
+    pub name: &'static str,
+    pub bloom: Option<BloomPreset>,
+    pub depth_of_field: Option<DepthOfFieldPreset>,
+    pub color_correction: Option<ColorCorrectionPreset>,
+    pub sun_rays: Option<SunRaysPreset>,
+}
+
+pub const POST_EFFECTS_PRESETS: &[PostEffectsPreset] = &[
+    PostEffectsPreset {
+        name: "cinematic",
+        bloom: Some(BloomPreset {
+            intensity: 0.5,
+            size: 24.0,
+            threshold: 1.5,
+        }),
+        depth_of_field: Some(DepthOfFieldPreset {
+            far_intensity: 0.3,
+            focus_distance: 40.0,
+            in_focus_radius: 30.0,
+            near_intensity: 0.1,
+        }),
+        color_correction: Some(ColorCorrectionPreset {
+            brightness: 0.0,
+            contrast: 0.15,
+            saturation: -0.1,
+            tint_color: "#FFF4E6",
+        }),
+        sun_rays: Some(SunRaysPreset {
+            intensity: 0.08,
+            spread: 0.6,
+        }),
+    },
+    PostEffectsPreset {
+        name: "horror",
+        bloom: None,
+        depth_of_field: Some(DepthOfFieldPreset {
+            far_intensity: 0.6,
+            focus_distance: 10.0,
+            in_focus_radius: 12.0,
+            near_intensity: 0.2,
+        }),
+        color_correction: Some(ColorCorrectionPreset {
+            brightness: -0.1,
+            contrast: 0.3,
+            saturation: -0.6,
+            tint_color: "#C8D6E0",
+        }),
+        sun_rays: None,
+    },
+    PostEffectsPreset {
+        name: "vibrant",
+        bloom: Some(BloomPreset {
+            intensity: 0.8,
+            size: 20.0,
+            threshold: 1.2,
+        }),
+        depth_of_field: None,
+        color_correction: Some(ColorCorrectionPreset {
+            brightness: 0.05,
+            contrast: 0.1,
+            saturation: 0.4,
+            tint_color: "#FFFFFF",
+        }),
+        sun_rays: Some(SunRaysPreset {
+            intensity: 0.15,
+            spread: 0.8,
+        }),
+    },
+];
+
+pub fn find(name: &str) -> Result<&'static PostEffectsPreset, String> {
+    POST_EFFECTS_PRESETS
+        .iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = POST_EFFECTS_PRESETS
+                .iter()
+                .map(|preset| preset.name)
+                .collect();
+            format!("There is no {name} preset, use one of {}", names.join(", "))
+        })
+}
@@ -0,0 +1,194 @@
+use rmcp::model::{
+    GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole,
+};
+use rmcp::ErrorData;
+
+use crate::completion::Completion;
+
+/// An argument of a prompt, filled into the template wherever `{name}` appears
+struct TemplateArgument {
+    name: &'static str,
+    description: &'static str,
+    /// Used when the client leaves the argument out, `None` makes it required
+    default: Option<&'static str>,
+    /// What `completion/complete` suggests for the argument
+    completion: Option<Completion>,
+}
+
+struct PromptTemplate {
+    name: &'static str,
+    title: &'static str,
+    description: &'static str,
+    arguments: &'static [TemplateArgument],
+    template: &'static str,
+}
+
+const PROMPTS: &[PromptTemplate] = &[
+    PromptTemplate {
+        name: "build_lobby",
+        title: "Build a lobby",
+        description: "Builds a spawn lobby for players to gather in before a round",
+        arguments: &[
+            TemplateArgument {
+                name: "theme",
+                description: "Look of the lobby, e.g. medieval, sci-fi or tropical",
+                default: Some("clean and modern"),
+                completion: None,
+            },
+            TemplateArgument {
+                name: "size",
+                description: "Footprint of the lobby",
+                default: Some("about 120 by 120 studs"),
+                completion: None,
+            },
+        ],
+        template: "Build a {theme} lobby in the open Roblox Studio place, {size}, where players spawn and wait before a round.
+
+1. Call get_workspace_stats and get_children_info on workspace to see what is already there, and find_gaps to pick an empty area so nothing existing is overlapped.
+2. Lay the ground with fill_terrain_region or a floor of anchored parts made with run_code.
+3. Furnish it with batch_insert_models using marketplace searches that match the theme, then check the placement with get_model_bounds.
+4. Add a SpawnLocation, seating and a sign for the round status with run_code. Group everything in a Model named Lobby.
+5. Use capture_viewport to look at the result and fix anything that floats, clips or blocks the spawn.
+
+Anchor every part, keep the spawn area clear and finish with a short summary of what was built.",
+    },
+    PromptTemplate {
+        name: "obby_checkpoints",
+        title: "Set up an obby checkpoint system",
+        description: "Adds numbered checkpoints to an obstacle course and saves player progress",
+        arguments: &[
+            TemplateArgument {
+                name: "course",
+                description: "Path of the model holding the course",
+                default: Some("workspace"),
+                completion: Some(Completion::InstancePath),
+            },
+            TemplateArgument {
+                name: "checkpoints",
+                description: "How many checkpoints to place along the course",
+                default: Some("10"),
+                completion: None,
+            },
+        ],
+        template: "Set up a checkpoint system for the obby in {course} with {checkpoints} checkpoints.
+
+1. Inspect the course with get_children_info and get_model_bounds to find the route from start to finish.
+2. Place {checkpoints} anchored SpawnLocation checkpoints evenly along the route with run_code, named Checkpoint1, Checkpoint2 and so on, inside a Checkpoints folder.
+3. Add a server Script with run_code that puts a leaderstats Stage value on each player, advances it when they touch the next checkpoint and respawns them at their latest stage. Keep progress in a DataStore so it survives rejoining.
+4. Test it with run_script_in_play_mode and check get_console_logs for errors.
+
+Report where each checkpoint ended up and anything in the course that needed changing.",
+    },
+    PromptTemplate {
+        name: "optimize_place",
+        title: "Optimize this place",
+        description: "Reviews the open place for performance problems and fixes the safe ones",
+        arguments: &[TemplateArgument {
+            name: "focus",
+            description: "What to prioritise, e.g. part count, scripts or lighting",
+            default: Some("overall performance"),
+            completion: None,
+        }],
+        template: "Review the open Roblox Studio place for performance problems, focusing on {focus}.
+
+1. Call get_workspace_stats for part and instance counts, then get_children_info on the largest containers to find what dominates them.
+2. Use run_code to look for unanchored parts that never move, needlessly high-detail meshes, many small parts that could be merged, expensive lighting settings and scripts with tight loops or per-frame work.
+3. Run the place with run_script_in_play_mode and read get_console_logs for errors and warnings.
+4. Fix the changes that can't alter gameplay, like anchoring static parts or disabling CastShadow on small details. Call save_scene first so the place can be restored with load_scene.
+
+List the remaining problems with suggested fixes instead of making risky changes.",
+    },
+    PromptTemplate {
+        name: "reshape_terrain",
+        title: "Reshape terrain",
+        description: "Sculpts or repaints the terrain around part of the place",
+        arguments: &[
+            TemplateArgument {
+                name: "area",
+                description: "Path of the model or part the terrain around should change",
+                default: None,
+                completion: Some(Completion::InstancePath),
+            },
+            TemplateArgument {
+                name: "mode",
+                description: "Sculpting mode: add, subtract, paint or smooth",
+                default: Some("smooth"),
+                completion: Some(Completion::SculptMode),
+            },
+            TemplateArgument {
+                name: "material",
+                description: "Terrain material to use",
+                default: Some("Grass"),
+                completion: Some(Completion::TerrainMaterial),
+            },
+        ],
+        template: "Reshape the terrain around {area} in the open Roblox Studio place using the {mode} sculpting mode with {material}.
+
+1. Call get_model_bounds on {area} to find the region to work on, and save_scene so the change can be undone with load_scene.
+2. Plan a set of points over that region, following the outline of {area} rather than a plain grid.
+3. Call sculpt_terrain with mode {mode} and material {material}, a radius that blends into the surrounding terrain and a moderate strength. Repeat with smaller radii for the edges.
+4. Use capture_viewport to check that nothing in {area} is buried or left floating, and adjust.
+
+Finish with a short summary of what changed.",
+    },
+];
+
+pub fn list() -> Vec<Prompt> {
+    PROMPTS
+        .iter()
+        .map(|template| {
+            let arguments = template
+                .arguments
+                .iter()
+                .map(|argument| PromptArgument {
+                    name: argument.name.to_string(),
+                    title: None,
+                    description: Some(argument.description.to_string()),
+                    required: Some(argument.default.is_none()),
+                })
+                .collect();
+            let mut prompt =
+                Prompt::new(template.name, Some(template.description), Some(arguments));
+            prompt.title = Some(template.title.to_string());
+            prompt
+        })
+        .collect()
+}
+
+/// How the `argument` of the prompt called `name` is completed, if at all
+pub fn completion(name: &str, argument: &str) -> Option<Completion> {
+    PROMPTS
+        .iter()
+        .find(|template| template.name == name)?
+        .arguments
+        .iter()
+        .find(|template_argument| template_argument.name == argument)?
+        .completion
+}
+
+/// Fills in the prompt called `name` with the client's arguments
+pub fn get(name: &str, arguments: Option<JsonObject>) -> Result<GetPromptResult, ErrorData> {
+    let template = PROMPTS
+        .iter()
+        .find(|template| template.name == name)
+        .ok_or_else(|| ErrorData::invalid_params(format!("Unknown prompt {name}"), None))?;
+    let arguments = arguments.unwrap_or_default();
+    let mut text = template.template.to_string();
+    for argument in template.arguments {
+        let value = match arguments.get(argument.name) {
+            Some(serde_json::Value::String(value)) => value.clone(),
+            Some(value) => value.to_string(),
+            None => argument
+                .default
+                .ok_or_else(|| {
+                    ErrorData::invalid_params(format!("Missing argument {}", argument.name), None)
+                })?
+                .to_string(),
+        };
+        text = text.replace(&format!("{{{}}}", argument.name), &value);
+    }
+    Ok(GetPromptResult {
+        description: Some(template.description.to_string()),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+    })
+}
@@ -1,30 +1,255 @@
+use crate::ambient_zones;
+use crate::api_dump;
+use crate::atmosphere_presets::{self, AtmospherePreset};
+use crate::auth::AUTH_HEADER;
+use crate::code_search::{self, ScriptSource, ScriptSources};
+use crate::command_queue::{CommandQueue, Priority};
+use crate::completion::{self, Completion, InstancePaths};
+use crate::config::Config;
+use crate::day_night;
 use crate::error::Result;
+use crate::format;
+use crate::heightmap;
+use crate::journal::Journal;
+use crate::lint;
+use crate::luau_tokens;
+use crate::marketplace;
+use crate::mesh_files;
+use crate::model_files::{self, SceneObjects};
+use crate::module_graph;
+use crate::open_cloud;
+use crate::particle_presets::{self, ParticlePreset};
+use crate::post_effect_presets::{self, PostEffectsPreset};
+use crate::prompts;
+use crate::rename;
+use crate::scenes;
+use crate::script_patch::{self, Hunk, LineEdit};
+use crate::script_templates;
+use crate::sky_presets;
+use crate::structures::{self, DoorSpec, Opening, Piece, WindowSpec};
+use crate::terrain_export::{self, TerrainColumns};
+use crate::terrain_stamps::{self, StampRead};
+use crate::terrain_tiles;
+use crate::test_runner;
+use crate::tls;
+use crate::tool_results::{self, Reply};
+use crate::viewport_capture;
+use crate::weather;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
-use axum::{extract::State, Json};
-use color_eyre::eyre::{Error, OptionExt};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use color_eyre::eyre::{eyre, OptionExt};
+use regex::RegexBuilder;
 use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::{
-        CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo,
+        AnnotateAble, CallToolResult, CompleteRequestParams, CompleteResult, CompletionInfo,
+        Content, GetPromptRequestParams, GetPromptResult, Implementation, JsonObject,
+        ListPromptsResult, ListResourceTemplatesResult, ListResourcesResult, LoggingLevel,
+        LoggingMessageNotificationParam, PaginatedRequestParams, ProgressNotificationParam,
+        ProtocolVersion, RawResource, RawResourceTemplate, ReadResourceRequestParams,
+        ReadResourceResult, Reference, ResourceContents, ResourceUpdatedNotificationParam,
+        ServerCapabilities, ServerInfo, SetLevelRequestParams, SubscribeRequestParams,
+        UnsubscribeRequestParams,
     },
-    schemars, tool, tool_handler, tool_router, ErrorData, ServerHandler,
+    schemars,
+    service::{NotificationContext, Peer, RequestContext},
+    tool, tool_handler, tool_router, ErrorData, RoleServer, ServerHandler,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::oneshot::Receiver;
-use tokio::sync::{mpsc, watch, Mutex};
-use tokio::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
+use tokio::time::{Duration, Instant};
 use uuid::Uuid;
 
 pub const STUDIO_PLUGIN_PORT: u16 = 44755;
-const LONG_POLL_DURATION: Duration = Duration::from_secs(15);
+const PORT_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Studio sessions that haven't polled or sent a heartbeat for this long are dropped
+const STUDIO_SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+const WS_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Extra time a play mode script gets on top of its own timeout to start and stop play
+const PLAY_MODE_TIMEOUT_MARGIN: Duration = Duration::from_secs(30);
+/// Seconds `run_tests` lets the tests run unless asked otherwise, and the most it allows
+const DEFAULT_TEST_TIMEOUT: u32 = 120;
+const MAX_TEST_TIMEOUT: u32 = 3600;
+/// Slack on top of the poll interval before `/health` reports the plugin as gone
+const PLUGIN_SEEN_GRACE: Duration = Duration::from_secs(5);
+/// How long the rest of an oversized tool result is kept for `fetch_result_page`
+const RESULT_PAGE_TTL: Duration = Duration::from_secs(600);
+/// How long a viewport capture waits for the `capture_viewport` call it was taken for
+const CAPTURE_TTL: Duration = Duration::from_secs(120);
+/// Completion waits at most this long for Studio, it's meant to be interactive
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the instance tree used for completion is reused before it's listed again
+const INSTANCE_TREE_TTL: Duration = Duration::from_secs(30);
+/// Bounds on the instance tree listed for completion, so large places stay quick
+const INSTANCE_TREE_DEPTH: u32 = 8;
+const INSTANCE_TREE_LIMIT: u32 = 5000;
+/// Most copies `clone_instance`, `array_duplicate` and `place_along_path` make in one call
+const MAX_CLONE_COUNT: u32 = 1000;
+/// Largest source `write_script_source` accepts
+const MAX_SCRIPT_BYTES: usize = 8 * 1024 * 1024;
+/// Script sources and heightmaps longer than this are left out of the command and fetched
+/// from `/chunk`
+const SCRIPT_CHUNK_BYTES: usize = 256 * 1024;
+const SCRIPT_CLASSES: &[&str] = &["Script", "LocalScript", "ModuleScript"];
+/// What `insert_decal_or_texture` can put on a part
+const SURFACE_KINDS: &[&str] = &["decal", "texture", "surface_appearance"];
+/// Faces a decal or texture can go on, `All` puts one on each
+const FACES: &[&str] = &["Front", "Back", "Top", "Bottom", "Left", "Right", "All"];
+/// Lights `place_lights` creates
+const LIGHT_KINDS: &[&str] = &["point", "spot", "surface"];
+const PARTICLE_DIRECTIONS: &[&str] = &["Top", "Bottom", "Front", "Back", "Left", "Right"];
+const PARTICLE_ORIENTATIONS: &[&str] = &[
+    "FacingCamera",
+    "FacingCameraWorldUp",
+    "VelocityParallel",
+    "VelocityPerpendicular",
+];
+/// Most keypoints Studio takes in a NumberSequence or ColorSequence
+const MAX_SEQUENCE_KEYPOINTS: usize = 20;
+const PHYSICS_STEPPING_METHODS: &[&str] = &["Default", "Fixed", "Adaptive"];
+/// Values of Workspace's Retargeting and IKControlConstraintSupport
+const FEATURE_MODES: &[&str] = &["Default", "Disabled", "Enabled"];
+/// Studio clamps FallenPartsDestroyHeight to this
+const MIN_FALLEN_PARTS_DESTROY_HEIGHT: f64 = -50000.0;
+/// Modes `start_playtest` starts, named like `start_stop_play`'s
+const PLAYTEST_MODES: &[&str] = &["start_play", "run_server"];
+/// Severities `get_output_log` filters by, least severe first
+const OUTPUT_LEVELS: &[&str] = &["info", "warn", "error"];
+/// Entries the plugin keeps of the Studio output
+const MAX_OUTPUT_ENTRIES: u32 = 500;
+/// Longest `get_performance_stats` averages the frame timings over
+const MAX_STATS_SAMPLE_SECONDS: f64 = 10.0;
+/// Most containers `get_memory_report` lists
+const MAX_MEMORY_REPORT_CONTAINERS: u32 = 100;
+/// Longest ray Roblox casts, in studs
+const MAX_RAY_DISTANCE: f64 = 15000.0;
+const RAYCAST_FILTER_TYPES: &[&str] = &["exclude", "include"];
+/// Most parts `query_region` returns
+const MAX_REGION_PARTS: u32 = 1000;
+const ROLL_OFF_MODES: &[&str] = &["Inverse", "Linear", "LinearSquare", "InverseTapered"];
+const COLLISION_FIDELITIES: &[&str] = &["Default", "Hull", "Box", "PreciseConvexDecomposition"];
+const RENDER_FIDELITIES: &[&str] = &["Automatic", "Performance", "Precise"];
+const BATCH_PLACEMENTS: &[&str] = &["exact", "grounded"];
+const PART_SHAPES: &[&str] = &["Block", "Sphere", "Cylinder", "Wedge", "CornerWedge"];
+const CONSTRAINT_KINDS: &[&str] = &[
+    "weld",
+    "rigid",
+    "hinge",
+    "motor",
+    "servo",
+    "rope",
+    "rod",
+    "spring",
+    "ball_socket",
+    "prismatic",
+];
+const ALIGN_MODES: &[&str] = &["min", "center", "max", "distribute"];
+const AXES: &[&str] = &["x", "y", "z"];
+/// Most stars a Sky shows
+const MAX_SKY_STARS: u32 = 5000;
+/// Longest preview `insert_audio` plays
+const MAX_AUDIO_PREVIEW_SECS: f64 = 10.0;
+/// Largest scene snapshot the plugin may upload to `/scene`
+pub const MAX_SCENE_BYTES: usize = 64 * 1024 * 1024;
+/// Largest model file `import_model_file` reads
+const MAX_MODEL_FILE_BYTES: usize = 64 * 1024 * 1024;
+/// Files `import_3d_file` takes, only OBJ files are read by the server
+const MESH_FILE_EXTENSIONS: &[&str] = &["obj", "fbx", "gltf", "glb"];
+const MESH_IMPORT_METHODS: &[&str] = &["upload", "editable_mesh"];
+/// Studs between the terrain columns `import_heightmap` fills unless asked otherwise
+const DEFAULT_HEIGHTMAP_RESOLUTION: f64 = 4.0;
+/// Finest `import_heightmap` resolution, closer columns only fill the same 4 stud voxels
+/// again
+const MIN_HEIGHTMAP_RESOLUTION: f64 = 1.0;
+/// Entrances the plugin digs into caves unless asked otherwise
+const DEFAULT_CAVE_ENTRANCES: u32 = 2;
+/// `_meta` field of a tool call holding the token its progress is reported against
+const PROGRESS_TOKEN_META: &str = "progressToken";
+/// Most terrain columns one heightmap import fills
+const MAX_HEIGHTMAP_COLUMNS: usize = 1024 * 1024;
+/// Matches `search_code` returns unless asked for another number
+const DEFAULT_CODE_MATCHES: u32 = 100;
+/// Results `search_marketplace` returns unless asked for another number, and the most it
+/// returns
+const DEFAULT_MARKETPLACE_RESULTS: u32 = 10;
+const MAX_MARKETPLACE_RESULTS: u32 = 50;
+/// Scenes `find_scenes` returns unless asked for another number
+const DEFAULT_SCENE_MATCHES: usize = 50;
+/// Commands handled by plugins from before they reported their tools on `/register`
+const LEGACY_COMMANDS: &[&str] = &[
+    "RunCode",
+    "InsertModel",
+    "BatchInsertModels",
+    "BatchRunCode",
+    "GenerateTerrain",
+    "FillTerrainRegion",
+    "SculptTerrain",
+    "ClearWorkspace",
+    "SaveScene",
+    "LoadScene",
+    "GetConsoleLogs",
+    "GetWorkspaceStats",
+    "GetChildrenInfo",
+    "GetModelBounds",
+    "FindGaps",
+    "CaptureViewport",
+    "GetConsoleOutput",
+    "StartStopPlay",
+    "RunScriptInPlayMode",
+    "GetStudioMode",
+];
+/// Tools the server answers itself, offered whatever the plugin supports
+const SERVER_TOOLS: &[&str] = &[
+    "list_studio_sessions",
+    "select_studio_session",
+    "get_pending_operations",
+    "fetch_result_page",
+    "list_terrain_stamps",
+    "list_scene_revisions",
+    "find_scenes",
+    "prune_scene_history",
+    "upload_asset",
+];
+/// `_meta` field of a tool call that marks retries of the same call
+const IDEMPOTENCY_KEY_META: &str = "idempotency_key";
+/// Instances are exposed as resources under `roblox://<service>/<child>/...`, with each
+/// name percent-encoded
+const RESOURCE_SCHEME: &str = "roblox://";
+/// Services listed as resources, anything below them is reached through the template
+const RESOURCE_ROOTS: &[&str] = &[
+    "workspace",
+    "ReplicatedStorage",
+    "ServerScriptService",
+    "ServerStorage",
+    "StarterGui",
+    "StarterPlayer",
+    "Lighting",
+];
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ToolArguments {
     args: ToolArgumentValues,
     id: Option<Uuid>,
+    /// Studio session that must run the command, any session may take it when unset
+    session: Option<String>,
+    #[serde(default)]
+    priority: Priority,
+    /// Caller supplied key identifying retries of the same tool call
+    #[serde(default)]
+    idempotency_key: Option<String>,
+    /// How many times the command was taken back from a plugin that stopped responding
+    #[serde(skip)]
+    retries: u32,
+    #[serde(skip, default = "Instant::now")]
+    queued_at: Instant,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -33,53 +258,945 @@ pub struct RunCommandResponse {
     id: Uuid,
 }
 
+/// Interim progress the plugin reports for a long running command
+#[derive(Deserialize, Debug)]
+pub struct ProgressUpdate {
+    id: Uuid,
+    progress: f64,
+    total: Option<f64>,
+    message: Option<String>,
+}
+
+/// Watched paths the plugin saw change, as posted to `/changes`
+#[derive(Deserialize, Debug)]
+pub struct InstanceChanges {
+    paths: Vec<String>,
+}
+
+/// Output from the Studio console, as posted to `/log`
+#[derive(Deserialize, Debug)]
+pub struct StudioLogs {
+    entries: Vec<StudioLogEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StudioLogEntry {
+    /// `output`, `info`, `warn` or `error`, after the `MessageType` Studio logged it with
+    level: String,
+    message: String,
+}
+
+impl StudioLogEntry {
+    fn logging_level(&self) -> LoggingLevel {
+        match self.level.as_str() {
+            "info" => LoggingLevel::Notice,
+            "warn" => LoggingLevel::Warning,
+            "error" => LoggingLevel::Error,
+            _ => LoggingLevel::Info,
+        }
+    }
+}
+
+/// A PNG taken by `capture_viewport`, kept until the call that took it returns
+struct StoredCapture {
+    png: Vec<u8>,
+    at: Instant,
+}
+
+/// A tool result too long to return at once
+struct StoredResult {
+    text: String,
+    at: Instant,
+}
+
+/// Marketplace search results kept for `marketplace_cache_secs`
+struct CachedSearch {
+    /// Most results the search asked for, it holds fewer if there weren't more
+    limit: u32,
+    found: tool_results::SearchMarketplaceResult,
+    at: Instant,
+}
+
+/// Snapshot of the instance paths in a Studio session, used to complete paths
+struct InstanceTree {
+    session: Option<String>,
+    paths: Arc<Vec<Vec<String>>>,
+    at: Instant,
+}
+
+/// An MCP client receiving the Studio output at or above `level`
+struct LogListener {
+    peer: Peer<RoleServer>,
+    level: LoggingLevel,
+}
+
+/// A Studio window running the plugin, as announced on `/register`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct StudioSession {
+    id: String,
+    place_name: Option<String>,
+    place_id: Option<u64>,
+    /// Commands the plugin handles, named like the `ToolArgumentValues` variants. Left
+    /// out by older plugins, which are assumed to handle `LEGACY_COMMANDS`.
+    #[serde(default)]
+    tools: Option<Vec<String>>,
+}
+
+impl StudioSession {
+    fn commands(&self) -> Vec<String> {
+        match &self.tools {
+            Some(tools) => tools.clone(),
+            None => LEGACY_COMMANDS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// A command the plugin took but hasn't answered yet
+struct Lease {
+    command: ToolArguments,
+    /// Session that took the command, `None` for plugins that don't announce one
+    holder: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+enum OperationStatus {
+    /// Waiting for a plugin to take it
+    Queued,
+    /// Taken by a plugin that hasn't replied yet
+    Running,
+}
+
+/// A command waiting on Studio, as listed by `get_pending_operations` and `/queue`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct PendingOperation {
+    id: Uuid,
+    tool: String,
+    status: OperationStatus,
+    session: Option<String>,
+    priority: Priority,
+    /// Seconds since the tool was called
+    age_secs: u64,
+}
+
+/// The command started for an idempotency key and its result once Studio replied
+struct IdempotencyEntry {
+    id: Uuid,
+    result: Option<Result<String, String>>,
+    /// When the command was queued, or when it finished once there is a result
+    at: Instant,
+}
+
+/// What `AppState::enqueue` did with a command
+enum Enqueued {
+    /// Queued, the reply arrives on the receiver
+    Waiting(oneshot::Receiver<Result<String>>),
+    /// An earlier call with the same idempotency key finished with this result
+    Cached(Result<String, String>),
+    /// An earlier call with the same idempotency key is still waiting on Studio
+    Duplicate(Uuid),
+    /// The queue is full
+    Full,
+}
+
+struct StudioSessionEntry {
+    session: StudioSession,
+    last_seen: Instant,
+    /// False for sessions only known from polling, e.g. after a server restart, whose
+    /// tools are unknown
+    registered: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SceneQuery {
+    /// See `scenes::load`, the latest revision when missing
+    revision: Option<i64>,
+}
+
+/// Size of the pixels the plugin uploads for `capture_viewport`, and the longest side of
+/// the image it asked for
+#[derive(Deserialize)]
+pub struct CaptureQuery {
+    width: u32,
+    height: u32,
+    max_size: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct PluginQuery {
+    session: Option<String>,
+    /// Set by plugins that accept several commands per long poll, which then get a JSON
+    /// array of up to this many commands and reply to each one separately
+    batch: Option<usize>,
+}
+
 pub struct AppState {
-    process_queue: VecDeque<ToolArguments>,
-    output_map: HashMap<Uuid, mpsc::UnboundedSender<Result<String>>>,
+    process_queue: CommandQueue<ToolArguments>,
+    output_map: HashMap<Uuid, oneshot::Sender<Result<String>>>,
+    in_flight: HashMap<Uuid, Lease>,
+    /// Commands restored from the journal of a previous run, nothing waits on their replies
+    recovered: HashSet<Uuid>,
+    idempotency: HashMap<String, IdempotencyEntry>,
+    /// Tool calls that asked for progress notifications, keyed by command
+    progress: HashMap<Uuid, mpsc::UnboundedSender<ProgressUpdate>>,
+    /// Clients subscribed to each resource path, keyed by the server instance they use
+    subscriptions: HashMap<String, HashMap<Uuid, Peer<RoleServer>>>,
+    /// Clients the Studio output is forwarded to, keyed by the server instance they use
+    log_listeners: HashMap<Uuid, LogListener>,
+    sessions: HashMap<String, StudioSessionEntry>,
+    /// Viewport captures uploaded by the plugin, keyed by the ID its result names them by
+    captures: HashMap<Uuid, StoredCapture>,
+    last_plugin_poll: Option<Instant>,
+    started_at: Instant,
+    /// Set when another instance owns the plugin port and commands are proxied to it
+    proxied: bool,
+    /// Commands the connected plugins handle between them, `None` while none is connected
+    plugin_commands: watch::Sender<Option<Arc<HashSet<String>>>>,
     waiter: watch::Receiver<()>,
     trigger: watch::Sender<()>,
 }
-pub type PackedState = Arc<Mutex<AppState>>;
+type StateJob = Box<dyn FnOnce(&mut AppState) + Send>;
+
+/// Handle to the task that owns the `AppState`. Every access runs as a job on that task,
+/// so a handler can never hold the state across an await and block everyone else.
+#[derive(Clone)]
+pub struct PackedState {
+    jobs: mpsc::UnboundedSender<StateJob>,
+    plugin_commands: watch::Receiver<Option<Arc<HashSet<String>>>>,
+}
+
+impl PackedState {
+    pub fn spawn(mut state: AppState) -> Self {
+        let (jobs, mut rx) = mpsc::unbounded_channel::<StateJob>();
+        let plugin_commands = state.plugin_commands.subscribe();
+        tokio::spawn(async move {
+            while let Some(job) = rx.recv().await {
+                job(&mut state);
+            }
+        });
+        Self {
+            jobs,
+            plugin_commands,
+        }
+    }
+
+    /// Follows the commands the connected plugins handle
+    fn plugin_commands(&self) -> watch::Receiver<Option<Arc<HashSet<String>>>> {
+        self.plugin_commands.clone()
+    }
+
+    fn send(&self, job: StateJob) {
+        // The task only stops once every handle is gone
+        self.jobs.send(job).expect("state task stopped");
+    }
+
+    /// Runs `f` on the state task and returns its result
+    pub async fn call<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut AppState) -> R + Send + 'static,
+    ) -> R {
+        let (tx, rx) = oneshot::channel();
+        self.send(Box::new(move |state| {
+            tx.send(f(state)).ok();
+        }));
+        rx.await.expect("state task stopped")
+    }
+
+    /// Leases the next command for `session`, or for a WebSocket only one that can run
+    /// beside what it is already running. If the caller stops waiting before the command
+    /// reached it, even after the lease was made, it goes back to the front of the queue.
+    async fn lease_next(
+        &self,
+        session: Option<String>,
+        lease_timeout: Duration,
+        pushed: bool,
+    ) -> Option<ToolArguments> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Box::new(move |state| {
+            state.reclaim_expired_leases(lease_timeout);
+            let command = if pushed {
+                state.lease_beside_running(session.as_deref())
+            } else {
+                state.lease_next(session.as_deref())
+            };
+            if let Some(command) = command {
+                if let Err(Some(command)) = tx.send(Some(command)) {
+                    state.release(command);
+                }
+            } else {
+                tx.send(None).ok();
+            }
+        }));
+        let mut lease = LeaseReceiver {
+            rx,
+            state: self.clone(),
+        };
+        (&mut lease.rx).await.expect("state task stopped")
+    }
+}
+
+/// Waits for a command leased by `PackedState::lease_next`. Dropping it before the
+/// command arrived puts the command back, so it isn't lost when a long poll times out or
+/// the WebSocket loop picks another branch.
+struct LeaseReceiver {
+    rx: oneshot::Receiver<Option<ToolArguments>>,
+    state: PackedState,
+}
+
+impl Drop for LeaseReceiver {
+    fn drop(&mut self) {
+        // After closing, the lease job either already sent the command, which is taken
+        // back here, or fails to send it and puts it back itself
+        self.rx.close();
+        if let Ok(Some(command)) = self.rx.try_recv() {
+            self.state
+                .send(Box::new(move |state| state.release(command)));
+        }
+    }
+}
 
 impl AppState {
     pub fn new() -> Self {
         let (trigger, waiter) = watch::channel(());
         Self {
-            process_queue: VecDeque::new(),
+            process_queue: CommandQueue::new(),
             output_map: HashMap::new(),
+            in_flight: HashMap::new(),
+            recovered: HashSet::new(),
+            idempotency: HashMap::new(),
+            progress: HashMap::new(),
+            subscriptions: HashMap::new(),
+            log_listeners: HashMap::new(),
+            sessions: HashMap::new(),
+            captures: HashMap::new(),
+            last_plugin_poll: None,
+            started_at: Instant::now(),
+            proxied: false,
+            plugin_commands: watch::Sender::new(None),
             waiter,
             trigger,
         }
     }
+
+    pub fn set_proxied(&mut self) {
+        self.proxied = true;
+    }
+
+    fn register_session(&mut self, session: StudioSession) {
+        // A window that (re)connects has lost whatever it watched before
+        if !self.subscriptions.is_empty() {
+            self.push_watches(Some(session.id.clone()));
+        }
+        let entry = StudioSessionEntry {
+            session,
+            last_seen: Instant::now(),
+            registered: true,
+        };
+        self.sessions.insert(entry.session.id.clone(), entry);
+        self.update_plugin_commands(true);
+    }
+
+    /// Works out which commands the registered plugins handle between them. Clients are
+    /// told the tool list changed when that differs from before, or always if `reconnected`.
+    fn update_plugin_commands(&mut self, reconnected: bool) {
+        let registered: Vec<_> = self
+            .sessions
+            .values()
+            .filter(|entry| entry.registered)
+            .collect();
+        let commands = (!registered.is_empty()).then(|| {
+            Arc::new(
+                registered
+                    .iter()
+                    .flat_map(|entry| entry.session.commands())
+                    .collect(),
+            )
+        });
+        self.plugin_commands.send_if_modified(|current| {
+            let changed = *current != commands;
+            *current = commands;
+            changed || reconnected
+        });
+    }
+
+    /// Records that a plugin polled or is still connected over the WebSocket. The session
+    /// is tracked even if the plugin never registered, e.g. when it reconnects after a
+    /// server restart.
+    fn plugin_seen(&mut self, session: Option<&str>) {
+        self.last_plugin_poll = Some(Instant::now());
+        let Some(id) = session else {
+            return;
+        };
+        self.sessions
+            .entry(id.to_string())
+            .or_insert_with(|| StudioSessionEntry {
+                session: StudioSession {
+                    id: id.to_string(),
+                    place_name: None,
+                    place_id: None,
+                    tools: None,
+                },
+                last_seen: Instant::now(),
+                registered: false,
+            })
+            .last_seen = Instant::now();
+    }
+
+    /// Queues a command for the plugin unless the queue is full or the command repeats
+    /// an earlier call with the same idempotency key.
+    fn enqueue(&mut self, id: Uuid, command: ToolArguments, config: &Config) -> Enqueued {
+        // A proxying instance forwards the key, the owner of the plugin port tracks it
+        let key = command.idempotency_key.clone().filter(|_| !self.proxied);
+        if let Some(key) = &key {
+            let window = config.idempotency_window();
+            self.idempotency
+                .retain(|_, entry| entry.at.elapsed() < window);
+            if let Some(entry) = self.idempotency.get(key) {
+                return match &entry.result {
+                    Some(result) => Enqueued::Cached(result.clone()),
+                    None => Enqueued::Duplicate(entry.id),
+                };
+            }
+        }
+        if self.process_queue.len() >= config.max_queue_depth {
+            return Enqueued::Full;
+        }
+        if let Some(key) = key {
+            let entry = IdempotencyEntry {
+                id,
+                result: None,
+                at: Instant::now(),
+            };
+            self.idempotency.insert(key, entry);
+        }
+        let (tx, rx) = oneshot::channel();
+        self.output_map.insert(id, tx);
+        self.process_queue.push_back(command.priority, command);
+        // The state keeps its own receiver, so this can't fail
+        self.trigger.send(()).ok();
+        Enqueued::Waiting(rx)
+    }
+
+    /// Keeps the result of a command started with an idempotency key for later retries
+    fn remember_result(&mut self, id: Uuid, result: Result<String, String>) {
+        if let Some(entry) = self
+            .idempotency
+            .values_mut()
+            .find(|entry| entry.id == id && entry.result.is_none())
+        {
+            entry.result = Some(result);
+            entry.at = Instant::now();
+        }
+    }
+
+    /// Lets a retry with the same idempotency key run the command again
+    fn forget_key(&mut self, id: Uuid) {
+        self.idempotency.retain(|_, entry| entry.id != id);
+    }
+
+    /// Whether a plugin polled or kept its WebSocket alive recently
+    fn plugin_connected(&self, config: &Config) -> bool {
+        let poll_interval = config.long_poll_timeout().max(WS_HEARTBEAT_INTERVAL);
+        self.last_plugin_poll
+            .is_some_and(|at| at.elapsed() < poll_interval + PLUGIN_SEEN_GRACE)
+    }
+
+    /// Forgets a command whose caller stopped waiting for it. Returns whether the plugin
+    /// had not picked it up yet.
+    fn abandon_command(&mut self, id: Uuid) -> bool {
+        self.output_map.remove(&id);
+        self.progress.remove(&id);
+        if self.in_flight.remove(&id).is_some() {
+            self.trigger.send(()).ok();
+        }
+        let queued = self.process_queue.len();
+        self.process_queue.retain(|command| command.id != Some(id));
+        let never_ran = self.process_queue.len() != queued;
+        if never_ran {
+            self.forget_key(id);
+        }
+        never_ran
+    }
+
+    /// Fails a command on behalf of its caller. Returns false if nothing is waiting for it.
+    fn cancel_command(&mut self, id: Uuid) -> bool {
+        let tx = self.output_map.remove(&id);
+        if tx.is_none() && !self.recovered.remove(&id) {
+            return false;
+        }
+        self.forget_key(id);
+        let message = if self.abandon_command(id) {
+            "Cancelled before Studio ran it"
+        } else {
+            "Cancelled while Studio was running it, the change may still be applied"
+        };
+        if let Some(tx) = tx {
+            tx.send(Err(eyre!(message).into())).ok();
+        }
+        true
+    }
+
+    /// Snapshot of the unfinished commands for the on-disk journal
+    pub fn journal(&self) -> Journal {
+        Journal {
+            queued: self.process_queue.iter().cloned().collect(),
+            running: self
+                .in_flight
+                .values()
+                .map(|lease| lease.command.clone())
+                .collect(),
+        }
+    }
+
+    /// Queues the commands a previous run left unfinished. Their callers are gone, so
+    /// read-only commands are dropped and commands Studio was running aren't repeated
+    /// since they may have partly applied.
+    pub fn restore_journal(&mut self, journal: Journal) {
+        for command in journal.running {
+            tracing::warn!(
+                "{} was running in Studio when the server stopped and may have been partly applied, it won't be run again",
+                command.args.tool_name()
+            );
+        }
+        for command in journal.queued {
+            let (Some(id), false) = (command.id, command.args.is_read_only()) else {
+                continue;
+            };
+            tracing::warn!(
+                "Offering {} {id} to Studio again, it was still queued when the server stopped",
+                command.args.tool_name()
+            );
+            self.recovered.insert(id);
+            self.process_queue.push_back(command.priority, command);
+        }
+        self.trigger.send(()).ok();
+    }
+
+    /// Hands a reply from the plugin to the tool call waiting on it.
+    fn deliver_response(&mut self, payload: RunCommandResponse) -> Result<()> {
+        self.in_flight.remove(&payload.id);
+        // A WebSocket may be holding back its next command until this one finished
+        self.trigger.send(()).ok();
+        self.progress.remove(&payload.id);
+        self.remember_result(payload.id, Ok(payload.response.clone()));
+        if self.recovered.remove(&payload.id) {
+            tracing::info!(
+                "Recovered command {} finished: {}",
+                payload.id,
+                payload.response
+            );
+            return Ok(());
+        }
+        let tx = self
+            .output_map
+            .remove(&payload.id)
+            .ok_or_eyre("Unknown ID")?;
+        tx.send(Ok(payload.response))
+            .map_err(|_| eyre!("Tool call {} is no longer waiting", payload.id))?;
+        Ok(())
+    }
+
+    /// Passes progress from the plugin on to the tool call waiting for it. Returns false
+    /// if no tool call asked for progress on that command.
+    fn report_progress(&mut self, update: ProgressUpdate) -> bool {
+        let id = update.id;
+        let Some(tx) = self.progress.get(&id) else {
+            return false;
+        };
+        if tx.send(update).is_err() {
+            self.progress.remove(&id);
+            return false;
+        }
+        true
+    }
+
+    fn subscribe(&mut self, path: String, subscriber: Uuid, peer: Peer<RoleServer>) {
+        let new_path = !self.subscriptions.contains_key(&path);
+        self.subscriptions
+            .entry(path)
+            .or_default()
+            .insert(subscriber, peer);
+        if new_path {
+            self.push_watches(None);
+        }
+    }
+
+    fn unsubscribe(&mut self, path: &str, subscriber: Uuid) {
+        let Some(subscribers) = self.subscriptions.get_mut(path) else {
+            return;
+        };
+        subscribers.remove(&subscriber);
+        if subscribers.is_empty() {
+            self.subscriptions.remove(path);
+            self.push_watches(None);
+        }
+    }
+
+    fn log_listeners(&self) -> Vec<(Uuid, Peer<RoleServer>, LoggingLevel)> {
+        self.log_listeners
+            .iter()
+            .map(|(listener, entry)| (*listener, entry.peer.clone(), entry.level))
+            .collect()
+    }
+
+    /// Clients to notify about the changed paths
+    fn subscribers_of(&self, paths: &[String]) -> Vec<(String, Uuid, Peer<RoleServer>)> {
+        paths
+            .iter()
+            .filter_map(|path| Some((path, self.subscriptions.get(path)?)))
+            .flat_map(|(path, subscribers)| {
+                subscribers
+                    .iter()
+                    .map(|(subscriber, peer)| (path.clone(), *subscriber, peer.clone()))
+            })
+            .collect()
+    }
+
+    /// Sends the subscribed paths to the plugin of `session`, or of every connected
+    /// window, replacing whatever it watched before. A watch list that hasn't been
+    /// picked up yet is superseded, and the list skips the queue limit since it is small
+    /// and losing it would silently stop notifications.
+    fn push_watches(&mut self, session: Option<String>) {
+        let mut paths: Vec<_> = self.subscriptions.keys().cloned().collect();
+        paths.sort();
+        let sessions = match session {
+            Some(session) => vec![Some(session)],
+            None => {
+                let active: Vec<_> = self
+                    .active_sessions()
+                    .into_iter()
+                    .map(|session| Some(session.id))
+                    .collect();
+                if active.is_empty() {
+                    vec![None]
+                } else {
+                    active
+                }
+            }
+        };
+        for session in sessions {
+            let superseded: Vec<_> = self
+                .process_queue
+                .iter()
+                .filter(|command| {
+                    matches!(command.args, ToolArgumentValues::WatchInstances(_))
+                        && command.session == session
+                })
+                .filter_map(|command| command.id)
+                .collect();
+            for id in superseded {
+                self.abandon_command(id);
+            }
+            let args = ToolArgumentValues::WatchInstances(WatchInstances {
+                paths: paths.clone(),
+            });
+            let priority = args.default_priority();
+            let (command, id) = ToolArguments::new(args, session, priority, None);
+            let (tx, rx) = oneshot::channel();
+            self.output_map.insert(id, tx);
+            self.process_queue.push_back(priority, command);
+            tokio::spawn(async move {
+                match rx.await {
+                    Ok(Ok(reply)) => tracing::debug!("Studio is watching {reply}"),
+                    Ok(Err(e)) => {
+                        tracing::warn!("Studio could not watch the subscribed resources: {e}")
+                    }
+                    // Superseded by a newer watch list
+                    Err(_) => {}
+                }
+            });
+        }
+        self.trigger.send(()).ok();
+    }
+
+    /// Commands Studio is running followed by the queue in the order it will be handed out
+    fn pending_operations(&self) -> Vec<PendingOperation> {
+        let operation = |command: &ToolArguments, status| PendingOperation {
+            id: command.id.unwrap_or_default(),
+            tool: command.args.tool_name().to_string(),
+            status,
+            session: command.session.clone(),
+            priority: command.priority,
+            age_secs: command.queued_at.elapsed().as_secs(),
+        };
+        let mut running: Vec<_> = self
+            .in_flight
+            .values()
+            .map(|lease| operation(&lease.command, OperationStatus::Running))
+            .collect();
+        running.sort_by_key(|operation| std::cmp::Reverse(operation.age_secs));
+        let queued = self
+            .process_queue
+            .iter()
+            .map(|command| operation(command, OperationStatus::Queued));
+        running.into_iter().chain(queued).collect()
+    }
+
+    /// Takes the next command for `session` off the queue and leases it to that session
+    /// until the plugin replies.
+    fn lease_next(&mut self, session: Option<&str>) -> Option<ToolArguments> {
+        let command = self
+            .process_queue
+            .pop_first(|command| command.runs_in(session))?;
+        if let Some(id) = command.id {
+            let lease = Lease {
+                command: command.clone(),
+                holder: session.map(str::to_string),
+            };
+            self.in_flight.insert(id, lease);
+        }
+        Some(command)
+    }
+
+    /// Keeps a viewport capture for the `capture_viewport` call it was taken for
+    fn store_capture(&mut self, id: Uuid, png: Vec<u8>) {
+        self.captures
+            .retain(|_, capture| capture.at.elapsed() < CAPTURE_TTL);
+        let capture = StoredCapture {
+            png,
+            at: Instant::now(),
+        };
+        self.captures.insert(id, capture);
+    }
+
+    fn take_capture(&mut self, id: Uuid) -> Option<Vec<u8>> {
+        self.captures.remove(&id).map(|capture| capture.png)
+    }
+
+    /// Chunk `index` of the script source or heightmap sent with the running command `id`
+    fn source_chunk(&self, id: Uuid, index: usize) -> Option<String> {
+        let source = self.in_flight.get(&id)?.command.args.chunked_text()?;
+        source_chunks(source)
+            .get(index)
+            .map(|chunk| chunk.to_string())
+    }
+
+    /// Leases the next command for a plugin that gets commands pushed over a WebSocket
+    /// while it still runs earlier ones. Like `lease_batch`, edits run alone: nothing is
+    /// leased while an edit is running for `session`, and an edit waits for the running
+    /// lookups to finish.
+    fn lease_beside_running(&mut self, session: Option<&str>) -> Option<ToolArguments> {
+        let mut running = self
+            .in_flight
+            .values()
+            .filter(|lease| lease.holder.as_deref() == session)
+            .peekable();
+        let busy = running.peek().is_some();
+        if running.any(|lease| !lease.command.args.is_read_only()) {
+            return None;
+        }
+        let next_is_edit = self
+            .process_queue
+            .first(|command| command.runs_in(session))
+            .is_some_and(|command| !command.args.is_read_only());
+        if busy && next_is_edit {
+            return None;
+        }
+        self.lease_next(session)
+    }
+
+    /// Leases the read-only commands queued right behind `first` along with it, so quick
+    /// lookups reach Studio together. Edits always go alone since they can't safely run
+    /// side by side.
+    fn lease_batch(
+        &mut self,
+        first: ToolArguments,
+        session: Option<&str>,
+        size: usize,
+    ) -> Vec<ToolArguments> {
+        let mut batch = vec![first];
+        if !batch[0].args.is_read_only() {
+            return batch;
+        }
+        while batch.len() < size
+            && self
+                .process_queue
+                .first(|command| command.runs_in(session))
+                .is_some_and(|command| command.args.is_read_only())
+        {
+            batch.extend(self.lease_next(session));
+        }
+        batch
+    }
+
+    /// Puts a leased command back at the front of the queue, e.g. when it couldn't be
+    /// sent to the plugin.
+    fn release(&mut self, command: ToolArguments) {
+        if let Some(id) = command.id {
+            self.in_flight.remove(&id);
+        }
+        self.process_queue.push_front(command.priority, command);
+        self.trigger.send(()).ok();
+    }
+
+    /// Takes commands back from plugins that went quiet, e.g. because Studio crashed.
+    /// Read-only commands are offered again once, anything else fails because it may
+    /// have partly run.
+    fn reclaim_expired_leases(&mut self, lease_timeout: Duration) {
+        let expired: Vec<Uuid> = self
+            .in_flight
+            .iter()
+            .filter(|(_, lease)| {
+                let last_seen = match &lease.holder {
+                    Some(holder) => self.sessions.get(holder).map(|entry| entry.last_seen),
+                    None => self.last_plugin_poll,
+                };
+                last_seen.is_none_or(|at| at.elapsed() >= lease_timeout)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            let Some(Lease { mut command, .. }) = self.in_flight.remove(&id) else {
+                continue;
+            };
+            let tool = command.args.tool_name();
+            self.recovered.remove(&id);
+            if command.args.is_read_only() && command.retries == 0 {
+                tracing::warn!("Studio stopped responding to {tool} {id}, offering it again");
+                command.retries += 1;
+                self.process_queue.push_front(command.priority, command);
+            } else {
+                tracing::warn!("Studio stopped responding to {tool} {id}, failing it");
+                let message = format!(
+                    "Studio stopped responding while running {tool}. The change may have been partly applied, check the place before retrying"
+                );
+                self.remember_result(id, Err(message.clone()));
+                self.progress.remove(&id);
+                if let Some(tx) = self.output_map.remove(&id) {
+                    tx.send(Err(eyre!(message).into())).ok();
+                }
+            }
+        }
+    }
+
+    fn active_sessions(&mut self) -> Vec<StudioSession> {
+        self.sessions
+            .retain(|_, entry| entry.last_seen.elapsed() < STUDIO_SESSION_TIMEOUT);
+        self.update_plugin_commands(false);
+        let mut sessions: Vec<_> = self
+            .sessions
+            .values()
+            .map(|entry| entry.session.clone())
+            .collect();
+        sessions.sort_by(|a, b| a.id.cmp(&b.id));
+        sessions
+    }
 }
 
 impl ToolArguments {
-    fn new(args: ToolArgumentValues) -> (Self, Uuid) {
-        Self { args, id: None }.with_id()
+    fn new(
+        args: ToolArgumentValues,
+        session: Option<String>,
+        priority: Priority,
+        idempotency_key: Option<String>,
+    ) -> (Self, Uuid) {
+        Self {
+            args,
+            id: None,
+            session,
+            priority,
+            idempotency_key,
+            retries: 0,
+            queued_at: Instant::now(),
+        }
+        .with_id()
+    }
+    /// Whether the plugin of `session` may take this command
+    fn runs_in(&self, session: Option<&str>) -> bool {
+        self.session.is_none() || self.session.as_deref() == session
+    }
+    /// The command as sent to the plugin. Script sources and heightmaps too large for one
+    /// message are replaced by where to fetch them in chunks.
+    fn for_plugin(&self) -> Self {
+        let mut command = self.clone();
+        if let Some(id) = self.id {
+            command.args.chunk_text(id);
+        }
+        command
     }
     fn with_id(self) -> (Self, Uuid) {
         let id = Uuid::new_v4();
         (
             Self {
-                args: self.args,
                 id: Some(id),
+                ..self
             },
             id,
         )
     }
 }
+/// Splits a script source or heightmap into chunks of at most `SCRIPT_CHUNK_BYTES`, cut
+/// between characters
+fn source_chunks(source: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = source;
+    while !rest.is_empty() {
+        let mut end = rest.len().min(SCRIPT_CHUNK_BYTES);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+/// Groups script writes into commands of at most `SCRIPT_CHUNK_BYTES` of source. A larger
+/// script goes on its own, with its source sent in chunks.
+fn write_batches(writes: Vec<ScriptWrite>) -> Vec<Vec<ScriptWrite>> {
+    let mut batches: Vec<Vec<ScriptWrite>> = Vec::new();
+    let mut size = 0;
+    for write in writes {
+        let fits = size + write.source.len() <= SCRIPT_CHUNK_BYTES;
+        match batches.last_mut() {
+            Some(batch) if fits => {
+                size += write.source.len();
+                batch.push(write);
+            }
+            _ => {
+                size = write.source.len();
+                batches.push(vec![write]);
+            }
+        }
+    }
+    batches
+}
+
 #[derive(Clone)]
 pub struct RBXStudioServer {
     state: PackedState,
+    config: Arc<Config>,
+    /// Studio session picked with `select_studio_session` for this MCP connection
+    selected_session: Arc<Mutex<Option<String>>>,
+    /// Identifies this MCP connection's resource subscriptions and log level
+    subscriber: Uuid,
+    /// Oversized results returned to this MCP connection, read a page at a time
+    result_pages: Arc<Mutex<HashMap<Uuid, StoredResult>>>,
+    /// Instance paths last listed for completion
+    instance_tree: Arc<Mutex<Option<InstanceTree>>>,
+    /// Marketplace searches made over this MCP connection, keyed by query and filters
+    marketplace_searches: Arc<Mutex<HashMap<String, CachedSearch>>>,
     tool_router: ToolRouter<Self>,
 }
 
-#[tool_handler]
+#[tool_handler(router = self.available_tools())]
 impl ServerHandler for RBXStudioServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::LATEST,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .enable_prompts()
+                .enable_completions()
+                .enable_logging()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
             server_info: Implementation {
                 name: "Roblox_Studio".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
@@ -87,53 +1204,352 @@ impl ServerHandler for RBXStudioServer {
                 icons: None,
                 website_url: None,
             },
-            instructions: Some(
-                "User run_command to query data from Roblox Studio place or to change it"
-                    .to_string(),
-            ),
+            instructions: Some(format!(
+                "User run_command to query data from Roblox Studio place or to change it. \
+                 The Studio plugin connects to this server on port {}",
+                self.config.port
+            )),
         }
     }
-}
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct RunCode {
-    #[schemars(description = "Code to run")]
-    command: String,
-}
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct InsertModel {
-    #[schemars(description = "Query to search for the model")]
-    query: String,
-}
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        // Studio output and plugin connections only reach the instance that owns the
+        // plugin port
+        let listener = self.subscriber;
+        let peer = context.peer.clone();
+        let proxied = self
+            .state
+            .call(move |state| {
+                if !state.proxied {
+                    let listener_entry = LogListener {
+                        peer,
+                        level: LoggingLevel::Info,
+                    };
+                    state.log_listeners.insert(listener, listener_entry);
+                }
+                state.proxied
+            })
+            .await;
+        if proxied {
+            return;
+        }
+        let mut plugin_commands = self.state.plugin_commands();
+        plugin_commands.borrow_and_update();
+        tokio::spawn(async move {
+            while plugin_commands.changed().await.is_ok() {
+                if context.peer.notify_tool_list_changed().await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct Position {
-    x: f64,
-    y: f64,
-    z: f64,
-}
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let listener = self.subscriber;
+        self.state
+            .call(move |state| {
+                if !state.proxied {
+                    let listener_entry = LogListener {
+                        peer: context.peer,
+                        level: request.level,
+                    };
+                    state.log_listeners.insert(listener, listener_entry);
+                }
+            })
+            .await;
+        Ok(())
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct Rotation {
-    x: f64,
-    y: f64,
-    z: f64,
-}
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        Ok(ListPromptsResult::with_all_items(prompts::list()))
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct Scale {
-    x: f64,
-    y: f64,
-    z: f64,
-}
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        prompts::get(&request.name, request.arguments)
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct BatchModelEntry {
-    #[schemars(description = "Query to search for the model in the marketplace")]
-    query: String,
-    #[schemars(description = "Position to place the model (x, y, z)")]
-    position: Option<Position>,
-    #[schemars(description = "Rotation in degrees (x, y, z)")]
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        let resources = RESOURCE_ROOTS
+            .iter()
+            .map(|root| {
+                let mut resource = RawResource::new(format!("{RESOURCE_SCHEME}{root}"), *root);
+                resource.mime_type = Some("application/json".to_string());
+                resource.no_annotation()
+            })
+            .collect();
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        let template = RawResourceTemplate {
+            uri_template: format!("{RESOURCE_SCHEME}{{+path}}"),
+            name: "instance".to_string(),
+            title: Some("Roblox instance".to_string()),
+            description: Some(
+                "An instance in the open place, e.g. roblox://workspace/Map/SpawnLocation. Returns its class, common properties, attributes and children with their URIs"
+                    .to_string(),
+            ),
+            mime_type: Some("application/json".to_string()),
+            icons: None,
+        };
+        Ok(ListResourceTemplatesResult::with_all_items(vec![
+            template.no_annotation()
+        ]))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let path = resource_path(&request.uri)?;
+        let args = ToolArgumentValues::ReadInstance(ReadInstance { path });
+        let text = match self.run_command(args, context).await? {
+            Ok(text) => text,
+            Err(failed) => {
+                let message = failed
+                    .content
+                    .first()
+                    .and_then(|content| content.as_text())
+                    .map(|content| content.text.clone())
+                    .unwrap_or_default();
+                return Err(ErrorData::internal_error(message, None));
+            }
+        };
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some("application/json".to_string()),
+                text,
+                meta: None,
+            }],
+        })
+    }
+
+    async fn complete(
+        &self,
+        request: CompleteRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, ErrorData> {
+        let typed = request.argument.value.as_str();
+        let completion = match &request.r#ref {
+            Reference::Resource(resource)
+                if resource.uri.starts_with(RESOURCE_SCHEME) && request.argument.name == "path" =>
+            {
+                completion::instance_paths(&self.instance_tree(context).await, typed, '/')
+            }
+            Reference::Prompt(prompt) => {
+                match prompts::completion(&prompt.name, &request.argument.name) {
+                    Some(Completion::InstancePath) => {
+                        completion::instance_paths(&self.instance_tree(context).await, typed, '.')
+                    }
+                    Some(Completion::TerrainMaterial) => {
+                        completion::matching(completion::TERRAIN_MATERIALS, typed)
+                    }
+                    Some(Completion::SculptMode) => {
+                        completion::matching(completion::SCULPT_MODES, typed)
+                    }
+                    None => CompletionInfo::default(),
+                }
+            }
+            Reference::Resource(_) => CompletionInfo::default(),
+        };
+        Ok(CompleteResult { completion })
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let path = resource_path(&request.uri)?;
+        let subscriber = self.subscriber;
+        let subscribed = self
+            .state
+            .call(move |state| {
+                // Changes are posted to the instance that owns the plugin port
+                if state.proxied {
+                    return false;
+                }
+                state.subscribe(path, subscriber, context.peer);
+                true
+            })
+            .await;
+        if !subscribed {
+            return Err(ErrorData::invalid_request(
+                "Another MCP server instance is connected to Studio, subscribe through that one",
+                None,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let path = resource_path(&request.uri)?;
+        let subscriber = self.subscriber;
+        self.state
+            .call(move |state| state.unsubscribe(&path, subscriber))
+            .await;
+        Ok(())
+    }
+}
+
+/// Name of the plugin command behind a tool, e.g. `GetStudioMode` for `get_studio_mode`
+fn command_name(tool: &str) -> String {
+    tool.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Plugin commands a tool needs. Tools that do their work on the server need the commands
+/// that fetch what they work on and write it back.
+fn plugin_commands(tool: &str) -> Vec<String> {
+    match tool {
+        "search_code" | "lint_scripts" | "get_module_dependencies" => {
+            vec!["GetScriptSources".to_string()]
+        }
+        "format_scripts" | "rename_symbol" => vec![
+            "GetScriptSources".to_string(),
+            "SetScriptSources".to_string(),
+        ],
+        _ => vec![command_name(tool)],
+    }
+}
+
+/// The instance path of a `roblox://` resource URI
+fn resource_path(uri: &str) -> Result<String, ErrorData> {
+    uri.strip_prefix(RESOURCE_SCHEME)
+        .map(str::to_string)
+        .ok_or_else(|| {
+            ErrorData::resource_not_found(
+                format!("Resource URIs start with {RESOURCE_SCHEME}"),
+                None,
+            )
+        })
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RunCode {
+    #[schemars(description = "Code to run")]
+    command: String,
+}
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct InsertModel {
+    #[schemars(description = "Query to search for the model")]
+    query: String,
+    #[schemars(
+        description = "Asset ID of a search_marketplace result to insert instead of the first hit for the query, which still names the model. The filters don't apply to it"
+    )]
+    asset_id: Option<u64>,
+    #[schemars(description = "Which results of the query may be inserted")]
+    filters: Option<MarketplaceFilters>,
+    #[schemars(
+        description = "Search the marketplace again instead of reusing the asset found for the same query earlier"
+    )]
+    force_refresh: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SearchMarketplace {
+    #[schemars(description = "Query to search the marketplace's free models for")]
+    query: String,
+    #[schemars(description = "Most results to return (default: 10, at most 50)")]
+    limit: Option<u32>,
+    #[schemars(description = "Which results to return")]
+    filters: Option<MarketplaceFilters>,
+    #[schemars(
+        description = "Search the marketplace again instead of reusing results for the same query and filters"
+    )]
+    force_refresh: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone, Default)]
+struct MarketplaceFilters {
+    #[schemars(
+        description = "Only assets from creators with the verified badge (default: true unless turned off in the server config)"
+    )]
+    verified_only: Option<bool>,
+    #[schemars(description = "Only assets made by this user ID")]
+    creator_id: Option<u64>,
+    #[schemars(description = "Only assets made by this group ID")]
+    group_id: Option<u64>,
+    #[schemars(description = "Only assets favorited at least this many times")]
+    min_favorites: Option<u64>,
+    #[schemars(description = "Only assets created at least this many days ago")]
+    min_age_days: Option<u32>,
+    #[schemars(description = "Only assets created at most this many days ago")]
+    max_age_days: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Position {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Rotation {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Scale {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Size {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct BatchModelEntry {
+    #[schemars(description = "Query to search for the model in the marketplace")]
+    query: String,
+    #[schemars(
+        description = "Asset ID of a search_marketplace result to insert instead of the first hit for the query, which still names the model"
+    )]
+    asset_id: Option<u64>,
+    #[schemars(description = "Position to place the model (x, y, z)")]
+    position: Option<Position>,
+    #[schemars(description = "Rotation in degrees (x, y, z)")]
     rotation: Option<Rotation>,
     #[schemars(description = "Scale multiplier (x, y, z)")]
     scale: Option<Scale>,
@@ -147,6 +1563,16 @@ struct BatchModelEntry {
 struct BatchInsertModels {
     #[schemars(description = "Array of models to insert")]
     models: Vec<BatchModelEntry>,
+    #[schemars(
+        description = "Search the marketplace again instead of reusing the assets found for the same queries earlier"
+    )]
+    force_refresh: Option<bool>,
+    #[schemars(
+        description = "exact to place models where asked, or grounded to set each on the terrain or parts below its position and move it the least distance needed to stay min_spacing away from the models placed before it (default: exact)"
+    )]
+    placement: Option<String>,
+    #[schemars(description = "Studs kept between grounded models (default: 2)")]
+    min_spacing: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
@@ -173,6 +1599,32 @@ struct Region {
     max: Position,
 }
 
+impl Region {
+    /// The region cut into tiles along X and Z, see `terrain_tiles::split`
+    fn tiles(&self, gap: f64) -> Vec<Region> {
+        let columns = terrain_tiles::split(self.min.x, self.max.x, gap);
+        let rows = terrain_tiles::split(self.min.z, self.max.z, gap);
+        let mut tiles = Vec::with_capacity(columns.len() * rows.len());
+        for &(min_x, max_x) in &columns {
+            for &(min_z, max_z) in &rows {
+                tiles.push(Region {
+                    min: Position {
+                        x: min_x,
+                        y: self.min.y,
+                        z: min_z,
+                    },
+                    max: Position {
+                        x: max_x,
+                        y: self.max.y,
+                        z: max_z,
+                    },
+                });
+            }
+        }
+        tiles
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
 struct HeightmapConfig {
     #[schemars(description = "Type of heightmap: flat, perlin, or ridged")]
@@ -185,6 +1637,48 @@ struct HeightmapConfig {
     seed: Option<i32>,
 }
 
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CaveConfig {
+    #[schemars(description = "How much of the ground is tunnels, from 0 to 1 (default: 0.5)")]
+    density: Option<f64>,
+    #[schemars(description = "Radius of the tunnels in studs (default: 8)")]
+    tunnel_radius: Option<f64>,
+    #[schemars(description = "Studs of solid ground left above the tunnels (default: 8). Small values leave overhangs")]
+    surface_depth: Option<f64>,
+    #[schemars(description = "Shafts dug from the surface down into the caves (default: 2)")]
+    entrances: Option<u32>,
+    #[schemars(description = "Random seed for the caves, defaults to the terrain's")]
+    seed: Option<i32>,
+}
+
+impl CaveConfig {
+    /// The caves of one of `tiles` tiles, with the entrances shared out between them
+    fn tile_share(&self, tile: usize, tiles: usize) -> CaveConfig {
+        let entrances = self.entrances.unwrap_or(DEFAULT_CAVE_ENTRANCES) as usize;
+        let share = entrances / tiles + usize::from(tile < entrances % tiles);
+        CaveConfig {
+            entrances: Some(share as u32),
+            ..self.clone()
+        }
+    }
+
+    /// Why the plugin can't carve these caves, if anything
+    fn problem(&self) -> Option<String> {
+        if self
+            .density
+            .is_some_and(|density| !(0.0..=1.0).contains(&density))
+        {
+            Some("caves.density must be from 0 to 1".to_string())
+        } else if self.tunnel_radius.is_some_and(|radius| radius < 2.0) {
+            Some("caves.tunnel_radius must be at least 2 studs".to_string())
+        } else if self.surface_depth.is_some_and(|depth| depth < 0.0) {
+            Some("caves.surface_depth can't be negative".to_string())
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
 struct GenerateTerrain {
     #[schemars(description = "Region to generate terrain in (min/max positions)")]
@@ -195,6 +1689,38 @@ struct GenerateTerrain {
     heightmap: Option<HeightmapConfig>,
     #[schemars(description = "Y level for water fill")]
     water_level: Option<f64>,
+    #[schemars(description = "Carve caves and overhangs into the terrain with 3D noise")]
+    caves: Option<CaveConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Biome {
+    #[schemars(description = "Name reported back with the columns it covered, e.g. 'forest'")]
+    name: String,
+    #[schemars(description = "Terrain materials from the biome's lowest ground to its peaks, e.g. ['Grass', 'Rock', 'Snow']")]
+    materials: Vec<String>,
+    #[schemars(description = "Height of the biome's hills in studs")]
+    amplitude: f64,
+    #[schemars(description = "Detail level/frequency of its hills (default: 0.02)")]
+    frequency: Option<f64>,
+    #[schemars(description = "Chance from 0 to 1 of a tree on each 4 stud column (default: 0)")]
+    vegetation_density: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GenerateBiomes {
+    #[schemars(description = "Region to generate terrain in (min/max positions)")]
+    region: Region,
+    #[schemars(description = "Biomes to blend, e.g. plains, forest and mountains")]
+    biomes: Vec<Biome>,
+    #[schemars(description = "Rough width of each biome's patches in studs (default: 256)")]
+    biome_size: Option<f64>,
+    #[schemars(description = "Random seed for the terrain, biome layout and trees")]
+    seed: Option<i32>,
+    #[schemars(description = "Y level to flood with water")]
+    water_level: Option<f64>,
+    #[schemars(description = "Carve caves and overhangs into the terrain with 3D noise")]
+    caves: Option<CaveConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
@@ -207,6 +1733,134 @@ struct FillTerrainRegion {
     replace_air: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CarvePath {
+    #[schemars(description = "Points the path runs through in order, at least two. The path surface follows their Y")]
+    waypoints: Vec<Position>,
+    #[schemars(description = "Width of the path in studs")]
+    width: f64,
+    #[schemars(description = "Terrain material of the path surface, e.g. Asphalt, Pavement, Cobblestone or Ground")]
+    material: String,
+    #[schemars(description = "Line both sides with guard rail parts, in a GuardRails folder in Workspace (default: false)")]
+    guard_rails: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CarveRiver {
+    #[schemars(description = "Where the river starts, at its water surface")]
+    from: Position,
+    #[schemars(description = "Where the river ends, at its water surface")]
+    to: Position,
+    #[schemars(description = "Points between from and to for the river to run through in order")]
+    waypoints: Option<Vec<Position>>,
+    #[schemars(description = "Width of the river in studs (default: 16)")]
+    width: Option<f64>,
+    #[schemars(description = "Depth of the water in studs (default: 6)")]
+    depth: Option<f64>,
+    #[schemars(description = "How far the banks slope back from the water in studs (default: half the width)")]
+    bank_width: Option<f64>,
+    #[schemars(description = "How far the river wanders sideways when no waypoints are given, in studs (default: the width, 0 for straight)")]
+    meander: Option<f64>,
+    #[schemars(description = "Random seed for the meanders")]
+    seed: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SaveTerrainStamp {
+    #[schemars(description = "Name to save the stamp as, letters, digits, '-' and '_'")]
+    name: String,
+    #[schemars(description = "Region to save (min/max positions), grown to whole 4 stud voxels")]
+    region: Region,
+    #[schemars(description = "Replace a stamp saved under the same name (default: false)")]
+    overwrite: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct PlaceTerrainStamp {
+    #[schemars(description = "Name of a stamp saved with save_terrain_stamp")]
+    name: String,
+    #[schemars(description = "Where the centre of the stamp's bottom goes, snapped to the 4 stud voxel grid")]
+    position: Position,
+    #[schemars(description = "Degrees to turn the stamp about Y, a multiple of 90 (default: 0)")]
+    rotation: Option<f64>,
+    #[schemars(description = "add (default) keeps the fuller of the stamp and the terrain in each voxel, replace writes every voxel of the stamp including empty ones")]
+    mode: Option<String>,
+}
+
+/// A saved stamp and where to put it, as sent to the plugin
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct StampPlacement {
+    name: String,
+    position: Position,
+    rotation: f64,
+    mode: String,
+    size: [u32; 3],
+    materials: Vec<String>,
+    /// Packed as in `terrain_stamps::Stamp`
+    voxels: String,
+    /// Set instead of `voxels` when they're sent to the plugin in chunks
+    #[schemars(skip)]
+    chunks: Option<SourceChunks>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ListTerrainStamps {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ReplaceTerrainMaterial {
+    #[schemars(description = "Region to replace in (min/max positions), grown to whole 4 stud voxels")]
+    region: Region,
+    #[schemars(description = "Terrain material to replace, as for generate_terrain or Water")]
+    from: String,
+    #[schemars(description = "Terrain material to replace it with, as for generate_terrain, or Water or Air to remove it")]
+    to: String,
+    #[schemars(description = "Only replace at or above this Y")]
+    min_height: Option<f64>,
+    #[schemars(description = "Only replace at or below this Y")]
+    max_height: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ImportHeightmap {
+    #[schemars(description = "Region to fill. The image is stretched over its X and Z extent, black is the bottom of the region and white the top")]
+    region: Region,
+    #[schemars(description = "Terrain material to fill with, as for generate_terrain")]
+    material: String,
+    #[schemars(description = "Path of a PNG or EXR image on the machine running the server")]
+    path: Option<String>,
+    #[schemars(description = "PNG or EXR image encoded as base64, instead of path")]
+    image_base64: Option<String>,
+    #[schemars(description = "Studs between terrain columns, at least 1, defaults to 4. The image is resampled to one height per column")]
+    resolution: Option<f64>,
+    #[schemars(description = "Y level for water fill")]
+    water_level: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ExportTerrain {
+    #[schemars(description = "Region to export (min/max positions), grown to whole 4 stud voxels")]
+    region: Region,
+    #[schemars(description = "Where to write the heightmap PNG on the machine running the server, e.g. 'terrain/island.png'. The material map is written next to it as island.materials.json")]
+    path: String,
+}
+
+/// Heights decoded from an `import_heightmap` image, as sent to the plugin
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct HeightmapColumns {
+    region: Region,
+    material: String,
+    resolution: f64,
+    /// Columns along X and rows along Z
+    columns: usize,
+    rows: usize,
+    /// Packed by `heightmap::encode`, row by row
+    heights: String,
+    water_level: Option<f64>,
+    /// Set instead of `heights` when they're sent to the plugin in chunks
+    #[schemars(skip)]
+    chunks: Option<SourceChunks>,
+}
+
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
 struct SculptPoint {
     #[schemars(description = "Position to sculpt at")]
@@ -247,12 +1901,20 @@ struct SaveScene {
     region: Option<Region>,
     #[schemars(description = "Instance names to exclude from save")]
     exclude_names: Option<Vec<String>>,
+    #[schemars(description = "What the scene holds, searched by find_scenes")]
+    description: Option<String>,
+    #[schemars(description = "Tags to find the scene by with find_scenes (e.g., ['castle', 'wip'])")]
+    tags: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
 struct LoadScene {
     #[schemars(description = "Name of the previously saved scene to load")]
     name: String,
+    #[schemars(
+        description = "Revision to load: a revision number from list_scene_revisions, or 0 or less to go back that many saves from the latest (-2 is two steps back). Defaults to the latest"
+    )]
+    revision: Option<i64>,
     #[schemars(description = "Position offset to apply to loaded objects")]
     position: Option<Position>,
     #[schemars(description = "Parent instance path (defaults to workspace)")]
@@ -261,6 +1923,131 @@ struct LoadScene {
     clear_existing: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ListSceneRevisions {
+    #[schemars(description = "Saved scene to list (defaults to every saved scene)")]
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindScenes {
+    #[schemars(
+        description = "Text to look for in scene names, descriptions and tags, ignoring case"
+    )]
+    text: Option<String>,
+    #[schemars(description = "Tags the scenes must all have, ignoring case")]
+    tags: Option<Vec<String>>,
+    #[schemars(description = "Most scenes to return, newest first (default: 50)")]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct PruneSceneHistory {
+    #[schemars(description = "Name of the saved scene to prune")]
+    name: String,
+    #[schemars(
+        description = "Newest revisions to keep (default: 1). 0 deletes the scene and its whole history"
+    )]
+    keep: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct UploadAsset {
+    #[schemars(description = "Path of a mesh (.fbx, .gltf, .glb), model (.rbxm, .rbxmx), audio (.mp3, .ogg, .wav, .flac) or image (.png, .jpg, .bmp, .tga) file on the machine running the server")]
+    path: String,
+    #[schemars(description = "Name of the new asset (defaults to the file name)")]
+    name: Option<String>,
+    #[schemars(description = "Description of the new asset")]
+    description: Option<String>,
+    #[schemars(description = "Wait until moderation approves or rejects the asset (default: true)")]
+    wait_for_moderation: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct DiffScenes {
+    #[schemars(description = "Name of the saved scene to compare from")]
+    from: String,
+    #[schemars(
+        description = "Name of the saved scene to compare to (defaults to the live workspace, read with the region and exclusions `from` was saved with)"
+    )]
+    to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ImportModelFile {
+    #[schemars(description = "Path of an .rbxm or .rbxmx file on the machine running the server")]
+    path: Option<String>,
+    #[schemars(description = "Model file encoded as base64, instead of path")]
+    data_base64: Option<String>,
+    #[schemars(description = "Parent instance path (defaults to workspace)")]
+    parent: Option<String>,
+    #[schemars(description = "Where to move the centre of the imported models, they keep the position saved in the file when unset")]
+    position: Option<Position>,
+    #[schemars(description = "Rotation in degrees (x, y, z) applied about the centre of the imported models")]
+    rotation: Option<Rotation>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Import3dFile {
+    #[schemars(description = "Path of an .obj, .fbx, .gltf or .glb file on the machine running the server")]
+    path: String,
+    #[schemars(description = "upload to add the file to Roblox with Open Cloud and insert the new asset, or editable_mesh to build it in Studio as an EditableMesh, which only .obj files can and which isn't saved with the place (default: upload when open_cloud_api_key is set)")]
+    method: Option<String>,
+    #[schemars(description = "Name of the inserted instance (defaults to the file name)")]
+    name: Option<String>,
+    #[schemars(description = "Parent instance path (defaults to workspace)")]
+    parent: Option<String>,
+    #[schemars(description = "Where to move the centre of the import (defaults to in front of the camera)")]
+    position: Option<Position>,
+    #[schemars(description = "Rotation in degrees (x, y, z)")]
+    rotation: Option<Rotation>,
+    #[schemars(description = "Studs per unit of the file (default: 1)")]
+    scale: Option<f64>,
+    #[schemars(description = "Asset ID of an image to texture the imported meshes with")]
+    texture_id: Option<u64>,
+}
+
+/// A 3D file and where to put it, as sent to the plugin
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct MeshImport {
+    name: String,
+    parent: Option<String>,
+    position: Option<Position>,
+    rotation: Option<Rotation>,
+    scale: Option<f64>,
+    texture_id: Option<u64>,
+    /// Uploaded asset holding the file, unset when `mesh` is built in Studio
+    asset_id: Option<u64>,
+    /// JSON `mesh_files::Mesh` to build an EditableMesh from, empty for uploads
+    mesh: String,
+    /// Set instead of `mesh` when it's sent to the plugin in chunks
+    #[schemars(skip)]
+    chunks: Option<SourceChunks>,
+}
+
+/// Instances decoded from a model file and where to put them, as sent to the plugin
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ModelInsertion {
+    parent: Option<String>,
+    position: Option<Position>,
+    rotation: Option<Rotation>,
+    /// JSON list of the file's top level `model_files::ModelInstance`s
+    instances: String,
+    /// Set instead of `instances` when they're sent to the plugin in chunks
+    #[schemars(skip)]
+    chunks: Option<SourceChunks>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ExportScene {
+    #[schemars(description = "Name of a saved scene to export")]
+    scene: Option<String>,
+    #[schemars(description = "Path of an instance to export with its descendants instead, e.g. 'workspace.Castle'")]
+    instance: Option<String>,
+    #[schemars(description = "Where to write the model on the machine running the server, e.g. 'models/castle.rbxm'. A .rbxm extension writes a binary model and .rbxmx an XML one")]
+    path: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
 struct GetConsoleLogs {
     #[schemars(description = "Only return logs with sequence number greater than this value. Use for polling to get new logs since last request.")]
@@ -273,6 +2060,28 @@ struct GetConsoleLogs {
     clear_after_read: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetOutputLog {
+    #[schemars(description = "Least severe output to return: 'info' (default, everything), 'warn' (warnings and errors) or 'error'")]
+    level: Option<String>,
+    #[schemars(description = "Only return output logged after this Unix time in seconds, e.g. the time of the last entry of the previous call")]
+    since: Option<f64>,
+    #[schemars(description = "Most entries to return, the latest ones (default: 100, max: 500)")]
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetPerformanceStats {
+    #[schemars(description = "Seconds to average the physics, heartbeat and network figures over, 0 for a single reading (default: 1, max: 10)")]
+    sample_seconds: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetMemoryReport {
+    #[schemars(description = "Most containers to list, the heaviest ones (default: 20, max: 100)")]
+    limit: Option<u32>,
+}
+
 #[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
 struct GetWorkspaceStats {
     #[schemars(description = "Optional path to analyze (defaults to entire Workspace)")]
@@ -283,424 +2092,6350 @@ struct GetWorkspaceStats {
     include_colors: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct GetChildrenInfo {
-    #[schemars(description = "Path to parent instance (e.g., 'workspace', 'workspace.MyModel', 'game.Lighting')")]
-    path: String,
-    #[schemars(description = "Include bounding box information for each child (min, max, size, center)")]
-    include_bounds: Option<bool>,
-}
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetChildrenInfo {
+    #[schemars(description = "Path to parent instance (e.g., 'workspace', 'workspace.MyModel', 'game.Lighting')")]
+    path: String,
+    #[schemars(description = "Include bounding box information for each child (min, max, size, center)")]
+    include_bounds: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetInstanceTree {
+    #[schemars(description = "Path to the root instance (e.g., 'workspace', 'workspace.MyModel', 'game.ReplicatedStorage')")]
+    path: String,
+    #[schemars(description = "How many levels below the root to include, defaults to 3")]
+    max_depth: Option<u32>,
+    #[schemars(description = "Properties to include for each instance that has them, e.g. ['Position', 'Size', 'Anchored']")]
+    properties: Option<Vec<String>>,
+    #[schemars(description = "Include the attributes of each instance")]
+    include_attributes: Option<bool>,
+    #[schemars(description = "Only include instances of these classes or their subclasses, e.g. ['BasePart', 'Model']. Matching descendants of other instances are still included")]
+    class_filter: Option<Vec<String>>,
+    #[schemars(description = "Stop after this many instances, defaults to 1000")]
+    max_nodes: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CreateInstance {
+    #[schemars(description = "Class of the new instance, e.g. 'Part', 'PointLight' or 'Folder'")]
+    class_name: String,
+    #[schemars(description = "Path to the parent instance (e.g., 'workspace', 'workspace.MyModel')")]
+    parent: String,
+    #[schemars(description = "Name of the new instance, defaults to the class name")]
+    name: Option<String>,
+    #[schemars(description = "Initial property values by name. Vector3 takes {x, y, z} or [x, y, z], Color3 takes {r, g, b} from 0 to 1, [r, g, b] from 0 to 255 or a hex string, CFrame takes {position, rotation} in degrees, enums take the item name and instance references take a path")]
+    properties: Option<JsonObject>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CreatePart {
+    #[schemars(description = "Block, Sphere, Cylinder, Wedge or CornerWedge (default: Block)")]
+    shape: Option<String>,
+    #[schemars(description = "Parent instance path (defaults to workspace)")]
+    parent: Option<String>,
+    #[schemars(description = "Name of the part (default: Part)")]
+    name: Option<String>,
+    #[schemars(description = "Size in studs. Spheres take the smallest axis for all three, and cylinders run along x")]
+    size: Option<Size>,
+    #[schemars(description = "Position of the part's centre (defaults to in front of the camera)")]
+    position: Option<Position>,
+    #[schemars(description = "Rotation in degrees (x, y, z)")]
+    rotation: Option<Rotation>,
+    #[schemars(description = "Hex color like '#FF8800' or a BrickColor name like 'Bright red'")]
+    color: Option<String>,
+    #[schemars(description = "Material name, e.g. Plastic, Wood, Concrete, Metal, Glass or Neon")]
+    material: Option<String>,
+    #[schemars(description = "Transparency from 0 (opaque) to 1 (invisible)")]
+    transparency: Option<f64>,
+    #[schemars(description = "Anchor the part (default: true)")]
+    anchored: Option<bool>,
+    #[schemars(description = "Whether other parts collide with it (default: true)")]
+    can_collide: Option<bool>,
+    #[schemars(description = "Whether it fires Touched events (default: true)")]
+    can_touch: Option<bool>,
+    #[schemars(description = "Whether raycasts and spatial queries see it (default: true)")]
+    can_query: Option<bool>,
+    #[schemars(description = "Name of the collision group it belongs to (default: Default)")]
+    collision_group: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct BuildStructure {
+    #[schemars(description = "Outer size along x in studs")]
+    width: f64,
+    #[schemars(description = "Outer size along z in studs")]
+    depth: f64,
+    #[schemars(description = "Height of the walls from the top of the floor (default: 12)")]
+    height: Option<f64>,
+    #[schemars(description = "Thickness of the walls, floor and ceiling in studs (default: 1)")]
+    thickness: Option<f64>,
+    #[schemars(description = "Walls to build: front (-z), back (+z), left (-x) and right (+x) (default: all four)")]
+    walls: Option<Vec<String>>,
+    #[schemars(description = "Build a floor under the walls (default: true)")]
+    floor: Option<bool>,
+    #[schemars(description = "Build a ceiling on top of the walls (default: false)")]
+    ceiling: Option<bool>,
+    #[schemars(description = "Doors and windows to cut out of the walls")]
+    openings: Option<Vec<Opening>>,
+    #[schemars(description = "Material name, e.g. Plastic, Wood, Concrete, Brick or SmoothPlastic")]
+    material: Option<String>,
+    #[schemars(description = "Hex color like '#FF8800' or a BrickColor name like 'Bright red'")]
+    color: Option<String>,
+    #[schemars(description = "Name of the Model holding the parts (default: Structure)")]
+    name: Option<String>,
+    #[schemars(description = "Parent instance path (defaults to workspace)")]
+    parent: Option<String>,
+    #[schemars(description = "Position of the middle of the footprint at ground level (defaults to in front of the camera)")]
+    position: Option<Position>,
+    #[schemars(description = "Rotation in degrees (x, y, z)")]
+    rotation: Option<Rotation>,
+    /// The walls, floor and ceiling as blocks, filled in before the command is queued
+    #[schemars(skip)]
+    pieces: Option<Vec<Piece>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CreateConstraint {
+    #[schemars(description = "weld (WeldConstraint), rigid, hinge, motor (a hinge driven at a speed), servo (a hinge turned to an angle), rope, rod, spring, ball_socket or prismatic")]
+    kind: String,
+    #[schemars(description = "Path to the first part (e.g., 'workspace.Door.Frame')")]
+    part0: String,
+    #[schemars(description = "Path to the second part (e.g., 'workspace.Door.Panel')")]
+    part1: String,
+    #[schemars(description = "Where the constraint holds part0, as an offset from its centre in its own space (default: the centre). Welds don't use attachments")]
+    attachment0: Option<Position>,
+    #[schemars(description = "Where the constraint holds part1, as an offset from its centre in its own space (default: the centre)")]
+    attachment1: Option<Position>,
+    #[schemars(description = "Direction in part0's space that hinges, motors and servos turn about and prismatics slide along (default: x)")]
+    axis: Option<Position>,
+    #[schemars(description = "Constraint properties by name, e.g. {\"AngularVelocity\": 2, \"MotorMaxTorque\": 10000} for a motor, {\"TargetAngle\": 90} for a servo or {\"Stiffness\": 500} for a spring. Ropes, rods and springs start at the distance between the attachments")]
+    properties: Option<JsonObject>,
+    #[schemars(description = "Name of the constraint, defaults to its class name")]
+    name: Option<String>,
+    #[schemars(description = "Path to put the constraint under, defaults to part0")]
+    parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Finish {
+    #[schemars(description = "Material name, e.g. Brick, Concrete, WoodPlanks, Slate or Glass")]
+    material: Option<String>,
+    #[schemars(description = "Hex color like '#FF8800' or a BrickColor name like 'Bright red'")]
+    color: Option<String>,
+    #[schemars(description = "Transparency from 0 (opaque) to 1 (invisible)")]
+    transparency: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Palette {
+    #[schemars(description = "Outside walls (default: Brick)")]
+    walls: Option<Finish>,
+    #[schemars(description = "Floor slabs (default: WoodPlanks)")]
+    floors: Option<Finish>,
+    #[schemars(description = "Roof (default: Slate)")]
+    roof: Option<Finish>,
+    #[schemars(description = "Stairs (default: Wood)")]
+    stairs: Option<Finish>,
+    #[schemars(description = "Window panes (default: Glass, half transparent)")]
+    windows: Option<Finish>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GenerateBuilding {
+    #[schemars(description = "Outer size along x in studs")]
+    width: f64,
+    #[schemars(description = "Outer size along z in studs")]
+    depth: f64,
+    #[schemars(description = "Number of storeys")]
+    floors: u32,
+    #[schemars(description = "Height of each storey from its floor to the slab above, in studs (default: 12)")]
+    floor_height: Option<f64>,
+    #[schemars(description = "Thickness of the walls, floors and roof in studs (default: 1)")]
+    wall_thickness: Option<f64>,
+    #[schemars(description = "Windows repeated along every wall of every storey")]
+    windows: Option<WindowSpec>,
+    #[schemars(description = "The door on the ground floor")]
+    door: Option<DoorSpec>,
+    #[schemars(description = "Build stairs between the storeys along the right (+x) wall (default: true)")]
+    stairs: Option<bool>,
+    #[schemars(description = "Width of each flight of stairs in studs, the stairwell takes twice this (default: 4)")]
+    stair_width: Option<f64>,
+    #[schemars(description = "Material, color and transparency of each part of the building")]
+    palette: Option<Palette>,
+    #[schemars(description = "Name of the Model holding the building (default: Building)")]
+    name: Option<String>,
+    #[schemars(description = "Parent instance path (defaults to workspace)")]
+    parent: Option<String>,
+    #[schemars(description = "Position of the middle of the footprint at ground level (defaults to in front of the camera)")]
+    position: Option<Position>,
+    #[schemars(description = "Rotation in degrees (x, y, z)")]
+    rotation: Option<Rotation>,
+    /// The storeys, stairs and roof as blocks, filled in before the command is queued
+    #[schemars(skip)]
+    pieces: Option<Vec<Piece>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SkyboxFaces {
+    #[schemars(description = "Asset ID or content URL of the back face (SkyboxBk)")]
+    back: String,
+    #[schemars(description = "Asset ID or content URL of the down face (SkyboxDn)")]
+    down: String,
+    #[schemars(description = "Asset ID or content URL of the front face (SkyboxFt)")]
+    front: String,
+    #[schemars(description = "Asset ID or content URL of the left face (SkyboxLf)")]
+    left: String,
+    #[schemars(description = "Asset ID or content URL of the right face (SkyboxRt)")]
+    right: String,
+    #[schemars(description = "Asset ID or content URL of the up face (SkyboxUp)")]
+    up: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetSkybox {
+    #[schemars(description = "Sky bundled with the server: 'default' (the sky new places start with) or 'classic' (the older sky that ships with Studio)")]
+    preset: Option<String>,
+    #[schemars(description = "The six faces of the skybox, instead of a preset")]
+    faces: Option<SkyboxFaces>,
+    #[schemars(description = "Asset ID or content URL of the sun texture, overrides the preset's")]
+    sun_texture_id: Option<String>,
+    #[schemars(description = "Asset ID or content URL of the moon texture, overrides the preset's")]
+    moon_texture_id: Option<String>,
+    #[schemars(description = "Apparent size of the sun in degrees, 0 to 60")]
+    sun_angular_size: Option<f64>,
+    #[schemars(description = "Apparent size of the moon in degrees, 0 to 60")]
+    moon_angular_size: Option<f64>,
+    #[schemars(description = "Whether the sun and moon are drawn")]
+    celestial_bodies_shown: Option<bool>,
+    #[schemars(description = "Stars shown at night, 0 to 5000")]
+    star_count: Option<u32>,
+    /// Sky texture properties from the preset, faces and sun and moon textures, filled in
+    /// before the command is queued
+    #[schemars(skip)]
+    textures: Option<BTreeMap<String, String>>,
+}
+
+impl SetSkybox {
+    /// The Sky texture properties the call sets, keyed by property name
+    fn resolve_textures(&self) -> Result<BTreeMap<String, String>, String> {
+        let mut textures = BTreeMap::new();
+        if let Some(preset) = &self.preset {
+            textures.extend(sky_presets::find(preset)?.textures());
+        }
+        if let Some(faces) = &self.faces {
+            textures.extend(sky_presets::faces([
+                &faces.back,
+                &faces.down,
+                &faces.front,
+                &faces.left,
+                &faces.right,
+                &faces.up,
+            ])?);
+        }
+        for (property, texture) in [
+            ("SunTextureId", &self.sun_texture_id),
+            ("MoonTextureId", &self.moon_texture_id),
+        ] {
+            if let Some(texture) = texture {
+                textures.insert(property.to_string(), sky_presets::content_url(texture)?);
+            }
+        }
+        Ok(textures)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetAtmosphere {
+    #[schemars(description = "Look bundled with the server: 'clear_day', 'overcast', 'dense_fog' or 'sunset_haze'. Other values passed override the preset's")]
+    preset: Option<String>,
+    #[schemars(description = "Set to false to remove the Atmosphere so the classic fog shows, which is hidden while there is one (default: true)")]
+    atmosphere: Option<bool>,
+    #[schemars(description = "How thick the air is, from 0 to 1. Higher values hide distant objects")]
+    density: Option<f64>,
+    #[schemars(description = "How much the air blends with the sky at the horizon, from 0 to 1")]
+    offset: Option<f64>,
+    #[schemars(description = "Hex color like '#C7C7C7' or a BrickColor name tinting the air")]
+    color: Option<String>,
+    #[schemars(description = "Hex color or BrickColor name of the air away from the sun")]
+    decay: Option<String>,
+    #[schemars(description = "Glow around the sun, from 0 to 10")]
+    glare: Option<f64>,
+    #[schemars(description = "Haziness at the horizon, from 0 to 10")]
+    haze: Option<f64>,
+    #[schemars(description = "Studs from the camera where the classic fog starts")]
+    fog_start: Option<f64>,
+    #[schemars(description = "Studs from the camera where the classic fog is fully opaque")]
+    fog_end: Option<f64>,
+    #[schemars(description = "Hex color or BrickColor name of the classic fog")]
+    fog_color: Option<String>,
+}
+
+impl SetAtmosphere {
+    fn sets_atmosphere(&self) -> bool {
+        self.density.is_some()
+            || self.offset.is_some()
+            || self.color.is_some()
+            || self.decay.is_some()
+            || self.glare.is_some()
+            || self.haze.is_some()
+    }
+
+    /// Fills the properties that weren't passed from `preset`
+    fn apply_preset(&mut self, preset: &AtmospherePreset) {
+        if self.atmosphere != Some(false) {
+            self.density.get_or_insert(preset.density);
+            self.offset.get_or_insert(preset.offset);
+            self.color.get_or_insert_with(|| preset.color.to_string());
+            self.decay.get_or_insert_with(|| preset.decay.to_string());
+            self.glare.get_or_insert(preset.glare);
+            self.haze.get_or_insert(preset.haze);
+        }
+        self.fog_start.get_or_insert(preset.fog_start);
+        self.fog_end.get_or_insert(preset.fog_end);
+        self.fog_color
+            .get_or_insert_with(|| preset.fog_color.to_string());
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct BloomSettings {
+    #[schemars(description = "Whether the effect is on (default: true)")]
+    enabled: Option<bool>,
+    #[schemars(description = "Strength of the glow, from 0 up")]
+    intensity: Option<f64>,
+    #[schemars(description = "How far the glow spreads, from 0 to 56")]
+    size: Option<f64>,
+    #[schemars(description = "Brightness above which pixels glow, from 0 up")]
+    threshold: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct DepthOfFieldSettings {
+    #[schemars(description = "Whether the effect is on (default: true)")]
+    enabled: Option<bool>,
+    #[schemars(description = "Blur beyond the focus, from 0 to 1")]
+    far_intensity: Option<f64>,
+    #[schemars(description = "Studs from the camera to the middle of the focus, from 0 to 200")]
+    focus_distance: Option<f64>,
+    #[schemars(description = "Studs around the focus distance kept sharp, from 0 to 50")]
+    in_focus_radius: Option<f64>,
+    #[schemars(description = "Blur in front of the focus, from 0 to 1")]
+    near_intensity: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ColorCorrectionSettings {
+    #[schemars(description = "Whether the effect is on (default: true)")]
+    enabled: Option<bool>,
+    #[schemars(description = "Brightness shift, from -1 to 1")]
+    brightness: Option<f64>,
+    #[schemars(description = "Contrast shift, from -1 to 1")]
+    contrast: Option<f64>,
+    #[schemars(description = "Saturation shift, from -1 (grey) to 1")]
+    saturation: Option<f64>,
+    #[schemars(description = "Hex color or BrickColor name the picture is multiplied by, white leaves it as it is")]
+    tint_color: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SunRaysSettings {
+    #[schemars(description = "Whether the effect is on (default: true)")]
+    enabled: Option<bool>,
+    #[schemars(description = "Strength of the rays, from 0 to 1")]
+    intensity: Option<f64>,
+    #[schemars(description = "How far the rays reach across the sky, from 0 to 1")]
+    spread: Option<f64>,
+}
+
+/// A message naming the first of `values` outside its range, given as the setting's name,
+/// value, minimum and maximum
+fn out_of_range(effect: &str, values: &[(&str, Option<f64>, f64, f64)]) -> Option<String> {
+    values.iter().find_map(|&(name, value, min, max)| {
+        let value = value.filter(|value| !(min..=max).contains(value))?;
+        Some(if max.is_infinite() {
+            format!("{effect}.{name} can't be negative, got {value}")
+        } else {
+            format!("{effect}.{name} must be from {min} to {max}, got {value}")
+        })
+    })
+}
+
+impl BloomSettings {
+    fn problem(&self) -> Option<String> {
+        out_of_range(
+            "bloom",
+            &[
+                ("intensity", self.intensity, 0.0, f64::INFINITY),
+                ("size", self.size, 0.0, 56.0),
+                ("threshold", self.threshold, 0.0, f64::INFINITY),
+            ],
+        )
+    }
+}
+
+impl DepthOfFieldSettings {
+    fn problem(&self) -> Option<String> {
+        out_of_range(
+            "depth_of_field",
+            &[
+                ("far_intensity", self.far_intensity, 0.0, 1.0),
+                ("focus_distance", self.focus_distance, 0.0, 200.0),
+                ("in_focus_radius", self.in_focus_radius, 0.0, 50.0),
+                ("near_intensity", self.near_intensity, 0.0, 1.0),
+            ],
+        )
+    }
+}
+
+impl ColorCorrectionSettings {
+    fn problem(&self) -> Option<String> {
+        out_of_range(
+            "color_correction",
+            &[
+                ("brightness", self.brightness, -1.0, 1.0),
+                ("contrast", self.contrast, -1.0, 1.0),
+                ("saturation", self.saturation, -1.0, 1.0),
+            ],
+        )
+    }
+}
+
+impl SunRaysSettings {
+    fn problem(&self) -> Option<String> {
+        out_of_range(
+            "sun_rays",
+            &[
+                ("intensity", self.intensity, 0.0, 1.0),
+                ("spread", self.spread, 0.0, 1.0),
+            ],
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetPostEffects {
+    #[schemars(description = "Look bundled with the server: 'cinematic', 'horror' or 'vibrant'. Effects the preset doesn't use are turned off, and other values passed override the preset's")]
+    preset: Option<String>,
+    #[schemars(description = "BloomEffect settings, makes bright areas glow")]
+    bloom: Option<BloomSettings>,
+    #[schemars(description = "DepthOfFieldEffect settings, blurs what's out of focus")]
+    depth_of_field: Option<DepthOfFieldSettings>,
+    #[schemars(description = "ColorCorrectionEffect settings, shifts brightness, contrast, saturation and tint")]
+    color_correction: Option<ColorCorrectionSettings>,
+    #[schemars(description = "SunRaysEffect settings, rays around the sun")]
+    sun_rays: Option<SunRaysSettings>,
+}
+
+impl SetPostEffects {
+    /// Fills the settings that weren't passed from `preset`
+    fn apply_preset(&mut self, preset: &PostEffectsPreset) {
+        let bloom = self.bloom.get_or_insert_with(Default::default);
+        bloom.enabled.get_or_insert(preset.bloom.is_some());
+        if let Some(values) = &preset.bloom {
+            bloom.intensity.get_or_insert(values.intensity);
+            bloom.size.get_or_insert(values.size);
+            bloom.threshold.get_or_insert(values.threshold);
+        }
+        let depth_of_field = self.depth_of_field.get_or_insert_with(Default::default);
+        depth_of_field
+            .enabled
+            .get_or_insert(preset.depth_of_field.is_some());
+        if let Some(values) = &preset.depth_of_field {
+            depth_of_field
+                .far_intensity
+                .get_or_insert(values.far_intensity);
+            depth_of_field
+                .focus_distance
+                .get_or_insert(values.focus_distance);
+            depth_of_field
+                .in_focus_radius
+                .get_or_insert(values.in_focus_radius);
+            depth_of_field
+                .near_intensity
+                .get_or_insert(values.near_intensity);
+        }
+        let color_correction = self.color_correction.get_or_insert_with(Default::default);
+        color_correction
+            .enabled
+            .get_or_insert(preset.color_correction.is_some());
+        if let Some(values) = &preset.color_correction {
+            color_correction.brightness.get_or_insert(values.brightness);
+            color_correction.contrast.get_or_insert(values.contrast);
+            color_correction.saturation.get_or_insert(values.saturation);
+            color_correction
+                .tint_color
+                .get_or_insert_with(|| values.tint_color.to_string());
+        }
+        let sun_rays = self.sun_rays.get_or_insert_with(Default::default);
+        sun_rays.enabled.get_or_insert(preset.sun_rays.is_some());
+        if let Some(values) = &preset.sun_rays {
+            sun_rays.intensity.get_or_insert(values.intensity);
+            sun_rays.spread.get_or_insert(values.spread);
+        }
+    }
+
+    /// Why the plugin can't apply these settings, if anything
+    fn problem(&self) -> Option<String> {
+        self.bloom
+            .as_ref()
+            .and_then(BloomSettings::problem)
+            .or_else(|| {
+                self.depth_of_field
+                    .as_ref()
+                    .and_then(DepthOfFieldSettings::problem)
+            })
+            .or_else(|| {
+                self.color_correction
+                    .as_ref()
+                    .and_then(ColorCorrectionSettings::problem)
+            })
+            .or_else(|| self.sun_rays.as_ref().and_then(SunRaysSettings::problem))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct InsertDecalOrTexture {
+    #[schemars(description = "Path to the part to apply it to (e.g., 'workspace.Wall'), a MeshPart for surface_appearance")]
+    path: String,
+    #[schemars(description = "What to apply: decal (stretched over the face, default), texture (tiled across the face) or surface_appearance (PBR materials for a MeshPart)")]
+    kind: Option<String>,
+    #[schemars(description = "Asset ID of the decal, image or SurfaceAppearance to apply")]
+    asset_id: Option<u64>,
+    #[schemars(description = "Search the marketplace for this instead and apply the first result")]
+    query: Option<String>,
+    #[schemars(description = "Face to apply a decal or texture to: Front, Back, Top, Bottom, Left, Right, or All for one on each (default: Front)")]
+    face: Option<String>,
+    #[schemars(description = "Width of one tile of a texture in studs (default: 2)")]
+    studs_per_tile_u: Option<f64>,
+    #[schemars(description = "Height of one tile of a texture in studs (default: 2)")]
+    studs_per_tile_v: Option<f64>,
+    #[schemars(description = "Horizontal offset of a texture's tiles in studs")]
+    offset_studs_u: Option<f64>,
+    #[schemars(description = "Vertical offset of a texture's tiles in studs")]
+    offset_studs_v: Option<f64>,
+    #[schemars(description = "Transparency of a decal or texture, from 0 to 1")]
+    transparency: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct InsertAudio {
+    #[schemars(description = "Path to the instance to put the Sound under (default: workspace). Sounds in a part are heard from the part, elsewhere they're heard everywhere")]
+    parent: Option<String>,
+    #[schemars(description = "Audio asset ID to insert")]
+    asset_id: Option<u64>,
+    #[schemars(description = "Search the Creator Store's audio for this instead and insert the first result")]
+    query: Option<String>,
+    #[schemars(description = "Name of the Sound (defaults to the audio's name for a search, else Sound)")]
+    name: Option<String>,
+    #[schemars(description = "Volume from 0 to 10 (default: 0.5)")]
+    volume: Option<f64>,
+    #[schemars(description = "Loop the sound")]
+    looped: Option<bool>,
+    #[schemars(description = "Play the sound when the game starts")]
+    playing: Option<bool>,
+    #[schemars(description = "Speed and pitch multiplier (default: 1)")]
+    playback_speed: Option<f64>,
+    #[schemars(description = "How the volume falls off with distance: Inverse, Linear, LinearSquare or InverseTapered")]
+    roll_off_mode: Option<String>,
+    #[schemars(description = "Distance in studs the volume starts falling off at")]
+    roll_off_min_distance: Option<f64>,
+    #[schemars(description = "Distance in studs the sound can no longer be heard at")]
+    roll_off_max_distance: Option<f64>,
+    #[schemars(description = "Play this many seconds of the sound in Studio so the user can hear it, at most 10")]
+    preview_seconds: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct InsertMesh {
+    #[schemars(description = "Asset ID of the mesh")]
+    mesh_id: u64,
+    #[schemars(description = "Asset ID of the image to texture the mesh with")]
+    texture_id: Option<u64>,
+    #[schemars(description = "Parent instance path (defaults to workspace)")]
+    parent: Option<String>,
+    #[schemars(description = "Name of the MeshPart (default: Mesh)")]
+    name: Option<String>,
+    #[schemars(description = "Position of the MeshPart's centre (defaults to in front of the camera)")]
+    position: Option<Position>,
+    #[schemars(description = "Rotation in degrees (x, y, z)")]
+    rotation: Option<Rotation>,
+    #[schemars(description = "Size in studs (defaults to the size the mesh was uploaded at)")]
+    size: Option<Size>,
+    #[schemars(description = "Collision shape: Default, Hull, Box or PreciseConvexDecomposition (default: Default)")]
+    collision_fidelity: Option<String>,
+    #[schemars(description = "Level of detail: Automatic, Performance or Precise (default: Automatic)")]
+    render_fidelity: Option<String>,
+    #[schemars(description = "Anchor the MeshPart (default: true)")]
+    anchored: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct DeleteInstances {
+    #[schemars(description = "Paths of instances to delete (e.g., 'workspace.OldMap', 'game.ServerStorage.Unused')")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Wildcard patterns with '/' between names, e.g. 'Workspace/Props/*Tree*'. '*' matches any characters and '?' one character within a name, '**' matches any number of levels")]
+    patterns: Option<Vec<String>>,
+    #[schemars(description = "Only report what would be deleted without deleting anything")]
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CloneInstance {
+    #[schemars(description = "Path to the instance to copy (e.g., 'workspace.Pillar')")]
+    path: String,
+    #[schemars(description = "Number of copies to make")]
+    count: u32,
+    #[schemars(description = "Position offset in studs between one copy and the next, the first copy is offset once from the original")]
+    offset: Option<Position>,
+    #[schemars(description = "Rotation in degrees added per copy, each copy turns around its own pivot")]
+    rotation: Option<Rotation>,
+    #[schemars(description = "Name of the copies, with {n} replaced by the copy number from 1 (e.g., 'Pillar{n}'). Defaults to the original name")]
+    name_pattern: Option<String>,
+    #[schemars(description = "Path to put the copies under, defaults to the parent of the original")]
+    parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ArrayDuplicate {
+    #[schemars(description = "Path to the part or model to copy (e.g., 'workspace.Pillar'). It fills the first cell of the grid")]
+    path: String,
+    #[schemars(description = "Number of cells along x, the original included (default: 1)")]
+    count_x: Option<u32>,
+    #[schemars(description = "Number of cells along y, the original included (default: 1)")]
+    count_y: Option<u32>,
+    #[schemars(description = "Number of cells along z, the original included (default: 1)")]
+    count_z: Option<u32>,
+    #[schemars(description = "Distance in studs between neighbouring cells on each axis, negative to grow the other way")]
+    spacing: Position,
+    #[schemars(description = "Lay the grid out along the original's own axes rather than the world's (default: false)")]
+    local_axes: Option<bool>,
+    #[schemars(description = "Rotation in degrees added per copy in grid order, each copy turns around its own pivot")]
+    rotation: Option<Rotation>,
+    #[schemars(description = "Name of the copies, with {n} replaced by the copy number from 1 and {x}, {y} and {z} by its cell from 1 (e.g., 'Window_{x}_{y}'). Defaults to the original name")]
+    name_pattern: Option<String>,
+    #[schemars(description = "Path to put the copies under, defaults to the parent of the original")]
+    parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct PlaceAlongPath {
+    #[schemars(description = "Path to the part or model to copy (e.g., 'workspace.Fence')")]
+    path: String,
+    #[schemars(description = "Points the path runs through in order, at least two. Each copy's pivot is placed on the path")]
+    waypoints: Vec<Position>,
+    #[schemars(description = "Distance in studs along the path between one copy and the next, the first copy goes on the first waypoint")]
+    spacing: f64,
+    #[schemars(description = "Follow a smooth curve through the waypoints instead of straight lines between them (default: false)")]
+    smooth: Option<bool>,
+    #[schemars(description = "Turn each copy to face along the path, staying upright. Otherwise copies keep the original's rotation (default: true)")]
+    align_to_tangent: Option<bool>,
+    #[schemars(description = "Move each copy up to this many studs to either side of the path at random (default: 0)")]
+    jitter: Option<f64>,
+    #[schemars(description = "Turn each copy up to this many degrees either way around the vertical axis at random (default: 0)")]
+    rotation_jitter: Option<f64>,
+    #[schemars(description = "Rotation in degrees added to every copy, e.g. to turn models whose front isn't -z")]
+    rotation: Option<Rotation>,
+    #[schemars(description = "Random seed for the jitter")]
+    seed: Option<i32>,
+    #[schemars(description = "Name of the copies, with {n} replaced by the copy number from 1 (e.g., 'Lamp{n}'). Defaults to the original name")]
+    name_pattern: Option<String>,
+    #[schemars(description = "Path to put the copies under, defaults to the parent of the original")]
+    parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct MoveInstances {
+    #[schemars(description = "Paths of the instances to move (e.g., ['workspace.Model', 'workspace.Model1'])")]
+    paths: Vec<String>,
+    #[schemars(description = "Path to the new parent (e.g., 'workspace.Props')")]
+    new_parent: String,
+    #[schemars(description = "Keep parts and models where they are in the world, defaults to true. When false they keep their offset from the parent's pivot, so they move along with the change of parent")]
+    keep_world_position: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct AlignInstances {
+    #[schemars(description = "Paths of the parts and models to line up (e.g., ['workspace.Crate1', 'workspace.Crate2'])")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Wildcard pattern with '/' between names, e.g. 'Workspace/Shelves/*'. '*' matches any characters and '?' one character within a name, '**' matches any number of levels")]
+    pattern: Option<String>,
+    #[schemars(description = "Only work on instances of this class or its subclasses (e.g., 'BasePart', 'Model')")]
+    class_name: Option<String>,
+    #[schemars(description = "min, center or max lines up those sides of the bounding boxes, distribute spaces the instances evenly. Without paths or a pattern the selection in Studio is used")]
+    mode: String,
+    #[schemars(description = "World axes to work along: x, y and z, e.g. ['y'] to put everything at one height")]
+    axes: Vec<String>,
+    #[schemars(description = "Coordinates to line up to on the chosen axes, defaults to the bounds of all the instances together")]
+    target: Option<Position>,
+    #[schemars(description = "Gap in studs between neighbouring bounding boxes when distributing, starting from the first instance. By default the first and last stay put and the gaps are equal")]
+    spacing: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct Raycast {
+    #[schemars(description = "Where the ray starts")]
+    origin: Position,
+    #[schemars(description = "Which way the ray goes, e.g. {x: 0, y: -1, z: 0} for straight down. Its length is how far it reaches unless distance is given")]
+    direction: Position,
+    #[schemars(description = "Studs the ray reaches along direction, at most 15000")]
+    distance: Option<f64>,
+    #[schemars(description = "Paths of instances whose parts the ray ignores, or the only ones it can hit with filter_type 'include'")]
+    filter: Option<Vec<String>>,
+    #[schemars(description = "'exclude' to ignore the filter instances or 'include' to only hit them (default: exclude)")]
+    filter_type: Option<String>,
+    #[schemars(description = "Collision group the ray belongs to, so it passes through parts that group doesn't collide with (default: Default)")]
+    collision_group: Option<String>,
+    #[schemars(description = "Pass through terrain water instead of hitting it (default: false)")]
+    ignore_water: Option<bool>,
+    #[schemars(description = "Pass through parts with CanCollide off (default: false)")]
+    respect_can_collide: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct QueryRegion {
+    #[schemars(description = "Box to look in, as its minimum and maximum corners. Pass either region or center and radius")]
+    region: Option<Region>,
+    #[schemars(description = "Center of a sphere to look in")]
+    center: Option<Position>,
+    #[schemars(description = "Radius of the sphere in studs")]
+    radius: Option<f64>,
+    #[schemars(description = "Only return parts of this class or its subclasses (e.g., 'MeshPart', 'Seat')")]
+    class_name: Option<String>,
+    #[schemars(description = "Only return parts whose name contains this text, ignoring case")]
+    name_contains: Option<String>,
+    #[schemars(description = "Only return parts with this CollectionService tag, or inside a model or folder with it")]
+    tag: Option<String>,
+    #[schemars(description = "Most parts to return, the closest to the center first (default: 100, max: 1000)")]
+    max_results: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SnapToGround {
+    #[schemars(description = "Paths of the parts and models to snap (e.g., ['workspace.Tree1', 'workspace.Rock'])")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Wildcard pattern with '/' between names, e.g. 'Workspace/Props/*'. '*' matches any characters and '?' one character within a name, '**' matches any number of levels. Without paths or a pattern the selection in Studio is used")]
+    pattern: Option<String>,
+    #[schemars(description = "Only work on instances of this class or its subclasses (e.g., 'BasePart', 'Model')")]
+    class_name: Option<String>,
+    #[schemars(description = "Tilt each instance so its up follows the slope of the ground under it (default: false)")]
+    align_to_normal: Option<bool>,
+    #[schemars(description = "Studs to leave between the bottom of each bounding box and the ground, negative to sink it in (default: 0)")]
+    offset: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct PaintInstances {
+    #[schemars(description = "Paths of the parts, models or folders to paint (e.g., ['workspace.House'])")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Wildcard pattern with '/' between names, e.g. 'Workspace/Houses/*'. '*' matches any characters and '?' one character within a name, '**' matches any number of levels")]
+    pattern: Option<String>,
+    #[schemars(description = "Paint the instances with this CollectionService tag. Without paths, a pattern or a tag the selection in Studio is used")]
+    tag: Option<String>,
+    #[schemars(description = "Only paint parts of this class (e.g., 'MeshPart', 'WedgePart'). Defaults to every BasePart")]
+    class_name: Option<String>,
+    #[schemars(description = "Also paint the parts inside the matched models and folders (default: true)")]
+    recursive: Option<bool>,
+    #[schemars(description = "Material name, e.g. Plastic, Wood, Concrete, Metal, Glass or Neon")]
+    material: Option<String>,
+    #[schemars(description = "Hex color like '#FF8800' or a BrickColor name like 'Bright red'")]
+    color: Option<String>,
+    #[schemars(description = "Transparency from 0 (opaque) to 1 (invisible)")]
+    transparency: Option<f64>,
+    #[schemars(description = "Reflectance from 0 to 1")]
+    reflectance: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct PlaceLights {
+    #[schemars(description = "Kind of light: point, spot or surface (default: point)")]
+    kind: Option<String>,
+    #[schemars(description = "Paths of the parts or models to light (e.g., ['workspace.LampPost1'])")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Wildcard pattern with '/' between names, e.g. 'Workspace/Street/LampPost*'. '*' matches any characters and '?' one character within a name, '**' matches any number of levels")]
+    pattern: Option<String>,
+    #[schemars(description = "Light the instances with this CollectionService tag. Without paths, a pattern, a tag or positions the selection in Studio is used")]
+    tag: Option<String>,
+    #[schemars(description = "Inside matched models, put the light in the part with this name (e.g. 'Bulb'). Defaults to the primary part, or the first part")]
+    part_name: Option<String>,
+    #[schemars(description = "World positions to put point and spot lights at, each on an Attachment in Terrain")]
+    positions: Option<Vec<Position>>,
+    #[schemars(description = "Offset from the middle of each part in its own axes, the light goes on an Attachment there")]
+    offset: Option<Position>,
+    #[schemars(description = "Brightness, from 0 up (default: 1)")]
+    brightness: Option<f64>,
+    #[schemars(description = "Studs the light reaches, from 0 to 60 (default: 8 for point lights, 16 for spot and surface lights)")]
+    range: Option<f64>,
+    #[schemars(description = "Hex color like '#FFD9A0' or a BrickColor name")]
+    color: Option<String>,
+    #[schemars(description = "Degrees the spot or surface light spreads over, from 0 to 180 (default: 90)")]
+    angle: Option<f64>,
+    #[schemars(description = "Face the spot or surface light shines from: Front, Back, Top, Bottom, Left or Right (default: Front)")]
+    face: Option<String>,
+    #[schemars(description = "Whether the light casts shadows (default: false)")]
+    shadows: Option<bool>,
+    #[schemars(description = "Name of each light, defaults to its class")]
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ValueRange {
+    min: f64,
+    max: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct NumberKeypoint {
+    #[schemars(description = "Point in the particle's life, from 0 (born) to 1 (gone)")]
+    time: f64,
+    value: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ColorKeypoint {
+    #[schemars(description = "Point in the particle's life, from 0 (born) to 1 (gone)")]
+    time: f64,
+    #[schemars(description = "Hex color like '#FF8800' or a BrickColor name")]
+    color: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CreateParticleEmitter {
+    #[schemars(description = "Path of the part or attachment the emitter goes in (e.g., 'workspace.Campfire.Logs')")]
+    parent: String,
+    #[schemars(description = "Emitter to start from: fire, smoke, sparkles or rain. Other values passed override the preset's")]
+    preset: Option<String>,
+    #[schemars(description = "Name of the emitter (default: ParticleEmitter)")]
+    name: Option<String>,
+    #[schemars(description = "Image asset ID of each particle")]
+    texture_id: Option<u64>,
+    #[schemars(description = "Particles emitted per second")]
+    rate: Option<f64>,
+    #[schemars(description = "Seconds each particle lives, picked between min and max")]
+    lifetime: Option<ValueRange>,
+    #[schemars(description = "Studs per second particles leave at, picked between min and max")]
+    speed: Option<ValueRange>,
+    #[schemars(description = "Degrees particles spread from the emission direction, from 0 to 180")]
+    spread_angle: Option<f64>,
+    #[schemars(description = "Acceleration of every particle in studs per second squared, e.g. {x: 0, y: -10, z: 0} to fall")]
+    acceleration: Option<Position>,
+    #[schemars(description = "Size in studs over each particle's life. One keypoint keeps it constant, more must start at time 0 and end at time 1")]
+    size: Option<Vec<NumberKeypoint>>,
+    #[schemars(description = "Transparency from 0 to 1 over each particle's life, keypoints like size")]
+    transparency: Option<Vec<NumberKeypoint>>,
+    #[schemars(description = "Color over each particle's life, keypoints like size")]
+    color: Option<Vec<ColorKeypoint>>,
+    #[schemars(description = "How much particles add light to what's behind them, from 0 to 1")]
+    light_emission: Option<f64>,
+    #[schemars(description = "Face of the parent part particles leave from: Top, Bottom, Front, Back, Left or Right")]
+    emission_direction: Option<String>,
+    #[schemars(description = "How particles face: FacingCamera, FacingCameraWorldUp, VelocityParallel or VelocityPerpendicular")]
+    orientation: Option<String>,
+    /// Content URL of the texture, from `texture_id` or the preset, filled in before the
+    /// command is queued
+    #[schemars(skip)]
+    texture: Option<String>,
+}
+
+/// Why `keypoints` at these times don't make a sequence, if anything
+fn sequence_problem(name: &str, times: &[f64]) -> Option<String> {
+    if times.is_empty() {
+        Some(format!("{name} needs at least one keypoint"))
+    } else if times.len() > MAX_SEQUENCE_KEYPOINTS {
+        Some(format!(
+            "{name} can have at most {MAX_SEQUENCE_KEYPOINTS} keypoints"
+        ))
+    } else if times.len() > 1
+        && (times[0] != 0.0
+            || times[times.len() - 1] != 1.0
+            || times.windows(2).any(|pair| pair[1] < pair[0]))
+    {
+        Some(format!(
+            "{name} keypoints must go in order of time from 0 to 1"
+        ))
+    } else {
+        None
+    }
+}
+
+impl CreateParticleEmitter {
+    /// Fills the properties that weren't passed from `preset`
+    fn apply_preset(&mut self, preset: &ParticlePreset) {
+        let [min, max] = preset.lifetime;
+        self.lifetime.get_or_insert(ValueRange { min, max });
+        let [min, max] = preset.speed;
+        self.speed.get_or_insert(ValueRange { min, max });
+        let [x, y, z] = preset.acceleration;
+        self.acceleration.get_or_insert(Position { x, y, z });
+        self.rate.get_or_insert(preset.rate);
+        self.spread_angle.get_or_insert(preset.spread_angle);
+        self.light_emission.get_or_insert(preset.light_emission);
+        self.size
+            .get_or_insert_with(|| number_keypoints(preset.size));
+        self.transparency
+            .get_or_insert_with(|| number_keypoints(preset.transparency));
+        self.color.get_or_insert_with(|| {
+            preset
+                .color
+                .iter()
+                .map(|&(time, color)| ColorKeypoint {
+                    time,
+                    color: color.to_string(),
+                })
+                .collect()
+        });
+        self.emission_direction
+            .get_or_insert_with(|| preset.emission_direction.to_string());
+        self.orientation
+            .get_or_insert_with(|| preset.orientation.to_string());
+    }
+
+    /// Why the plugin can't make this emitter, if anything
+    fn problem(&self) -> Option<String> {
+        let times = |keypoints: &Option<Vec<NumberKeypoint>>| {
+            keypoints
+                .as_ref()
+                .map(|keypoints| keypoints.iter().map(|keypoint| keypoint.time).collect())
+        };
+        let ranges = [("lifetime", &self.lifetime), ("speed", &self.speed)];
+        if let Some((name, _)) = ranges
+            .iter()
+            .find(|(_, range)| range.as_ref().is_some_and(|range| range.min > range.max))
+        {
+            return Some(format!("{name}.min can't be more than {name}.max"));
+        }
+        let sequences: [(&str, Option<Vec<f64>>); 3] = [
+            ("size", times(&self.size)),
+            ("transparency", times(&self.transparency)),
+            (
+                "color",
+                self.color
+                    .as_ref()
+                    .map(|keypoints| keypoints.iter().map(|keypoint| keypoint.time).collect()),
+            ),
+        ];
+        if let Some(message) = sequences.iter().find_map(|(name, times)| {
+            times
+                .as_deref()
+                .and_then(|times| sequence_problem(name, times))
+        }) {
+            Some(message)
+        } else if self.rate.is_some_and(|rate| rate < 0.0) {
+            Some("rate can't be negative".to_string())
+        } else if self
+            .spread_angle
+            .is_some_and(|angle| !(0.0..=180.0).contains(&angle))
+        {
+            Some("spread_angle must be between 0 and 180".to_string())
+        } else if self
+            .light_emission
+            .is_some_and(|emission| !(0.0..=1.0).contains(&emission))
+        {
+            Some("light_emission must be between 0 and 1".to_string())
+        } else if self
+            .emission_direction
+            .as_deref()
+            .is_some_and(|direction| !PARTICLE_DIRECTIONS.contains(&direction))
+        {
+            Some(format!(
+                "emission_direction must be one of {}",
+                PARTICLE_DIRECTIONS.join(", ")
+            ))
+        } else if self
+            .orientation
+            .as_deref()
+            .is_some_and(|orientation| !PARTICLE_ORIENTATIONS.contains(&orientation))
+        {
+            Some(format!(
+                "orientation must be one of {}",
+                PARTICLE_ORIENTATIONS.join(", ")
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+fn number_keypoints(keypoints: &[(f64, f64)]) -> Vec<NumberKeypoint> {
+    keypoints
+        .iter()
+        .map(|&(time, value)| NumberKeypoint { time, value })
+        .collect()
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetupWeather {
+    #[schemars(description = "Weather to start with: clear, rain, snow or storm (default: rain). The driver script switches weather when the Model's Weather attribute changes")]
+    weather: Option<String>,
+    #[schemars(description = "How heavy the rain, snow and fog are, from 0 to 1 (default: 0.5)")]
+    intensity: Option<f64>,
+    #[schemars(description = "Middle of the area the weather covers, at ground level (default: the origin)")]
+    position: Option<Position>,
+    #[schemars(description = "Width in studs of the square area rain and snow fall over, from 16 to 2048 (default: 512)")]
+    area: Option<f64>,
+    #[schemars(description = "Studs above the ground the rain and snow start falling from (default: 150)")]
+    height: Option<f64>,
+    #[schemars(description = "Whether the fog and Atmosphere thicken with the weather (default: true)")]
+    fog: Option<bool>,
+    #[schemars(description = "Seconds the fog takes to change between weathers (default: 5)")]
+    transition_seconds: Option<f64>,
+    #[schemars(description = "Whether storms flash and thunder (default: true)")]
+    thunder: Option<bool>,
+    #[schemars(description = "Audio asset ID of the thunder. Defaults to the first Creator Store result for 'thunder'")]
+    thunder_sound_id: Option<u64>,
+    #[schemars(description = "Seconds between thunder strikes in a storm, picked between min and max (default: 8 to 20)")]
+    thunder_interval: Option<ValueRange>,
+    #[schemars(description = "Minutes between random weather changes, 0 keeps the weather until the Weather attribute is set (default: 0)")]
+    cycle_minutes: Option<f64>,
+    #[schemars(description = "Weathers the cycle picks from (default: ['clear', 'rain'])")]
+    cycle_weathers: Option<Vec<String>>,
+    #[schemars(description = "Name of the Model (default: Weather)")]
+    name: Option<String>,
+    #[schemars(description = "Parent instance path (defaults to workspace)")]
+    parent: Option<String>,
+    /// Source of the driver script, filled in before the command is queued
+    #[schemars(skip)]
+    driver_source: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetupDayNightCycle {
+    #[schemars(description = "Real minutes one whole day takes (default: 12)")]
+    day_minutes: Option<f64>,
+    #[schemars(description = "Hour the game starts at, from 0 to 24 (default: 8)")]
+    start_time: Option<f64>,
+    #[schemars(description = "Hex color or BrickColor name of the outdoor light at dawn (default: '#E69B6E')")]
+    dawn_color: Option<String>,
+    #[schemars(description = "Outdoor light during the day (default: '#808080')")]
+    day_color: Option<String>,
+    #[schemars(description = "Outdoor light at dusk (default: '#D2785A')")]
+    dusk_color: Option<String>,
+    #[schemars(description = "Outdoor light at night (default: '#282D46')")]
+    night_color: Option<String>,
+    #[schemars(description = "CollectionService tag of the streetlights, the lights in tagged instances turn on at night (default: 'Streetlight')")]
+    light_tag: Option<String>,
+    #[schemars(description = "Hour the tagged lights turn on, from 0 to 24 (default: 18)")]
+    lights_on_at: Option<f64>,
+    #[schemars(description = "Hour the tagged lights turn off, from 0 to 24 (default: 6)")]
+    lights_off_at: Option<f64>,
+    #[schemars(description = "Name of the script (default: DayNightCycle)")]
+    name: Option<String>,
+    #[schemars(description = "Parent instance path (default: ServerScriptService)")]
+    parent: Option<String>,
+    /// Source of the script, filled in before the command is queued
+    #[schemars(skip)]
+    source: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct AmbientZone {
+    #[schemars(description = "Name of the zone's part, e.g. 'Forest' or 'Cave'")]
+    name: String,
+    #[schemars(description = "Box the sound plays in while the player is inside")]
+    region: Region,
+    #[schemars(description = "Audio asset ID of the ambience")]
+    asset_id: Option<u64>,
+    #[schemars(description = "Search the Creator Store's audio for this instead and use the first result")]
+    query: Option<String>,
+    #[schemars(description = "Volume inside the zone, from 0 to 10 (default: 0.5)")]
+    volume: Option<f64>,
+    #[schemars(description = "How the volume falls off with distance from the middle of the zone: Inverse, Linear, LinearSquare or InverseTapered")]
+    roll_off_mode: Option<String>,
+    #[schemars(description = "Distance in studs the volume starts falling off at (default: half the zone's largest side)")]
+    roll_off_min_distance: Option<f64>,
+    #[schemars(description = "Distance in studs the sound can no longer be heard at")]
+    roll_off_max_distance: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetupAmbientSound {
+    #[schemars(description = "Reverb of the whole place, e.g. NoReverb, Forest, City, Cave, Hallway or UnderWater")]
+    ambient_reverb: Option<String>,
+    #[schemars(description = "Studs per meter for 3D sound, more than 0 (Roblox default: 3.33)")]
+    distance_factor: Option<f64>,
+    #[schemars(description = "How much moving sounds change pitch, 0 turns it off")]
+    doppler_scale: Option<f64>,
+    #[schemars(description = "How quickly sounds fade with distance, from 0 up")]
+    rolloff_scale: Option<f64>,
+    #[schemars(description = "Zones with their own ambient sound, played while the player is inside by a LocalScript added to StarterPlayerScripts")]
+    zones: Option<Vec<AmbientZone>>,
+    #[schemars(description = "Seconds a zone's sound takes to fade in and out (default: 1.5)")]
+    fade_seconds: Option<f64>,
+    #[schemars(description = "Name of the folder holding the zone parts (default: AmbientZones)")]
+    folder: Option<String>,
+    #[schemars(description = "Parent of the zone folder (defaults to workspace)")]
+    parent: Option<String>,
+    /// Source of the zone trigger script, filled in before the command is queued
+    #[schemars(skip)]
+    trigger_source: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetWorkspacePhysics {
+    #[schemars(description = "Downward acceleration in studs/s², from 0 up (Roblox default: 196.2)")]
+    gravity: Option<f64>,
+    #[schemars(description = "Density of the air for aerodynamic forces, from 0 up")]
+    air_density: Option<f64>,
+    #[schemars(description = "Wind direction and speed in studs/s, moving particles, grass and aerodynamic parts")]
+    global_wind: Option<Position>,
+    #[schemars(description = "Height parts are destroyed below, at least -50000")]
+    fallen_parts_destroy_height: Option<f64>,
+    #[schemars(description = "How the physics solver steps: Default, Fixed or Adaptive")]
+    physics_stepping_method: Option<String>,
+    #[schemars(description = "Whether Touched events respect collision groups")]
+    touches_use_collision_groups: Option<bool>,
+    #[schemars(description = "Animation retargeting across rigs: Default, Disabled or Enabled")]
+    retargeting: Option<String>,
+    #[schemars(description = "Whether IKControls respect constraints: Default, Disabled or Enabled")]
+    ik_control_constraint_support: Option<String>,
+}
+
+impl AmbientZone {
+    /// Why the plugin can't set up this zone, if anything
+    fn problem(&self) -> Option<String> {
+        let Region { min, max } = &self.region;
+        let problem = if self.asset_id.is_some() == self.query.is_some() {
+            Some("pass either asset_id or query".to_string())
+        } else if min.x >= max.x || min.y >= max.y || min.z >= max.z {
+            Some("region.min must be below region.max on every axis".to_string())
+        } else if self
+            .volume
+            .is_some_and(|volume| !(0.0..=10.0).contains(&volume))
+        {
+            Some("volume must be between 0 and 10".to_string())
+        } else if self
+            .roll_off_mode
+            .as_deref()
+            .is_some_and(|mode| !ROLL_OFF_MODES.contains(&mode))
+        {
+            Some(format!(
+                "roll_off_mode must be one of {}",
+                ROLL_OFF_MODES.join(", ")
+            ))
+        } else if let (Some(min), Some(max)) =
+            (self.roll_off_min_distance, self.roll_off_max_distance)
+        {
+            (min > max).then(|| {
+                "roll_off_min_distance can't be more than roll_off_max_distance".to_string()
+            })
+        } else {
+            None
+        };
+        problem.map(|problem| format!("Zone {}: {problem}", self.name))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RenameInstances {
+    #[schemars(description = "Paths of instances to rename (e.g., ['workspace.Model', 'workspace.Model (1)'])")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Wildcard pattern with '/' between names, e.g. 'Workspace/Trees/Model*'. '*' matches any characters and '?' one character within a name, '**' matches any number of levels")]
+    pattern: Option<String>,
+    #[schemars(description = "Only rename instances of this class or its subclasses (e.g., 'Model', 'BasePart')")]
+    class_name: Option<String>,
+    #[schemars(description = "New name with {name} for the old name without a ' (2)' style copy suffix and {n} for a sequence number, e.g. 'Tree{n}' or 'Old_{name}'. Defaults to '{name}{n}'")]
+    template: Option<String>,
+    #[schemars(description = "First number of the sequence, defaults to 1")]
+    start: Option<u32>,
+    #[schemars(description = "Pad the numbers with zeros to this many digits")]
+    padding: Option<u32>,
+    #[schemars(description = "Only report the new names without renaming anything")]
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SearchInstances {
+    #[schemars(description = "Path to search under, defaults to 'workspace'")]
+    root: Option<String>,
+    #[schemars(description = "Only match instances of this class or its subclasses (e.g., 'BasePart', 'Model', 'Script')")]
+    class_name: Option<String>,
+    #[schemars(description = "Only match names containing this text, ignoring case")]
+    name_contains: Option<String>,
+    #[schemars(description = "Only match names matching this Lua string pattern (e.g., '^Tree%d+$'). Luau has no regular expressions")]
+    name_pattern: Option<String>,
+    #[schemars(description = "Only match instances whose attributes have these values")]
+    attributes: Option<JsonObject>,
+    #[schemars(description = "Only match instances that have these attributes, whatever their value")]
+    has_attributes: Option<Vec<String>>,
+    #[schemars(description = "Only match instances with this CollectionService tag")]
+    tag: Option<String>,
+    #[schemars(description = "Only match parts and models positioned inside this region")]
+    region: Option<Region>,
+    #[schemars(description = "Point to measure from when sorting by distance, defaults to the origin")]
+    near: Option<Position>,
+    #[schemars(description = "Sort order: path (tree order, the default), name, size (largest first) or distance (closest to near first)")]
+    sort: Option<String>,
+    #[schemars(description = "Maximum number of results, defaults to 100")]
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetSelection {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetSelection {
+    #[schemars(description = "Paths of the instances to select, an empty list clears the selection")]
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CreateCheckpoint {
+    #[schemars(description = "Name of the checkpoint, reusing a name moves that checkpoint here")]
+    name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct UndoToCheckpoint {
+    #[schemars(description = "Name of a checkpoint made with create_checkpoint")]
+    name: String,
+    #[schemars(
+        description = "Also undo changes the user made in Studio since the checkpoint (default: false, which stops without changing anything when one is found)"
+    )]
+    include_user_changes: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ManageTags {
+    #[schemars(description = "Paths of the instances to work on (e.g., ['workspace.Door', 'workspace.Gate'])")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Wildcard pattern with '/' between names, e.g. 'Workspace/Lava/*'. '*' matches any characters and '?' one character within a name, '**' matches any number of levels")]
+    pattern: Option<String>,
+    #[schemars(description = "Only work on instances of this class or its subclasses (e.g., 'BasePart', 'Model')")]
+    class_name: Option<String>,
+    #[schemars(description = "What to do: add or remove the tags, or list the tags each instance has")]
+    action: String,
+    #[schemars(description = "CollectionService tags to add or remove (e.g., ['KillBrick'])")]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ManageAttributes {
+    #[schemars(description = "Paths of the instances to work on (e.g., ['workspace.Coin1', 'workspace.Coin2'])")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Wildcard pattern with '/' between names, e.g. 'Workspace/Coins/*'. '*' matches any characters and '?' one character within a name, '**' matches any number of levels")]
+    pattern: Option<String>,
+    #[schemars(description = "Only work on instances of this class or its subclasses (e.g., 'BasePart', 'Model')")]
+    class_name: Option<String>,
+    #[schemars(description = "What to do: get the attributes, set the values or remove the named attributes")]
+    action: String,
+    #[schemars(description = "Attributes to set with their values: strings, numbers, booleans, {x, y, z} for a Vector3, {x, y} for a Vector2 or {r, g, b} from 0 to 1 for a Color3 (e.g., {\"Value\": 10, \"Respawn\": {\"x\": 0, \"y\": 5, \"z\": 0}})")]
+    values: Option<JsonObject>,
+    #[schemars(description = "Attributes to get or remove. Getting without names returns every attribute")]
+    names: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ReadScriptSource {
+    #[schemars(description = "Path to the Script, LocalScript or ModuleScript (e.g., 'ServerScriptService.GameLoop')")]
+    path: String,
+    #[schemars(description = "First line to return, counting from 1. Defaults to the start of the script")]
+    start_line: Option<u32>,
+    #[schemars(description = "Last line to return. Defaults to the end of the script")]
+    end_line: Option<u32>,
+    #[schemars(description = "Stop at the last whole line within this many bytes, defaults to 100000. Read the rest by starting after the returned endLine")]
+    max_bytes: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct WriteScriptSource {
+    #[schemars(description = "Path to the script (e.g., 'ServerScriptService.GameLoop')")]
+    path: String,
+    #[schemars(description = "The full new source of the script")]
+    source: String,
+    #[schemars(description = "Create the script if nothing exists at the path. Its parent must exist")]
+    create: Option<bool>,
+    #[schemars(description = "Class of a created script: Script (the default), LocalScript or ModuleScript")]
+    class_name: Option<String>,
+    /// Set instead of `source` when the source is sent to the plugin in chunks
+    #[schemars(skip)]
+    chunks: Option<SourceChunks>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct PatchScript {
+    #[schemars(description = "Path to the script (e.g., 'ServerScriptService.GameLoop')")]
+    path: String,
+    #[schemars(description = "Checksum from read_script_source or an earlier edit. The patch is refused if the script has changed since")]
+    base_checksum: Option<String>,
+    #[schemars(description = "Line ranges to replace, numbered as in the script before any of the edits")]
+    edits: Option<Vec<LineEdit>>,
+    #[schemars(description = "Unified diff to apply instead of edits. The context and removed lines must match the script")]
+    diff: Option<String>,
+    /// The edits or diff as line ranges, filled in before the command is queued
+    #[schemars(skip)]
+    hunks: Option<Vec<Hunk>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ListScripts {
+    #[schemars(description = "Only list scripts under this path (e.g., 'ServerScriptService'). Defaults to the whole place")]
+    root: Option<String>,
+    #[schemars(description = "Only list scripts of this class: Script, LocalScript, ModuleScript, or BaseScript for both runnable kinds")]
+    class_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CreateScript {
+    #[schemars(description = "Path of the instance to put the script in (e.g., 'ServerScriptService', 'ReplicatedStorage.Modules')")]
+    parent: String,
+    #[schemars(description = "Name of the new script")]
+    name: String,
+    #[schemars(description = "Script, LocalScript or ModuleScript. Defaults to the template's class, or Script")]
+    class_name: Option<String>,
+    #[schemars(description = "Source of the script. Leave out to start from a template")]
+    source: Option<String>,
+    #[schemars(description = "Template to start from: module, class, server (PlayerAdded handler), client (LocalPlayer setup), remote_event_handler or remote_function_handler. The remote templates create a remote named after the script in ReplicatedStorage")]
+    template: Option<String>,
+    /// Set instead of `source` when the source is sent to the plugin in chunks
+    #[schemars(skip)]
+    chunks: Option<SourceChunks>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SearchCode {
+    #[schemars(description = "Text to find, or a regular expression when regex is set")]
+    query: String,
+    #[schemars(description = "Treat the query as a regular expression (Rust syntax, e.g. 'FireServer\\(.*Damage')")]
+    regex: Option<bool>,
+    #[schemars(description = "Match upper and lower case exactly, off by default")]
+    case_sensitive: Option<bool>,
+    #[schemars(description = "Only search scripts under this path (e.g., 'ServerScriptService'). Defaults to the whole place")]
+    root: Option<String>,
+    #[schemars(description = "Lines to include before and after each match, defaults to 2")]
+    context_lines: Option<u32>,
+    #[schemars(description = "Maximum number of matches to return, defaults to 100")]
+    max_results: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct LintScripts {
+    #[schemars(description = "Only lint scripts under this path (e.g., 'ServerScriptService'). Defaults to the whole place")]
+    root: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FormatScripts {
+    #[schemars(description = "Only format scripts under this path (e.g., 'ServerScriptService'). Defaults to the whole place")]
+    root: Option<String>,
+    #[schemars(description = "Only report which scripts would change, without writing them. Defaults to false")]
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetModuleDependencies {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RenameSymbol {
+    #[schemars(description = "Name to replace, e.g. 'getPlayerData'")]
+    old_name: String,
+    #[schemars(description = "Name to use instead")]
+    new_name: String,
+    #[schemars(description = "Only rename in scripts under this path (e.g., 'ServerScriptService'). Defaults to the whole place")]
+    root: Option<String>,
+    #[schemars(description = "Only report the lines that would change, without writing them. Defaults to false")]
+    dry_run: Option<bool>,
+}
+
+/// Where the plugin fetches a script source or heightmap too large to send with its command
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct SourceChunks {
+    command: Uuid,
+    count: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetModelBounds {
+    #[schemars(description = "Path to instance (e.g., 'Workspace.GrandCanyon.CanyonWalls')")]
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FindGaps {
+    #[schemars(description = "Path to first model/part")]
+    model_a: String,
+    #[schemars(description = "Path to second model/part")]
+    model_b: String,
+    #[schemars(description = "Maximum distance to consider a 'gap' (default: 2 studs)")]
+    threshold: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct CaptureViewport {
+    #[schemars(description = "Optional: Set camera position before capture")]
+    camera_position: Option<Position>,
+    #[schemars(description = "Optional: Set camera look-at target")]
+    camera_target: Option<Position>,
+    #[schemars(description = "Optional: Frame this region, looking at its center from camera_position or from the current camera direction, far enough back to fit it")]
+    region: Option<Region>,
+    #[schemars(description = "Longest side of the returned image in pixels, from 64 to 1024 (default: 768)")]
+    max_size: Option<u32>,
+    #[schemars(description = "Image format, only 'png' is supported")]
+    format: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetConsoleOutput {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetStudioMode {}
+
+/// Reads an instance for `resources/read`, not exposed as a tool
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ReadInstance {
+    path: String,
+}
+
+/// Lists instance paths to complete, not exposed as a tool
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ListInstancePaths {
+    max_depth: u32,
+    limit: u32,
+}
+
+/// Fetches script sources for tools that work on them on the server, not exposed as a tool
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetScriptSources {
+    root: Option<String>,
+}
+
+/// Writes back sources edited on the server, not exposed as a tool. Scripts changed in
+/// Studio since their source was fetched are skipped.
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SetScriptSources {
+    scripts: Vec<ScriptWrite>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ScriptWrite {
+    path: String,
+    source: String,
+    /// Checksum the script's source must still have for it to be written
+    base_checksum: String,
+    #[schemars(skip)]
+    chunks: Option<SourceChunks>,
+}
+
+/// Reply of the plugin's `SetScriptSources`
+#[derive(Debug, Deserialize, Default)]
+struct WrittenScripts {
+    written: Vec<String>,
+    skipped: Vec<tool_results::SkippedScript>,
+}
+
+/// Replaces the paths the plugin reports changes for, not exposed as a tool
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct WatchInstances {
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct ListStudioSessions {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct SelectStudioSession {
+    #[schemars(description = "Session ID from list_studio_sessions. Omit to clear the selection")]
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct GetPendingOperations {
+    #[schemars(description = "IDs of queued or running commands to cancel before listing. Cancelling a running command only stops waiting for it, Studio may still finish it")]
+    cancel: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct FetchResultPage {
+    #[schemars(description = "Continuation token from the end of the previous page")]
+    token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct StartStopPlay {
+    #[schemars(description = "Mode to start or stop, must be start_play, stop, or run_server")]
+    mode: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RunScriptInPlayMode {
+    #[schemars(description = "Code to run")]
+    code: String,
+    #[schemars(description = "Timeout in seconds, defaults to 100 seconds")]
+    timeout: Option<u32>,
+    #[schemars(description = "Mode to run in, must be start_play or run_server")]
+    mode: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct RunTests {
+    #[schemars(description = "'testez' to run the .spec modules with TestEZ or 'testservice' to run the scripts in TestService (default: TestEZ when there are specs, TestService otherwise)")]
+    framework: Option<String>,
+    #[schemars(description = "Paths searched for TestEZ .spec modules (default: ReplicatedStorage, ServerScriptService, ServerStorage, StarterPlayer and Workspace)")]
+    paths: Option<Vec<String>>,
+    #[schemars(description = "Lua pattern a test's full name must match to run, e.g. 'Inventory' or '^Shop buys'")]
+    pattern: Option<String>,
+    #[schemars(description = "Path of the TestEZ ModuleScript (default: the first ModuleScript named TestEZ)")]
+    testez_path: Option<String>,
+    #[schemars(description = "Seconds the tests may take before they are stopped (default: 120)")]
+    timeout: Option<u32>,
+    /// Source of the runner script, filled in before the command is queued
+    #[schemars(skip)]
+    runner_source: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct StartPlaytest {
+    #[schemars(description = "start_play to play solo with a character, or run_server to run the game without one (default: start_play)")]
+    mode: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+struct StopPlaytest {}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+enum ToolArgumentValues {
+    RunCode(RunCode),
+    InsertModel(InsertModel),
+    SearchMarketplace(SearchMarketplace),
+    BatchInsertModels(BatchInsertModels),
+    BatchRunCode(BatchRunCode),
+    GenerateTerrain(GenerateTerrain),
+    GenerateBiomes(GenerateBiomes),
+    FillTerrainRegion(FillTerrainRegion),
+    SculptTerrain(SculptTerrain),
+    ImportHeightmap(HeightmapColumns),
+    ReplaceTerrainMaterial(ReplaceTerrainMaterial),
+    CarvePath(CarvePath),
+    CarveRiver(CarveRiver),
+    SaveTerrainStamp(SaveTerrainStamp),
+    PlaceTerrainStamp(StampPlacement),
+    ExportTerrain(ExportTerrain),
+    ClearWorkspace(ClearWorkspace),
+    SaveScene(SaveScene),
+    LoadScene(LoadScene),
+    DiffScenes(DiffScenes),
+    ExportScene(ExportScene),
+    ImportModelFile(ModelInsertion),
+    Import3dFile(MeshImport),
+    GetConsoleLogs(GetConsoleLogs),
+    GetOutputLog(GetOutputLog),
+    GetPerformanceStats(GetPerformanceStats),
+    GetMemoryReport(GetMemoryReport),
+    GetWorkspaceStats(GetWorkspaceStats),
+    GetChildrenInfo(GetChildrenInfo),
+    GetInstanceTree(GetInstanceTree),
+    CreateInstance(CreateInstance),
+    CreatePart(CreatePart),
+    CreateConstraint(CreateConstraint),
+    BuildStructure(BuildStructure),
+    GenerateBuilding(Box<GenerateBuilding>),
+    SetSkybox(SetSkybox),
+    SetAtmosphere(SetAtmosphere),
+    SetPostEffects(SetPostEffects),
+    InsertDecalOrTexture(InsertDecalOrTexture),
+    InsertAudio(InsertAudio),
+    InsertMesh(InsertMesh),
+    DeleteInstances(DeleteInstances),
+    CloneInstance(CloneInstance),
+    ArrayDuplicate(ArrayDuplicate),
+    PlaceAlongPath(PlaceAlongPath),
+    MoveInstances(MoveInstances),
+    AlignInstances(AlignInstances),
+    Raycast(Raycast),
+    QueryRegion(QueryRegion),
+    SnapToGround(SnapToGround),
+    PaintInstances(PaintInstances),
+    PlaceLights(PlaceLights),
+    CreateParticleEmitter(CreateParticleEmitter),
+    SetupWeather(SetupWeather),
+    SetupDayNightCycle(SetupDayNightCycle),
+    SetupAmbientSound(SetupAmbientSound),
+    SetWorkspacePhysics(SetWorkspacePhysics),
+    RunTests(RunTests),
+    StartPlaytest(StartPlaytest),
+    StopPlaytest(StopPlaytest),
+    RenameInstances(RenameInstances),
+    SearchInstances(SearchInstances),
+    GetSelection(GetSelection),
+    SetSelection(SetSelection),
+    CreateCheckpoint(CreateCheckpoint),
+    UndoToCheckpoint(UndoToCheckpoint),
+    ManageTags(ManageTags),
+    ManageAttributes(ManageAttributes),
+    ReadScriptSource(ReadScriptSource),
+    WriteScriptSource(WriteScriptSource),
+    PatchScript(PatchScript),
+    ListScripts(ListScripts),
+    CreateScript(CreateScript),
+    GetModelBounds(GetModelBounds),
+    FindGaps(FindGaps),
+    CaptureViewport(CaptureViewport),
+    GetConsoleOutput(GetConsoleOutput),
+    StartStopPlay(StartStopPlay),
+    RunScriptInPlayMode(RunScriptInPlayMode),
+    GetStudioMode(GetStudioMode),
+    ReadInstance(ReadInstance),
+    WatchInstances(WatchInstances),
+    ListInstancePaths(ListInstancePaths),
+    GetScriptSources(GetScriptSources),
+    SetScriptSources(SetScriptSources),
+}
+
+impl ToolArgumentValues {
+    /// How long to wait for Studio to reply. Play mode scripts carry their own timeout,
+    /// which wins if it is longer than the configured one.
+    fn timeout(&self, config: &Config) -> Duration {
+        let timeout = config.tool_timeout(self.tool_name());
+        match self {
+            Self::RunScriptInPlayMode(args) => timeout.max(
+                Duration::from_secs(args.timeout.unwrap_or(100).into()) + PLAY_MODE_TIMEOUT_MARGIN,
+            ),
+            Self::RunTests(args) => timeout.max(
+                Duration::from_secs(args.timeout.unwrap_or(DEFAULT_TEST_TIMEOUT).into())
+                    + PLAY_MODE_TIMEOUT_MARGIN,
+            ),
+            Self::ListInstancePaths(_) => timeout.min(COMPLETION_TIMEOUT),
+            _ => timeout,
+        }
+    }
+
+    /// Text the command sends that can be too large for one message: the source of the
+    /// script it writes, the heights of an imported heightmap, the voxels of a stamp, the
+    /// instances of a model file or an imported mesh
+    fn chunked_text(&self) -> Option<&str> {
+        match self {
+            Self::ImportHeightmap(args) => Some(&args.heights),
+            Self::PlaceTerrainStamp(args) => Some(&args.voxels),
+            Self::ImportModelFile(args) => Some(&args.instances),
+            Self::Import3dFile(args) => Some(&args.mesh),
+            Self::WriteScriptSource(args) => Some(&args.source),
+            Self::CreateScript(args) => args.source.as_deref(),
+            // Writes are batched so that only a script sent on its own is ever chunked
+            Self::SetScriptSources(args) => match args.scripts.as_slice() {
+                [write] => Some(&write.source),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Leaves text too large for one message out of the command, so the plugin of
+    /// `command` fetches it in chunks instead
+    fn chunk_text(&mut self, command: Uuid) {
+        let (source, chunks) = match self {
+            Self::ImportHeightmap(args) => (&mut args.heights, &mut args.chunks),
+            Self::PlaceTerrainStamp(args) => (&mut args.voxels, &mut args.chunks),
+            Self::ImportModelFile(args) => (&mut args.instances, &mut args.chunks),
+            Self::Import3dFile(args) => (&mut args.mesh, &mut args.chunks),
+            Self::WriteScriptSource(args) => (&mut args.source, &mut args.chunks),
+            Self::CreateScript(args) => match &mut args.source {
+                Some(source) => (source, &mut args.chunks),
+                None => return,
+            },
+            Self::SetScriptSources(args) => match args.scripts.as_mut_slice() {
+                [write] => (&mut write.source, &mut write.chunks),
+                _ => return,
+            },
+            _ => return,
+        };
+        if source.len() > SCRIPT_CHUNK_BYTES {
+            let count = source_chunks(source).len();
+            *chunks = Some(SourceChunks { command, count });
+            source.clear();
+        }
+    }
+
+    /// Read-only queries jump ahead of edits, and long running jobs yield to everything
+    /// else so quick lookups don't wait behind them.
+    fn default_priority(&self) -> Priority {
+        if self.is_read_only() {
+            return Priority::High;
+        }
+        match self {
+            Self::BatchInsertModels(_)
+            | Self::BatchRunCode(_)
+            | Self::GenerateTerrain(_)
+            | Self::FillTerrainRegion(_)
+            | Self::SculptTerrain(_)
+            | Self::ImportHeightmap(_)
+            | Self::ExportTerrain(_)
+            | Self::ReplaceTerrainMaterial(_)
+            | Self::GenerateBiomes(_)
+            | Self::CarvePath(_)
+            | Self::CarveRiver(_)
+            | Self::PlaceTerrainStamp(_)
+            | Self::LoadScene(_)
+            | Self::CloneInstance(_)
+            | Self::RunScriptInPlayMode(_) => Priority::Low,
+            _ => Priority::Normal,
+        }
+    }
+
+    /// Tools that only read from the place and are safe to run again
+    /// (keep in step with their `read_only_hint` annotation)
+    fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Self::GetConsoleLogs(_)
+                | Self::GetOutputLog(_)
+                | Self::SearchMarketplace(_)
+                | Self::GetPerformanceStats(_)
+                | Self::GetMemoryReport(_)
+                | Self::Raycast(_)
+                | Self::QueryRegion(_)
+                | Self::GetWorkspaceStats(_)
+                | Self::GetChildrenInfo(_)
+                | Self::GetModelBounds(_)
+                | Self::FindGaps(_)
+                | Self::GetConsoleOutput(_)
+                | Self::GetStudioMode(_)
+                | Self::ListScripts(_)
+                | Self::ReadScriptSource(_)
+                | Self::GetSelection(_)
+                | Self::SearchInstances(_)
+                | Self::GetInstanceTree(_)
+                | Self::ReadInstance(_)
+                | Self::WatchInstances(_)
+                | Self::ListInstancePaths(_)
+                | Self::GetScriptSources(_)
+                | Self::DiffScenes(_)
+        )
+    }
+
+    /// Name the tool is exposed under, as used in the config file
+    fn tool_name(&self) -> &'static str {
+        match self {
+            Self::RunCode(_) => "run_code",
+            Self::InsertModel(_) => "insert_model",
+            Self::SearchMarketplace(_) => "search_marketplace",
+            Self::BatchInsertModels(_) => "batch_insert_models",
+            Self::BatchRunCode(_) => "batch_run_code",
+            Self::GenerateTerrain(_) => "generate_terrain",
+            Self::FillTerrainRegion(_) => "fill_terrain_region",
+            Self::SculptTerrain(_) => "sculpt_terrain",
+            Self::ImportHeightmap(_) => "import_heightmap",
+            Self::ReplaceTerrainMaterial(_) => "replace_terrain_material",
+            Self::GenerateBiomes(_) => "generate_biomes",
+            Self::CarvePath(_) => "carve_path",
+            Self::CarveRiver(_) => "carve_river",
+            Self::SaveTerrainStamp(_) => "save_terrain_stamp",
+            Self::PlaceTerrainStamp(_) => "place_terrain_stamp",
+            Self::ExportTerrain(_) => "export_terrain",
+            Self::ClearWorkspace(_) => "clear_workspace",
+            Self::SaveScene(_) => "save_scene",
+            Self::LoadScene(_) => "load_scene",
+            Self::DiffScenes(_) => "diff_scenes",
+            Self::ImportModelFile(_) => "import_model_file",
+            Self::Import3dFile(_) => "import_3d_file",
+            Self::ExportScene(_) => "export_scene",
+            Self::GetConsoleLogs(_) => "get_console_logs",
+            Self::GetOutputLog(_) => "get_output_log",
+            Self::GetPerformanceStats(_) => "get_performance_stats",
+            Self::GetMemoryReport(_) => "get_memory_report",
+            Self::GetWorkspaceStats(_) => "get_workspace_stats",
+            Self::GetChildrenInfo(_) => "get_children_info",
+            Self::GetInstanceTree(_) => "get_instance_tree",
+            Self::CreateInstance(_) => "create_instance",
+            Self::CreatePart(_) => "create_part",
+            Self::CreateConstraint(_) => "create_constraint",
+            Self::BuildStructure(_) => "build_structure",
+            Self::GenerateBuilding(_) => "generate_building",
+            Self::SetSkybox(_) => "set_skybox",
+            Self::SetAtmosphere(_) => "set_atmosphere",
+            Self::SetPostEffects(_) => "set_post_effects",
+            Self::InsertDecalOrTexture(_) => "insert_decal_or_texture",
+            Self::InsertAudio(_) => "insert_audio",
+            Self::InsertMesh(_) => "insert_mesh",
+            Self::DeleteInstances(_) => "delete_instances",
+            Self::CloneInstance(_) => "clone_instance",
+            Self::ArrayDuplicate(_) => "array_duplicate",
+            Self::PlaceAlongPath(_) => "place_along_path",
+            Self::MoveInstances(_) => "move_instances",
+            Self::AlignInstances(_) => "align_instances",
+            Self::Raycast(_) => "raycast",
+            Self::QueryRegion(_) => "query_region",
+            Self::SnapToGround(_) => "snap_to_ground",
+            Self::PaintInstances(_) => "paint_instances",
+            Self::PlaceLights(_) => "place_lights",
+            Self::CreateParticleEmitter(_) => "create_particle_emitter",
+            Self::SetupWeather(_) => "setup_weather",
+            Self::SetupDayNightCycle(_) => "setup_day_night_cycle",
+            Self::SetupAmbientSound(_) => "setup_ambient_sound",
+            Self::SetWorkspacePhysics(_) => "set_workspace_physics",
+            Self::RunTests(_) => "run_tests",
+            Self::StartPlaytest(_) => "start_playtest",
+            Self::StopPlaytest(_) => "stop_playtest",
+            Self::RenameInstances(_) => "rename_instances",
+            Self::SearchInstances(_) => "search_instances",
+            Self::GetSelection(_) => "get_selection",
+            Self::SetSelection(_) => "set_selection",
+            Self::CreateCheckpoint(_) => "create_checkpoint",
+            Self::UndoToCheckpoint(_) => "undo_to_checkpoint",
+            Self::ManageTags(_) => "manage_tags",
+            Self::ManageAttributes(_) => "manage_attributes",
+            Self::ReadScriptSource(_) => "read_script_source",
+            Self::WriteScriptSource(_) => "write_script_source",
+            Self::PatchScript(_) => "patch_script",
+            Self::ListScripts(_) => "list_scripts",
+            Self::CreateScript(_) => "create_script",
+            Self::GetModelBounds(_) => "get_model_bounds",
+            Self::FindGaps(_) => "find_gaps",
+            Self::CaptureViewport(_) => "capture_viewport",
+            Self::GetConsoleOutput(_) => "get_console_output",
+            Self::StartStopPlay(_) => "start_stop_play",
+            Self::RunScriptInPlayMode(_) => "run_script_in_play_mode",
+            Self::GetStudioMode(_) => "get_studio_mode",
+            Self::ReadInstance(_) => "read_instance",
+            Self::WatchInstances(_) => "watch_instances",
+            Self::ListInstancePaths(_) => "list_instance_paths",
+            Self::GetScriptSources(_) => "get_script_sources",
+            Self::SetScriptSources(_) => "set_script_sources",
+        }
+    }
+}
+#[tool_router]
+impl RBXStudioServer {
+    pub fn new(state: PackedState, config: Arc<Config>) -> Self {
+        let mut tool_router = Self::tool_router();
+        for tool in tool_router.list_all() {
+            if !config.is_tool_enabled(&tool.name) {
+                tool_router.remove_route(&tool.name);
+            }
+        }
+        Self {
+            state,
+            config,
+            selected_session: Arc::default(),
+            subscriber: Uuid::new_v4(),
+            result_pages: Arc::default(),
+            instance_tree: Arc::default(),
+            marketplace_searches: Arc::default(),
+            tool_router,
+        }
+    }
+
+    #[tool(
+        description = "Runs a command in Roblox Studio and returns the printed output. Can be used to both make changes and retrieve information",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
+    )]
+    async fn run_code(
+        &self,
+        Parameters(args): Parameters<RunCode>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::RunCode(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Inserts a model from the Roblox marketplace into the workspace: the first result for the query that passes the filters, or the asset_id picked from search_marketplace. Only verified creators' assets are picked unless filters.verified_only is false. The asset found for a query is reused for later inserts of it unless force_refresh is set. Returns the inserted model name.",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn insert_model(
+        &self,
+        Parameters(mut args): Parameters<InsertModel>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let filters = self.marketplace_filters(args.filters.take());
+        if args.asset_id.is_none() {
+            // The search is a command of its own, it must not answer retries of the call
+            let mut search_context = context.clone();
+            search_context.meta.remove(IDEMPOTENCY_KEY_META);
+            let force_refresh = args.force_refresh.unwrap_or(false);
+            let found = match self
+                .cached_marketplace_search(&args.query, 1, filters, force_refresh, search_context)
+                .await?
+            {
+                Ok(found) => found,
+                Err(failed) => return Ok(failed),
+            };
+            let Some(asset) = found.results.first() else {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "No marketplace results for {:?} pass the filters ({} left out)",
+                    args.query, found.filtered_out
+                ))]));
+            };
+            args.asset_id = Some(asset.asset_id);
+        }
+        self.generic_tool_run(ToolArgumentValues::InsertModel(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Searches the Roblox marketplace's free models and returns the top results with their asset ID, name, creator, favorites, thumbnail URL and store page, without inserting anything. Only verified creators' assets are returned unless filters.verified_only is false, and results can be narrowed to a creator or group, a minimum of favorites and an age. Pick one and pass its asset ID to insert_model.",
+        output_schema = tool_results::output_schema::<tool_results::SearchMarketplaceResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn search_marketplace(
+        &self,
+        Parameters(args): Parameters<SearchMarketplace>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let limit = args.limit.unwrap_or(DEFAULT_MARKETPLACE_RESULTS);
+        if !(1..=MAX_MARKETPLACE_RESULTS).contains(&limit) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "limit must be between 1 and {MAX_MARKETPLACE_RESULTS}"
+            ))]));
+        }
+        let filters = self.marketplace_filters(args.filters);
+        let force_refresh = args.force_refresh.unwrap_or(false);
+        let found = match self
+            .cached_marketplace_search(&args.query, limit, filters, force_refresh, context)
+            .await?
+        {
+            Ok(found) => found,
+            Err(failed) => return Ok(failed),
+        };
+        let reply = serde_json::to_string(&found)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("search_marketplace", reply).await)
+    }
+
+    #[tool(
+        description = "Inserts multiple models from the Roblox marketplace in a single call. Each model can have custom position, rotation, scale, name, and parent. Queries inserted before reuse the asset found then unless force_refresh is set. With placement grounded, models are set on the ground and moved apart so they don't overlap. Returns JSON with inserted count, failures, and instance paths.",
+        output_schema = tool_results::output_schema::<tool_results::BatchInsertResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn batch_insert_models(
+        &self,
+        Parameters(mut args): Parameters<BatchInsertModels>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args
+            .placement
+            .as_deref()
+            .is_some_and(|placement| !BATCH_PLACEMENTS.contains(&placement))
+        {
+            Some(format!(
+                "placement must be one of {}",
+                BATCH_PLACEMENTS.join(", ")
+            ))
+        } else if args.min_spacing.is_some_and(|spacing| spacing < 0.0) {
+            Some("min_spacing can't be negative".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        // Queries are looked up like insert_model's without filters, which batches never
+        // had. One that finds nothing is left for the plugin to report.
+        let mut search_context = context.clone();
+        search_context.meta.remove(IDEMPOTENCY_KEY_META);
+        search_context.meta.remove(PROGRESS_TOKEN_META);
+        let force_refresh = args.force_refresh.unwrap_or(false);
+        let filters = MarketplaceFilters {
+            verified_only: Some(false),
+            ..MarketplaceFilters::default()
+        };
+        let unresolved = args
+            .models
+            .iter_mut()
+            .filter(|entry| entry.asset_id.is_none());
+        for entry in unresolved {
+            let found = match self
+                .cached_marketplace_search(
+                    &entry.query,
+                    1,
+                    filters.clone(),
+                    force_refresh,
+                    search_context.clone(),
+                )
+                .await?
+            {
+                Ok(found) => found,
+                Err(failed) => return Ok(failed),
+            };
+            entry.asset_id = found.results.first().map(|asset| asset.asset_id);
+        }
+        self.generic_tool_run(ToolArgumentValues::BatchInsertModels(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Executes multiple Luau scripts sequentially with shared state between them. Scripts can store values in _G to pass data to subsequent scripts. Returns JSON with execution results for each script. Calls with a progress token also get each line the scripts print as progress messages while they run.",
+        output_schema = tool_results::output_schema::<tool_results::BatchRunResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
+    )]
+    async fn batch_run_code(
+        &self,
+        Parameters(args): Parameters<BatchRunCode>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::BatchRunCode(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Generates terrain using noise-based heightmaps. Supports flat, perlin, and ridged noise types. Can optionally carve caves and overhangs with 3D noise and fill water below a specified level.",
+        output_schema = tool_results::output_schema::<tool_results::GenerateTerrainResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
+    )]
+    async fn generate_terrain(
+        &self,
+        Parameters(mut args): Parameters<GenerateTerrain>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if let Some(problem) = args.caves.as_ref().and_then(CaveConfig::problem) {
+            return Ok(CallToolResult::error(vec![Content::text(problem)]));
+        }
+        // Every tile needs the same noise for the terrain to line up between them
+        if let Some(heightmap) = &mut args.heightmap {
+            heightmap.seed.get_or_insert_with(random_seed);
+        }
+        let tiles = args.region.tiles(terrain_tiles::COLUMN_GAP);
+        let count = tiles.len();
+        let commands = tiles
+            .into_iter()
+            .enumerate()
+            .map(|(tile, region)| {
+                ToolArgumentValues::GenerateTerrain(GenerateTerrain {
+                    region,
+                    caves: args
+                        .caves
+                        .as_ref()
+                        .map(|caves| caves.tile_share(tile, count)),
+                    ..args.clone()
+                })
+            })
+            .collect();
+        self.run_tiled(commands, context).await
+    }
+
+    #[tool(
+        description = "Generates terrain from several biomes blended with noise masks, so one call can make plains, forests and mountains that flow into each other. Each biome has a material palette from low ground to peaks, a hill height, and a density of simple trees placed in a Vegetation folder in Workspace. Can optionally carve caves and overhangs with 3D noise and flood water up to a level.",
+        output_schema = tool_results::output_schema::<tool_results::GenerateBiomesResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
+    )]
+    async fn generate_biomes(
+        &self,
+        Parameters(args): Parameters<GenerateBiomes>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let problem = if args.biomes.is_empty() {
+            Some("biomes is empty".to_string())
+        } else if let Some(biome) = args.biomes.iter().find(|b| b.materials.is_empty()) {
+            Some(format!("Biome '{}' has no materials", biome.name))
+        } else if args.biome_size.is_some_and(|size| size <= 0.0) {
+            Some("biome_size must be above 0".to_string())
+        } else {
+            args.biomes
+                .iter()
+                .find(|b| {
+                    b.vegetation_density
+                        .is_some_and(|density| !(0.0..=1.0).contains(&density))
+                })
+                .map(|biome| {
+                    format!(
+                        "vegetation_density of biome '{}' must be from 0 to 1",
+                        biome.name
+                    )
+                })
+                .or_else(|| args.caves.as_ref().and_then(CaveConfig::problem))
+        };
+        if let Some(problem) = problem {
+            return Ok(CallToolResult::error(vec![Content::text(problem)]));
+        }
+        // Every tile needs the same noise for the biomes to line up between them
+        let args = GenerateBiomes {
+            seed: Some(args.seed.unwrap_or_else(random_seed)),
+            ..args
+        };
+        let tiles = args.region.tiles(terrain_tiles::COLUMN_GAP);
+        let count = tiles.len();
+        let commands = tiles
+            .into_iter()
+            .enumerate()
+            .map(|(tile, region)| {
+                ToolArgumentValues::GenerateBiomes(GenerateBiomes {
+                    region,
+                    caves: args
+                        .caves
+                        .as_ref()
+                        .map(|caves| caves.tile_share(tile, count)),
+                    ..args.clone()
+                })
+            })
+            .collect();
+        self.run_tiled(commands, context).await
+    }
+
+    #[tool(
+        description = "Fills a terrain region with a specific material. Can optionally only fill empty space (air).",
+        output_schema = tool_results::output_schema::<tool_results::FillTerrainResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn fill_terrain_region(
+        &self,
+        Parameters(args): Parameters<FillTerrainRegion>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let commands = args
+            .region
+            .tiles(0.0)
+            .into_iter()
+            .map(|region| {
+                ToolArgumentValues::FillTerrainRegion(FillTerrainRegion {
+                    region,
+                    ..args.clone()
+                })
+            })
+            .collect();
+        self.run_tiled(commands, context).await
+    }
+
+    #[tool(
+        description = "Sculpts terrain by raising, lowering, painting, or smoothing at specified points. Each point has position, radius, and strength.",
+        output_schema = tool_results::output_schema::<tool_results::SculptTerrainResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
+    )]
+    async fn sculpt_terrain(
+        &self,
+        Parameters(args): Parameters<SculptTerrain>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SculptTerrain(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Clears objects from the workspace. Can optionally preserve camera, terrain, and specific named instances. Can also clear only within a region. Unless turned off in the server config, the workspace is saved first as a revision of the safety-snapshot scene, named in the result, which load_scene restores.",
+        output_schema = tool_results::output_schema::<tool_results::ClearWorkspaceResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn clear_workspace(
+        &self,
+        Parameters(args): Parameters<ClearWorkspace>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.run_after_safety_snapshot(ToolArgumentValues::ClearWorkspace(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Saves a snapshot of the current workspace with a given name. The snapshot is also stored on disk by the server, so load_scene finds it after Studio restarts. Saving to an existing name adds a new revision, and earlier revisions stay loadable. Can optionally save only objects within a region or exclude specific objects, and take a description and tags to find it by with find_scenes.",
+        output_schema = tool_results::output_schema::<tool_results::SaveSceneResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn save_scene(
+        &self,
+        Parameters(args): Parameters<SaveScene>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !scenes::is_valid_name(&args.name) {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Scene names may only use letters, digits, spaces, - and _, up to 64 characters",
+            )]));
+        }
+        if args.name == scenes::SAFETY_SCENE {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "{} is reserved for the snapshots taken before destructive tools",
+                scenes::SAFETY_SCENE
+            ))]));
+        }
+        self.generic_tool_run(ToolArgumentValues::SaveScene(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Loads a previously saved scene snapshot by name, including scenes saved in earlier Studio sessions. Loads the latest revision unless a revision is given, which can count back from the latest to undo recent saves. Can apply position offset and optionally clear workspace before loading, after saving it as a revision of the safety-snapshot scene unless that's turned off in the server config.",
+        output_schema = tool_results::output_schema::<tool_results::LoadSceneResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn load_scene(
+        &self,
+        Parameters(mut args): Parameters<LoadScene>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !scenes::is_valid_name(&args.name) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Scene not found: {}",
+                args.name
+            ))]));
+        }
+        if !args.clear_existing.unwrap_or(false) {
+            return self
+                .generic_tool_run(ToolArgumentValues::LoadScene(args), context)
+                .await;
+        }
+        // Restoring a safety snapshot takes another one first, so a revision counted from
+        // the latest is pinned before it's added
+        if args.name == scenes::SAFETY_SCENE && args.revision.is_none_or(|revision| revision <= 0) {
+            let pinned = scenes::resolve(&self.config, &args.name, args.revision)
+                .await
+                .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))?;
+            if let Some(revision) = pinned {
+                args.revision = Some(revision.into());
+            }
+        }
+        self.run_after_safety_snapshot(ToolArgumentValues::LoadScene(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Lists the revisions kept of saved scenes, oldest first, with when each was saved and its object count. Pass a revision to load_scene to go back to it.",
+        output_schema = tool_results::output_schema::<tool_results::ListSceneRevisionsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn list_scene_revisions(
+        &self,
+        Parameters(args): Parameters<ListSceneRevisions>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let scenes = match args.name {
+            Some(name) => {
+                let revisions = if scenes::is_valid_name(&name) {
+                    scenes::history(&self.config, &name)
+                        .await
+                        .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))?
+                } else {
+                    Vec::new()
+                };
+                if revisions.is_empty() {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Scene not found: {name}"
+                    ))]));
+                }
+                vec![tool_results::SceneSummary { name, revisions }]
+            }
+            None => scenes::list(&self.config)
+                .await
+                .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))?,
+        };
+        let reply = serde_json::to_string(&tool_results::ListSceneRevisionsResult { scenes })
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(tool_result("list_scene_revisions", reply))
+    }
+
+    #[tool(
+        description = "Searches the saved scenes by text in their name, description and tags, and by tags they must all have, and lists the matches newest first with the latest revision's description, tags, capture time and object count.",
+        output_schema = tool_results::output_schema::<tool_results::FindScenesResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn find_scenes(
+        &self,
+        Parameters(args): Parameters<FindScenes>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tags = args.tags.unwrap_or_default();
+        let mut scenes = scenes::find(&self.config, args.text.as_deref(), &tags)
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))?;
+        let limit = args.limit.unwrap_or(DEFAULT_SCENE_MATCHES);
+        let truncated = scenes.len().saturating_sub(limit);
+        scenes.truncate(limit);
+        let reply = serde_json::to_string(&tool_results::FindScenesResult { scenes, truncated })
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(tool_result("find_scenes", reply))
+    }
+
+    #[tool(
+        description = "Deletes the oldest revisions of a saved scene, keeping the newest ones (default: 1). keep = 0 deletes the scene entirely. Scenes only kept in Studio's memory aren't affected.",
+        output_schema = tool_results::output_schema::<tool_results::PruneSceneHistoryResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn prune_scene_history(
+        &self,
+        Parameters(args): Parameters<PruneSceneHistory>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !scenes::is_valid_name(&args.name) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Scene not found: {}",
+                args.name
+            ))]));
+        }
+        let keep = args.keep.unwrap_or(1) as usize;
+        let (removed, kept) = scenes::prune(&self.config, &args.name, keep)
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))?;
+        if removed.is_empty() && kept.is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Scene not found: {}",
+                args.name
+            ))]));
+        }
+        let reply = serde_json::to_string(&tool_results::PruneSceneHistoryResult {
+            name: args.name,
+            removed,
+            kept,
+        })
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(tool_result("prune_scene_history", reply))
+    }
+
+    #[tool(
+        description = "Uploads a mesh, model, audio or image file from the machine running the server to Roblox with Open Cloud, waits for moderation, and returns the new asset ID with the tool that inserts it: meshes and models with insert_model, audio with insert_audio and images with insert_decal_or_texture. Needs open_cloud_api_key and an owner in the server config.",
+        output_schema = tool_results::output_schema::<tool_results::UploadAssetResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = true
+        )
+    )]
+    async fn upload_asset(
+        &self,
+        Parameters(args): Parameters<UploadAsset>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let path = std::path::Path::new(&args.path);
+        let name = args.name.unwrap_or_else(|| {
+            path.file_stem().map_or_else(
+                || args.path.clone(),
+                |stem| stem.to_string_lossy().into_owned(),
+            )
+        });
+        let uploaded = match open_cloud::upload(
+            &self.config,
+            path,
+            &name,
+            args.description.as_deref().unwrap_or_default(),
+            args.wait_for_moderation.unwrap_or(true),
+        )
+        .await
+        {
+            Ok(uploaded) => uploaded,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+        };
+        let insert_with = match uploaded.asset_type {
+            "Audio" => "insert_audio",
+            "Decal" => "insert_decal_or_texture",
+            _ => "insert_model",
+        };
+        let reply = serde_json::to_string(&tool_results::UploadAssetResult {
+            success: uploaded.moderation_state != "Rejected",
+            asset_id: uploaded.asset_id,
+            asset_type: uploaded.asset_type.to_string(),
+            name,
+            moderation_state: uploaded.moderation_state,
+            insert_with: insert_with.to_string(),
+        })
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(tool_result("upload_asset", reply))
+    }
+
+    #[tool(
+        description = "Compares two saved scene snapshots, or a snapshot with the live workspace, and reports the instances added, removed and modified with the properties that changed on each. Use it to review what was changed since a save_scene.",
+        output_schema = tool_results::output_schema::<tool_results::DiffScenesResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn diff_scenes(
+        &self,
+        Parameters(args): Parameters<DiffScenes>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::DiffScenes(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Inserts the instances of an .rbxm or .rbxmx model file, from the server's machine or sent as base64, under a parent, optionally moved and rotated about their centre. Properties of types Studio plugins can't set, such as meshes' physics data, keep their defaults and are counted in unsupportedProperties.",
+        output_schema = tool_results::output_schema::<tool_results::ImportModelFileResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn import_model_file(
+        &self,
+        Parameters(args): Parameters<ImportModelFile>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let contents = match (&args.path, &args.data_base64) {
+            (Some(path), None) => tokio::fs::read(path)
+                .await
+                .map_err(|e| format!("Couldn't read {path}: {e}")),
+            (None, Some(encoded)) => {
+                // Data URLs are accepted as they are
+                let data = encoded
+                    .rsplit_once("base64,")
+                    .map_or(encoded.as_str(), |(_, data)| data);
+                BASE64_STANDARD
+                    .decode(data.trim())
+                    .map_err(|e| format!("data_base64 isn't valid base64: {e}"))
+            }
+            _ => Err("Pass either path or data_base64".to_string()),
+        };
+        let contents = match contents {
+            Ok(contents) if contents.len() > MAX_MODEL_FILE_BYTES => Err(format!(
+                "The model file is {} bytes, the most import_model_file reads is {MAX_MODEL_FILE_BYTES}",
+                contents.len()
+            )),
+            other => other,
+        };
+        let contents = match contents {
+            Ok(contents) => contents,
+            Err(message) => return Ok(CallToolResult::error(vec![Content::text(message)])),
+        };
+        let read = tokio::task::spawn_blocking(move || model_files::read(&contents))
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let tree = match read {
+            Ok(tree) if tree.instances.is_empty() => {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "The model file holds no instances",
+                )]))
+            }
+            Ok(tree) => tree,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+        };
+        let instances = serde_json::to_string(&tree.instances)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let command = ModelInsertion {
+            parent: args.parent,
+            position: args.position,
+            rotation: args.rotation,
+            instances,
+            chunks: None,
+        };
+        let reply = match self
+            .run_command(ToolArgumentValues::ImportModelFile(command), context)
+            .await?
+        {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(failed),
+        };
+        // Properties left out of the command are only known here
+        let reply = match serde_json::from_str::<Value>(&reply) {
+            Ok(Value::Object(mut result)) if result.get("success") == Some(&Value::Bool(true)) => {
+                result.insert(
+                    "unsupportedProperties".to_string(),
+                    tree.unsupported_properties.into(),
+                );
+                Value::Object(result).to_string()
+            }
+            _ => reply,
+        };
+        Ok(self.paged_result("import_model_file", reply).await)
+    }
+
+    #[tool(
+        description = "Imports a 3D file from the machine running the server into the place, moved, rotated and scaled. By default the file is uploaded to Roblox with Open Cloud, .obj files converted to glTF first, and the new asset inserted. Without open_cloud_api_key in the server config, .obj files are built in Studio as an EditableMesh instead, which isn't saved with the place.",
+        output_schema = tool_results::output_schema::<tool_results::Import3dFileResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = true
+        )
+    )]
+    async fn import_3d_file(
+        &self,
+        Parameters(args): Parameters<Import3dFile>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let path = std::path::Path::new(&args.path);
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        let default_method = if self.config.open_cloud_api_key.is_some() {
+            "upload"
+        } else {
+            "editable_mesh"
+        };
+        let method = args.method.as_deref().unwrap_or(default_method);
+        let problem = if !MESH_FILE_EXTENSIONS.contains(&extension.as_str()) {
+            Some(format!(
+                "Can't import {}, supported files are .{}",
+                args.path,
+                MESH_FILE_EXTENSIONS.join(", .")
+            ))
+        } else if !MESH_IMPORT_METHODS.contains(&method) {
+            Some(format!(
+                "method must be one of {}",
+                MESH_IMPORT_METHODS.join(", ")
+            ))
+        } else if method == "editable_mesh" && extension != "obj" {
+            Some(format!(
+                "Only .obj files can be built as an EditableMesh, set open_cloud_api_key in the server config to upload .{extension} files"
+            ))
+        } else if args.scale.is_some_and(|scale| scale <= 0.0) {
+            Some("scale must be more than 0".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Ok(CallToolResult::error(vec![Content::text(problem)]));
+        }
+
+        let contents = match tokio::fs::read(path).await {
+            Ok(contents) if contents.len() > MAX_MODEL_FILE_BYTES => Err(format!(
+                "The file is {} bytes, the most import_3d_file reads is {MAX_MODEL_FILE_BYTES}",
+                contents.len()
+            )),
+            Ok(contents) => Ok(contents),
+            Err(e) => Err(format!("Couldn't read {}: {e}", args.path)),
+        };
+        let contents = match contents {
+            Ok(contents) => contents,
+            Err(message) => return Ok(CallToolResult::error(vec![Content::text(message)])),
+        };
+        let name = args.name.unwrap_or_else(|| {
+            path.file_stem().map_or_else(
+                || args.path.clone(),
+                |stem| stem.to_string_lossy().into_owned(),
+            )
+        });
+
+        let mesh = if extension == "obj" {
+            let read = tokio::task::spawn_blocking(move || {
+                mesh_files::read_obj(&String::from_utf8_lossy(&contents))
+            })
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            match read {
+                Ok(mesh) => Some(mesh),
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Couldn't read {}: {e:#}",
+                        args.path
+                    ))]))
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut command = MeshImport {
+            name: name.clone(),
+            parent: args.parent,
+            position: args.position,
+            rotation: args.rotation,
+            scale: args.scale,
+            texture_id: args.texture_id,
+            asset_id: None,
+            mesh: String::new(),
+            chunks: None,
+        };
+        let mut uploaded = None;
+        match (method, mesh) {
+            ("editable_mesh", Some(mesh)) => {
+                if !mesh.fits_editable_mesh() {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "The mesh has {} vertices and {} triangles, an EditableMesh can have at most {} and {}. Upload it instead",
+                        mesh.positions.len(),
+                        mesh.triangles.len(),
+                        mesh_files::MAX_EDITABLE_VERTICES,
+                        mesh_files::MAX_EDITABLE_TRIANGLES
+                    ))]));
+                }
+                command.mesh = serde_json::to_string(&mesh)
+                    .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            }
+            (_, mesh) => {
+                let upload = match &mesh {
+                    Some(mesh) => {
+                        open_cloud::upload_contents(
+                            &self.config,
+                            &format!("{name}.glb"),
+                            mesh.to_glb(&name),
+                            &name,
+                            "",
+                            true,
+                        )
+                        .await
+                    }
+                    None => open_cloud::upload(&self.config, path, &name, "", true).await,
+                };
+                let upload = match upload {
+                    Ok(upload) if upload.moderation_state == "Rejected" => {
+                        return Ok(CallToolResult::error(vec![Content::text(format!(
+                            "Moderation rejected the uploaded asset {}",
+                            upload.asset_id
+                        ))]))
+                    }
+                    Ok(upload) => upload,
+                    Err(e) => {
+                        return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))]))
+                    }
+                };
+                command.asset_id = Some(upload.asset_id);
+                uploaded = Some(upload);
+            }
+        }
+
+        let reply = match self
+            .run_command(ToolArgumentValues::Import3dFile(command), context)
+            .await?
+        {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(failed),
+        };
+        // How the file got to Studio is only known here
+        let reply = match serde_json::from_str::<Value>(&reply) {
+            Ok(Value::Object(mut result)) if result.get("success") == Some(&Value::Bool(true)) => {
+                result.insert("method".to_string(), method.into());
+                if let Some(uploaded) = uploaded {
+                    result.insert("assetId".to_string(), uploaded.asset_id.into());
+                    result.insert(
+                        "moderationState".to_string(),
+                        uploaded.moderation_state.into(),
+                    );
+                }
+                Value::Object(result).to_string()
+            }
+            _ => reply,
+        };
+        Ok(self.paged_result("import_3d_file", reply).await)
+    }
+
+    #[tool(
+        description = "Writes a saved scene, or an instance and its descendants, to an .rbxm or .rbxmx model file on the server's machine so builds can be committed to source control or shared. Keeps the properties scene snapshots hold: names, classes, size, CFrame, color, material, transparency, anchoring, collision, part shape and primary parts.",
+        output_schema = tool_results::output_schema::<tool_results::ExportSceneResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn export_scene(
+        &self,
+        Parameters(args): Parameters<ExportScene>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let path = std::path::PathBuf::from(&args.path);
+        let problem = if args.scene.is_some() == args.instance.is_some() {
+            Some("Give either scene or instance".to_string())
+        } else if model_files::ModelFormat::from_path(&path).is_none() {
+            Some(format!("{} must end in .rbxm or .rbxmx", args.path))
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Ok(CallToolResult::error(vec![Content::text(problem)]));
+        }
+        let reply = match self
+            .run_command(ToolArgumentValues::ExportScene(args), context)
+            .await?
+        {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(failed),
+        };
+        // Anything else is the plugin explaining why it couldn't read the scene
+        let Ok(scene) = serde_json::from_str::<SceneObjects>(&reply) else {
+            return Ok(CallToolResult::error(vec![Content::text(reply)]));
+        };
+        let written =
+            tokio::task::spawn_blocking(move || model_files::write(&scene.objects, &path))
+                .await
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let result = match written {
+            Ok(result) => result,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+        };
+        let reply = serde_json::to_string(&result)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("export_scene", reply).await)
+    }
+
+    #[tool(
+        description = "Retrieves console logs from Roblox Studio. Captures all print(), warn(), and error() output as well as Roblox engine messages. Supports polling with sequence numbers, level filtering, and pagination.",
+        output_schema = tool_results::output_schema::<tool_results::ConsoleLogsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_console_logs(
+        &self,
+        Parameters(args): Parameters<GetConsoleLogs>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetConsoleLogs(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Returns recent Studio output: prints, warnings and errors with the time they were logged and, for errors, the script, line and stack trace they came from. Filters by least severity and by time, pass the time of the last entry as since to get only newer output.",
+        output_schema = tool_results::output_schema::<tool_results::OutputLogResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_output_log(
+        &self,
+        Parameters(args): Parameters<GetOutputLog>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args
+            .level
+            .as_deref()
+            .is_some_and(|level| !OUTPUT_LEVELS.contains(&level))
+        {
+            Some(format!("level must be one of {}", OUTPUT_LEVELS.join(", ")))
+        } else if args
+            .limit
+            .is_some_and(|limit| !(1..=MAX_OUTPUT_ENTRIES).contains(&limit))
+        {
+            Some(format!("limit must be between 1 and {MAX_OUTPUT_ENTRIES}"))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::GetOutputLog(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Gets performance figures from Studio's Stats service: the instance count, physics step and heartbeat times averaged over a short sample with their peaks, physics FPS, simulated primitives and contacts, network send and receive rates, and memory in use per category. Call it before and after building to check a change isn't hurting performance. Physics and network figures are only meaningful while a playtest is running.",
+        output_schema = tool_results::output_schema::<tool_results::PerformanceStatsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_performance_stats(
+        &self,
+        Parameters(args): Parameters<GetPerformanceStats>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if args
+            .sample_seconds
+            .is_some_and(|seconds| !(0.0..=MAX_STATS_SAMPLE_SECONDS).contains(&seconds))
+        {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "sample_seconds must be between 0 and {MAX_STATS_SAMPLE_SECONDS}"
+            ))]));
+        }
+        self.generic_tool_run(ToolArgumentValues::GetPerformanceStats(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Reports what is making the place heavy: memory Studio has in use per category, each Workspace model and folder and each storage service with its instance, part and script counts, script source size, unique meshes and textures and an estimated size, heaviest first, and the terrain's voxel count with an estimate next to the memory Studio reports for it.",
+        output_schema = tool_results::output_schema::<tool_results::MemoryReportResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_memory_report(
+        &self,
+        Parameters(args): Parameters<GetMemoryReport>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if args
+            .limit
+            .is_some_and(|limit| !(1..=MAX_MEMORY_REPORT_CONTAINERS).contains(&limit))
+        {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "limit must be between 1 and {MAX_MEMORY_REPORT_CONTAINERS}"
+            ))]));
+        }
+        self.generic_tool_run(ToolArgumentValues::GetMemoryReport(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Gets statistics about the workspace including part count, model count, size distribution, and color distribution. Useful for analyzing scene complexity and visual composition.",
+        output_schema = tool_results::output_schema::<tool_results::WorkspaceStatsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_workspace_stats(
+        &self,
+        Parameters(args): Parameters<GetWorkspaceStats>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetWorkspaceStats(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Gets information about all children of a specified instance. Returns name, className, and part count for each child. Optionally includes bounding box information (min, max, size, center coordinates). Useful for exploring scene hierarchy and understanding model composition.",
+        output_schema = tool_results::output_schema::<tool_results::ChildrenInfoResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_children_info(
+        &self,
+        Parameters(args): Parameters<GetChildrenInfo>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetChildrenInfo(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Gets the instance hierarchy under a path as nested JSON, with each instance's name, class and children. Can include chosen properties and attributes, limit the depth and filter by class. Use this instead of traversal scripts with run_code.",
+        output_schema = tool_results::output_schema::<tool_results::InstanceTreeResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_instance_tree(
+        &self,
+        Parameters(args): Parameters<GetInstanceTree>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetInstanceTree(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Creates an instance of a class under a parent, with an optional name and initial property values. Returns the full path of the new instance and any properties that couldn't be set.",
+        output_schema = tool_results::output_schema::<tool_results::CreateInstanceResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn create_instance(
+        &self,
+        Parameters(args): Parameters<CreateInstance>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if let Err(message) = api_dump::check_creatable(&args.class_name) {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::CreateInstance(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Creates a primitive part (Block, Sphere, Cylinder, Wedge or CornerWedge) with a size, position, rotation, color, material, transparency, anchoring and collision settings. Returns the full path of the new part.",
+        output_schema = tool_results::output_schema::<tool_results::CreatePartResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn create_part(
+        &self,
+        Parameters(args): Parameters<CreatePart>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args
+            .shape
+            .as_deref()
+            .is_some_and(|shape| !PART_SHAPES.contains(&shape))
+        {
+            Some(format!("shape must be one of {}", PART_SHAPES.join(", ")))
+        } else if args
+            .size
+            .as_ref()
+            .is_some_and(|size| size.x <= 0.0 || size.y <= 0.0 || size.z <= 0.0)
+        {
+            Some("size must be more than 0 on every axis".to_string())
+        } else if args
+            .transparency
+            .is_some_and(|transparency| !(0.0..=1.0).contains(&transparency))
+        {
+            Some("transparency must be between 0 and 1".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::CreatePart(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Joins two parts with a constraint: a weld, hinge, motor, servo, rope, rod, spring, ball socket, prismatic or rigid joint, with the attachments placed at offsets on each part, an axis for hinges and sliders, and properties like motor speed and torque. Returns the paths of the constraint and its attachments.",
+        output_schema = tool_results::output_schema::<tool_results::CreateConstraintResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn create_constraint(
+        &self,
+        Parameters(args): Parameters<CreateConstraint>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if !CONSTRAINT_KINDS.contains(&args.kind.as_str()) {
+            Some(format!(
+                "kind must be one of {}",
+                CONSTRAINT_KINDS.join(", ")
+            ))
+        } else if args.part0 == args.part1 {
+            Some("part0 and part1 must be different parts".to_string())
+        } else if args
+            .axis
+            .as_ref()
+            .is_some_and(|axis| axis.x == 0.0 && axis.y == 0.0 && axis.z == 0.0)
+        {
+            Some("axis needs a direction, it can't be zero".to_string())
+        } else if args.kind == "weld"
+            && (args.attachment0.is_some() || args.attachment1.is_some() || args.axis.is_some())
+        {
+            Some("Welds hold the parts as they are, attachments and axis don't apply".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::CreateConstraint(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Builds a room as a Model of anchored parts: walls around a rectangular footprint with a thickness, height, material and color, an optional floor and ceiling, and doors and windows cut out of the walls. Returns the path of the Model and the number of parts in it.",
+        output_schema = tool_results::output_schema::<tool_results::BuildStructureResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn build_structure(
+        &self,
+        Parameters(mut args): Parameters<BuildStructure>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let walls = args.walls.clone().unwrap_or_else(|| {
+            structures::WALLS
+                .iter()
+                .map(|wall| wall.to_string())
+                .collect()
+        });
+        let room = structures::Room {
+            width: args.width,
+            depth: args.depth,
+            height: args.height.unwrap_or(12.0),
+            thickness: args.thickness.unwrap_or(1.0),
+            floor: args.floor.unwrap_or(true),
+            ceiling: args.ceiling.unwrap_or(false),
+            walls: &walls,
+            openings: args.openings.as_deref().unwrap_or_default(),
+        };
+        match room.pieces() {
+            Ok(pieces) if pieces.is_empty() => {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "Nothing to build, enable the floor, the ceiling or at least one wall",
+                )]))
+            }
+            Ok(pieces) => args.pieces = Some(pieces),
+            Err(message) => return Ok(CallToolResult::error(vec![Content::text(message)])),
+        }
+        self.generic_tool_run(ToolArgumentValues::BuildStructure(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Generates a multi-storey building as a Model: outside walls with rows of windows on every storey, a door on the ground floor, floor slabs, switchback stairs between the storeys and a flat roof, finished from a material palette. Each storey is its own Model inside. Returns the path of the building and the number of parts in it.",
+        output_schema = tool_results::output_schema::<tool_results::GenerateBuildingResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn generate_building(
+        &self,
+        Parameters(mut args): Parameters<GenerateBuilding>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let windows = args.windows.clone().unwrap_or_default();
+        let door = args.door.clone().unwrap_or_default();
+        let building = structures::Building {
+            width: args.width,
+            depth: args.depth,
+            floors: args.floors,
+            floor_height: args.floor_height.unwrap_or(12.0),
+            thickness: args.wall_thickness.unwrap_or(1.0),
+            stairs: args.stairs.unwrap_or(true),
+            stair_width: args.stair_width.unwrap_or(4.0),
+            windows: &windows,
+            door: &door,
+        };
+        let message = if building.stair_width <= 0.0 {
+            Some("stair_width must be more than 0".to_string())
+        } else {
+            match building.pieces() {
+                Ok(pieces) => {
+                    args.pieces = Some(pieces);
+                    None
+                }
+                Err(message) => Some(message),
+            }
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(
+            ToolArgumentValues::GenerateBuilding(Box::new(args)),
+            context,
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Sets up the Sky in Lighting, creating it if there is none, from a preset bundled with the server or six face textures, with the sun and moon textures and sizes and the number of stars. Returns the Sky's previous settings, which undo the change when passed back, or no previous settings if the Sky was created.",
+        output_schema = tool_results::output_schema::<tool_results::SetSkyboxResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn set_skybox(
+        &self,
+        Parameters(mut args): Parameters<SetSkybox>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args.preset.is_some() && args.faces.is_some() {
+            Some("Pass either a preset or faces".to_string())
+        } else if args
+            .sun_angular_size
+            .is_some_and(|size| !(0.0..=60.0).contains(&size))
+        {
+            Some("sun_angular_size must be between 0 and 60".to_string())
+        } else if args
+            .moon_angular_size
+            .is_some_and(|size| !(0.0..=60.0).contains(&size))
+        {
+            Some("moon_angular_size must be between 0 and 60".to_string())
+        } else if args.star_count.is_some_and(|count| count > MAX_SKY_STARS) {
+            Some(format!("star_count can be at most {MAX_SKY_STARS}"))
+        } else {
+            match args.resolve_textures() {
+                Ok(textures) => {
+                    args.textures = Some(textures);
+                    None
+                }
+                Err(message) => Some(message),
+            }
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::SetSkybox(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Sets up the Atmosphere in Lighting, creating it if there is none, and the classic fog from a preset like 'clear_day', 'dense_fog' or 'sunset_haze' and individual density, offset, color, decay, glare, haze and fog values. The classic fog only shows once the Atmosphere is removed with atmosphere: false. Returns the previous settings, which undo the change when passed back.",
+        output_schema = tool_results::output_schema::<tool_results::SetAtmosphereResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn set_atmosphere(
+        &self,
+        Parameters(mut args): Parameters<SetAtmosphere>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let in_range =
+            |value: Option<f64>, max: f64| value.is_none_or(|value| (0.0..=max).contains(&value));
+        let message = if args.preset.is_none()
+            && args.atmosphere.is_none()
+            && !args.sets_atmosphere()
+            && args.fog_start.is_none()
+            && args.fog_end.is_none()
+            && args.fog_color.is_none()
+        {
+            Some("Pass a preset or the properties to change".to_string())
+        } else if args.atmosphere == Some(false) && args.sets_atmosphere() {
+            Some("density, offset, color, decay, glare and haze set the Atmosphere, which atmosphere: false removes".to_string())
+        } else if !in_range(args.density, 1.0) || !in_range(args.offset, 1.0) {
+            Some("density and offset must be between 0 and 1".to_string())
+        } else if !in_range(args.glare, 10.0) || !in_range(args.haze, 10.0) {
+            Some("glare and haze must be between 0 and 10".to_string())
+        } else if let Some(preset) = &args.preset {
+            match atmosphere_presets::find(preset) {
+                Ok(preset) => {
+                    args.apply_preset(preset);
+                    None
+                }
+                Err(message) => Some(message),
+            }
+        } else {
+            None
+        };
+        let message = message.or_else(|| match (args.fog_start, args.fog_end) {
+            _ if !in_range(args.fog_start, f64::MAX) || !in_range(args.fog_end, f64::MAX) => {
+                Some("fog_start and fog_end can't be negative".to_string())
+            }
+            (Some(start), Some(end)) if end < start => {
+                Some("fog_end must be at least fog_start".to_string())
+            }
+            _ => None,
+        });
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::SetAtmosphere(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Sets up the Bloom, DepthOfField, ColorCorrection and SunRays effects in Lighting, creating the ones that are missing, from a preset like 'cinematic', 'horror' or 'vibrant' and individual settings per effect. Returns the settings of every effect in Lighting afterwards.",
+        output_schema = tool_results::output_schema::<tool_results::SetPostEffectsResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn set_post_effects(
+        &self,
+        Parameters(mut args): Parameters<SetPostEffects>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args.preset.is_none()
+            && args.bloom.is_none()
+            && args.depth_of_field.is_none()
+            && args.color_correction.is_none()
+            && args.sun_rays.is_none()
+        {
+            Some("Pass a preset or the effects to change".to_string())
+        } else if let Some(message) = args.problem() {
+            Some(message)
+        } else if let Some(preset) = &args.preset {
+            match post_effect_presets::find(preset) {
+                Ok(preset) => {
+                    args.apply_preset(preset);
+                    None
+                }
+                Err(message) => Some(message),
+            }
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::SetPostEffects(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Applies an image to a part by asset ID or marketplace search: a Decal stretched over a face, a Texture tiled across it with a size and offset per tile, or a SurfaceAppearance on a MeshPart. Returns the paths of the instances created.",
+        output_schema = tool_results::output_schema::<tool_results::InsertDecalOrTextureResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn insert_decal_or_texture(
+        &self,
+        Parameters(args): Parameters<InsertDecalOrTexture>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let kind = args.kind.as_deref().unwrap_or("decal");
+        let message = if !SURFACE_KINDS.contains(&kind) {
+            Some(format!("kind must be one of {}", SURFACE_KINDS.join(", ")))
+        } else if args.asset_id.is_some() == args.query.is_some() {
+            Some("Pass either asset_id or query".to_string())
+        } else if args
+            .face
+            .as_deref()
+            .is_some_and(|face| !FACES.contains(&face))
+        {
+            Some(format!("face must be one of {}", FACES.join(", ")))
+        } else if kind != "texture"
+            && [
+                args.studs_per_tile_u,
+                args.studs_per_tile_v,
+                args.offset_studs_u,
+                args.offset_studs_v,
+            ]
+            .iter()
+            .any(Option::is_some)
+        {
+            Some("Tile sizes and offsets only apply to textures".to_string())
+        } else if kind == "surface_appearance"
+            && (args.face.is_some() || args.transparency.is_some())
+        {
+            Some("face and transparency don't apply to surface appearances".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::InsertDecalOrTexture(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Inserts a Sound by audio asset ID or Creator Store search under an instance, with its volume, looping, playback speed and roll-off, and can play a few seconds of it in Studio so the user hears the choice. Returns the Sound's path and whether the audio loaded, which fails for audio the place isn't allowed to use.",
+        output_schema = tool_results::output_schema::<tool_results::InsertAudioResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn insert_audio(
+        &self,
+        Parameters(mut args): Parameters<InsertAudio>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args.asset_id.is_some() == args.query.is_some() {
+            Some("Pass either asset_id or query".to_string())
+        } else if args
+            .volume
+            .is_some_and(|volume| !(0.0..=10.0).contains(&volume))
+        {
+            Some("volume must be between 0 and 10".to_string())
+        } else if args
+            .preview_seconds
+            .is_some_and(|seconds| !(seconds > 0.0 && seconds <= MAX_AUDIO_PREVIEW_SECS))
+        {
+            Some(format!(
+                "preview_seconds must be more than 0 and at most {MAX_AUDIO_PREVIEW_SECS}"
+            ))
+        } else if args
+            .roll_off_mode
+            .as_deref()
+            .is_some_and(|mode| !ROLL_OFF_MODES.contains(&mode))
+        {
+            Some(format!(
+                "roll_off_mode must be one of {}",
+                ROLL_OFF_MODES.join(", ")
+            ))
+        } else if args
+            .roll_off_min_distance
+            .zip(args.roll_off_max_distance)
+            .is_some_and(|(min, max)| min > max)
+        {
+            Some("roll_off_min_distance can't be more than roll_off_max_distance".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        // Plugins have no way to search audio, so the server picks the asset
+        if let Some(query) = args.query.take() {
+            match marketplace::search_audio(&query).await {
+                Ok(Some((asset_id, name))) => {
+                    args.asset_id = Some(asset_id);
+                    args.name.get_or_insert(name);
+                }
+                Ok(None) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "No audio found for {query:?}"
+                    ))]))
+                }
+                Err(e) => {
+                    let message = format!(
+                        "Could not search the Creator Store for audio, pass an asset_id instead: {e:#}"
+                    );
+                    return Ok(CallToolResult::error(vec![Content::text(message)]));
+                }
+            }
+        }
+        self.generic_tool_run(ToolArgumentValues::InsertAudio(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Creates a MeshPart from a mesh asset ID, which insert_model can't insert, with an optional texture, position, rotation, size, collision fidelity and render fidelity. Returns the MeshPart's path, its size and the size the mesh was uploaded at.",
+        output_schema = tool_results::output_schema::<tool_results::InsertMeshResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn insert_mesh(
+        &self,
+        Parameters(args): Parameters<InsertMesh>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args
+            .collision_fidelity
+            .as_deref()
+            .is_some_and(|fidelity| !COLLISION_FIDELITIES.contains(&fidelity))
+        {
+            Some(format!(
+                "collision_fidelity must be one of {}",
+                COLLISION_FIDELITIES.join(", ")
+            ))
+        } else if args
+            .render_fidelity
+            .as_deref()
+            .is_some_and(|fidelity| !RENDER_FIDELITIES.contains(&fidelity))
+        {
+            Some(format!(
+                "render_fidelity must be one of {}",
+                RENDER_FIDELITIES.join(", ")
+            ))
+        } else if args
+            .size
+            .as_ref()
+            .is_some_and(|size| size.x <= 0.0 || size.y <= 0.0 || size.z <= 0.0)
+        {
+            Some("size must be more than 0 on every axis".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::InsertMesh(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Deletes instances by path or wildcard pattern and returns how many were removed. Services, Terrain and the current camera are never deleted. Use dry_run first to check what a pattern matches. Unless turned off in the server config, the workspace is saved first as a revision of the safety-snapshot scene, named in the result, which load_scene restores.",
+        output_schema = tool_results::output_schema::<tool_results::DeleteInstancesResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn delete_instances(
+        &self,
+        Parameters(args): Parameters<DeleteInstances>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if args.paths.as_ref().is_none_or(Vec::is_empty)
+            && args.patterns.as_ref().is_none_or(Vec::is_empty)
+        {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Pass the paths or patterns of the instances to delete",
+            )]));
+        }
+        if args.dry_run.unwrap_or(false) {
+            return self
+                .generic_tool_run(ToolArgumentValues::DeleteInstances(args), context)
+                .await;
+        }
+        self.run_after_safety_snapshot(ToolArgumentValues::DeleteInstances(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Copies an instance several times, moving and rotating each copy by a further offset to build rows, rings or stacks. Returns the paths of the copies. Offsets apply to parts and models.",
+        output_schema = tool_results::output_schema::<tool_results::CloneInstanceResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn clone_instance(
+        &self,
+        Parameters(args): Parameters<CloneInstance>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !(1..=MAX_CLONE_COUNT).contains(&args.count) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "count must be between 1 and {MAX_CLONE_COUNT}"
+            ))]));
+        }
+        self.generic_tool_run(ToolArgumentValues::CloneInstance(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Copies a part or model into a grid of cells along x, y and z with a spacing per axis, for rows of pillars, floors of windows or stacks of shelving. The original fills the first cell. Copies can turn by a further rotation each. Returns the paths of the copies.",
+        output_schema = tool_results::output_schema::<tool_results::ArrayDuplicateResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn array_duplicate(
+        &self,
+        Parameters(args): Parameters<ArrayDuplicate>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let counts = [args.count_x, args.count_y, args.count_z].map(|count| count.unwrap_or(1));
+        let cells: u64 = counts.iter().map(|&count| u64::from(count)).product();
+        let message = if counts.contains(&0) {
+            Some("count_x, count_y and count_z must be at least 1".to_string())
+        } else if cells < 2 {
+            Some("The grid needs more than one cell, set count_x, count_y or count_z".to_string())
+        } else if cells - 1 > u64::from(MAX_CLONE_COUNT) {
+            Some(format!(
+                "The grid has {cells} cells, at most {MAX_CLONE_COUNT} copies can be made at once"
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::ArrayDuplicate(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Places copies of a part or model at even spacing along a path through waypoints, straight or smoothed, for fences, street lights and railings. Copies can face along the path and be jittered sideways and in rotation. Returns the paths of the copies.",
+        output_schema = tool_results::output_schema::<tool_results::PlaceAlongPathResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn place_along_path(
+        &self,
+        Parameters(args): Parameters<PlaceAlongPath>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        // Straight lines between the waypoints, a smoothed path is a little longer
+        let length: f64 = args
+            .waypoints
+            .windows(2)
+            .map(|pair| {
+                let (from, to) = (&pair[0], &pair[1]);
+                ((to.x - from.x).powi(2) + (to.y - from.y).powi(2) + (to.z - from.z).powi(2)).sqrt()
+            })
+            .sum();
+        let message = if args.waypoints.len() < 2 {
+            Some("A path needs at least two waypoints".to_string())
+        } else if args.spacing <= 0.0 {
+            Some("spacing must be more than 0".to_string())
+        } else if length / args.spacing >= f64::from(MAX_CLONE_COUNT) {
+            Some(format!(
+                "The path is {length:.0} studs long, which would take more than {MAX_CLONE_COUNT} copies {} studs apart",
+                args.spacing
+            ))
+        } else if args.jitter.is_some_and(|jitter| jitter < 0.0)
+            || args.rotation_jitter.is_some_and(|jitter| jitter < 0.0)
+        {
+            Some("jitter and rotation_jitter can't be negative".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::PlaceAlongPath(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Moves instances to a new parent, e.g. to group models scattered by batch inserts into folders. Returns the old and new path of each instance.",
+        output_schema = tool_results::output_schema::<tool_results::MoveInstancesResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn move_instances(
+        &self,
+        Parameters(args): Parameters<MoveInstances>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::MoveInstances(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Lines up parts and models by path, pattern or the Studio selection on the x, y or z axis by the min, center or max of their bounding boxes, or distributes them with even gaps, like Studio's Align tool. Returns where each instance's pivot ended up.",
+        output_schema = tool_results::output_schema::<tool_results::AlignInstancesResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn align_instances(
+        &self,
+        Parameters(args): Parameters<AlignInstances>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if !ALIGN_MODES.contains(&args.mode.as_str()) {
+            Some(format!("mode must be one of {}", ALIGN_MODES.join(", ")))
+        } else if args.axes.is_empty()
+            || args.axes.iter().any(|axis| !AXES.contains(&axis.as_str()))
+        {
+            Some("axes must list one or more of x, y and z".to_string())
+        } else if args.target.is_some() && args.mode == "distribute" {
+            Some("target only applies to min, center and max".to_string())
+        } else if args.spacing.is_some() && args.mode != "distribute" {
+            Some("spacing only applies to distribute".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::AlignInstances(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Casts a ray through the workspace from an origin along a direction and returns what it hits first: the instance's path, the hit position, the surface normal, the material and the distance. Parts can be ignored or singled out by path, and the ray can take a collision group. Use it to find the ground or a wall before placing something.",
+        output_schema = tool_results::output_schema::<tool_results::RaycastResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn raycast(
+        &self,
+        Parameters(args): Parameters<Raycast>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let Position { x, y, z } = args.direction;
+        let length = (x * x + y * y + z * z).sqrt();
+        let distance = args.distance.unwrap_or(length);
+        let message = if length == 0.0 {
+            Some("direction can't be zero".to_string())
+        } else if distance <= 0.0 || distance > MAX_RAY_DISTANCE {
+            Some(format!(
+                "distance must be above 0 and at most {MAX_RAY_DISTANCE}"
+            ))
+        } else if args
+            .filter_type
+            .as_deref()
+            .is_some_and(|filter_type| !RAYCAST_FILTER_TYPES.contains(&filter_type))
+        {
+            Some(format!(
+                "filter_type must be one of {}",
+                RAYCAST_FILTER_TYPES.join(", ")
+            ))
+        } else if args.filter_type.as_deref() == Some("include")
+            && args.filter.as_ref().is_none_or(Vec::is_empty)
+        {
+            Some("filter_type 'include' needs filter paths to hit".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::Raycast(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Lists the parts whose bounding boxes overlap a box or a sphere, closest to its center first, with their path, class, the model they belong to, position and size. Filters by class, name and tag. Use it to see what is already at a spot before building there.",
+        output_schema = tool_results::output_schema::<tool_results::QueryRegionResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn query_region(
+        &self,
+        Parameters(args): Parameters<QueryRegion>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let sphere = args.center.is_some() || args.radius.is_some();
+        let inverted = args
+            .region
+            .as_ref()
+            .is_some_and(|Region { min, max }| min.x >= max.x || min.y >= max.y || min.z >= max.z);
+        let message = if args.region.is_some() == sphere {
+            Some("pass either region or center and radius".to_string())
+        } else if args.center.is_some() != args.radius.is_some() {
+            Some("a sphere needs both center and radius".to_string())
+        } else if args.radius.is_some_and(|radius| radius <= 0.0) {
+            Some("radius must be above 0".to_string())
+        } else if inverted {
+            Some("region.min must be below region.max on every axis".to_string())
+        } else if args
+            .max_results
+            .is_some_and(|max_results| !(1..=MAX_REGION_PARTS).contains(&max_results))
+        {
+            Some(format!(
+                "max_results must be between 1 and {MAX_REGION_PARTS}"
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::QueryRegion(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Drops parts and models by path, pattern or the Studio selection onto the terrain or surface below them, moving each up or down until the bottom of its bounding box rests on the highest ground under its footprint. Can tilt them to the slope. Use it to fix floating or buried models after batch inserts. Returns where each one ended up and what it rests on.",
+        output_schema = tool_results::output_schema::<tool_results::SnapToGroundResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn snap_to_ground(
+        &self,
+        Parameters(args): Parameters<SnapToGround>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SnapToGround(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Sets the material, color, transparency and reflectance of every part matched by path, pattern, tag or the Studio selection, including the parts inside matched models, e.g. to restyle an inserted free model. Returns how many parts matched and how many changed.",
+        output_schema = tool_results::output_schema::<tool_results::PaintInstancesResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn paint_instances(
+        &self,
+        Parameters(args): Parameters<PaintInstances>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args.material.is_none()
+            && args.color.is_none()
+            && args.transparency.is_none()
+            && args.reflectance.is_none()
+        {
+            Some("Pass a material, color, transparency or reflectance to paint".to_string())
+        } else if args
+            .transparency
+            .is_some_and(|transparency| !(0.0..=1.0).contains(&transparency))
+        {
+            Some("transparency must be between 0 and 1".to_string())
+        } else if args
+            .reflectance
+            .is_some_and(|reflectance| !(0.0..=1.0).contains(&reflectance))
+        {
+            Some("reflectance must be between 0 and 1".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::PaintInstances(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Creates a PointLight, SpotLight or SurfaceLight in each part matched by path, pattern, tag or the Studio selection, in a named part of each matched model such as the bulb of every lamp post, or at world positions, with brightness, range, color, angle, face and shadows. Returns the paths of the lights.",
+        output_schema = tool_results::output_schema::<tool_results::PlaceLightsResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn place_lights(
+        &self,
+        Parameters(args): Parameters<PlaceLights>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let kind = args.kind.as_deref().unwrap_or("point");
+        let message = if !LIGHT_KINDS.contains(&kind) {
+            Some(format!("kind must be one of {}", LIGHT_KINDS.join(", ")))
+        } else if kind == "surface" && args.positions.is_some() {
+            Some("Surface lights shine from a face of a part, pass parts instead".to_string())
+        } else if kind == "surface" && args.offset.is_some() {
+            Some("offset doesn't apply to surface lights".to_string())
+        } else if kind == "point" && (args.angle.is_some() || args.face.is_some()) {
+            Some("angle and face only apply to spot and surface lights".to_string())
+        } else if args
+            .face
+            .as_deref()
+            .is_some_and(|face| face == "All" || !FACES.contains(&face))
+        {
+            Some("face must be one of Front, Back, Top, Bottom, Left, Right".to_string())
+        } else if args.brightness.is_some_and(|brightness| brightness < 0.0) {
+            Some("brightness can't be negative".to_string())
+        } else if args
+            .range
+            .is_some_and(|range| !(0.0..=60.0).contains(&range))
+        {
+            Some("range must be between 0 and 60".to_string())
+        } else if args
+            .angle
+            .is_some_and(|angle| !(0.0..=180.0).contains(&angle))
+        {
+            Some("angle must be between 0 and 180".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::PlaceLights(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Creates a ParticleEmitter in a part or attachment from a preset (fire, smoke, sparkles or rain) and typed settings: texture, rate, lifetime, speed, spread, acceleration, size and transparency keypoints over each particle's life and a color sequence. Returns the path of the emitter.",
+        output_schema = tool_results::output_schema::<tool_results::CreateParticleEmitterResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn create_particle_emitter(
+        &self,
+        Parameters(mut args): Parameters<CreateParticleEmitter>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if let Some(message) = args.problem() {
+            Some(message)
+        } else if let Some(preset) = &args.preset {
+            match particle_presets::find(preset) {
+                Ok(preset) => {
+                    args.texture = Some(preset.texture.to_string());
+                    args.apply_preset(preset);
+                    None
+                }
+                Err(message) => Some(message),
+            }
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        if let Some(texture_id) = args.texture_id {
+            args.texture = Some(format!("rbxassetid://{texture_id}"));
+        }
+        self.generic_tool_run(ToolArgumentValues::CreateParticleEmitter(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Sets up a weather system as one Model: rain and snow particle rigs over an area, thunder with lightning flashes for storms, fog that thickens with the weather and a driver Script that switches between clear, rain, snow and storm when the Model's Weather attribute changes, optionally on a random cycle. Returns the paths of the Model, the script and the thunder sound.",
+        output_schema = tool_results::output_schema::<tool_results::SetupWeatherResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn setup_weather(
+        &self,
+        Parameters(mut args): Parameters<SetupWeather>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let unknown_weather = args
+            .weather
+            .iter()
+            .chain(args.cycle_weathers.iter().flatten())
+            .find(|weather| !weather::WEATHERS.contains(&weather.as_str()));
+        let message = if let Some(weather) = unknown_weather {
+            Some(format!(
+                "There is no {weather} weather, use one of {}",
+                weather::WEATHERS.join(", ")
+            ))
+        } else if args
+            .intensity
+            .is_some_and(|intensity| !(0.0..=1.0).contains(&intensity))
+        {
+            Some("intensity must be between 0 and 1".to_string())
+        } else if args
+            .area
+            .is_some_and(|area| !(16.0..=2048.0).contains(&area))
+        {
+            Some("area must be between 16 and 2048 studs".to_string())
+        } else if args.height.is_some_and(|height| height <= 0.0) {
+            Some("height must be more than 0".to_string())
+        } else if args.transition_seconds.is_some_and(|seconds| seconds < 0.0)
+            || args.cycle_minutes.is_some_and(|minutes| minutes < 0.0)
+        {
+            Some("transition_seconds and cycle_minutes can't be negative".to_string())
+        } else if args
+            .thunder_interval
+            .as_ref()
+            .is_some_and(|interval| interval.min < 1.0 || interval.min > interval.max)
+        {
+            Some("thunder_interval.min must be at least 1 second and at most max".to_string())
+        } else if args.cycle_weathers.as_ref().is_some_and(Vec::is_empty) {
+            Some("cycle_weathers needs at least one weather".to_string())
+        } else if args.thunder == Some(false) && args.thunder_sound_id.is_some() {
+            Some("thunder_sound_id doesn't apply with thunder: false".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        // Plugins have no way to search audio, so the server picks the thunder. A storm
+        // without a sound still flashes, so a failed search doesn't stop the setup.
+        if args.thunder != Some(false) && args.thunder_sound_id.is_none() {
+            match marketplace::search_audio("thunder").await {
+                Ok(found) => args.thunder_sound_id = found.map(|(asset_id, _)| asset_id),
+                Err(e) => tracing::warn!("Could not search the Creator Store for thunder: {e:#}"),
+            }
+        }
+        args.driver_source = Some(weather::DRIVER_SOURCE.to_string());
+        self.generic_tool_run(ToolArgumentValues::SetupWeather(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Inserts a day/night cycle Script that moves the clock through the day at a set speed, tints the outdoor light through dawn, day, dusk and night colors and turns the lights in instances with a tag, such as streetlights, on at night. Settings are attributes on the script, so they can be tuned in Studio later. Returns the path of the script.",
+        output_schema = tool_results::output_schema::<tool_results::SetupDayNightCycleResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn setup_day_night_cycle(
+        &self,
+        Parameters(mut args): Parameters<SetupDayNightCycle>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let hours = [
+            ("start_time", args.start_time),
+            ("lights_on_at", args.lights_on_at),
+            ("lights_off_at", args.lights_off_at),
+        ];
+        let message = if args.day_minutes.is_some_and(|minutes| minutes <= 0.0) {
+            Some("day_minutes must be more than 0".to_string())
+        } else if let Some((name, _)) = hours
+            .iter()
+            .find(|(_, hour)| hour.is_some_and(|hour| !(0.0..=24.0).contains(&hour)))
+        {
+            Some(format!("{name} must be an hour from 0 to 24"))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        args.source = Some(day_night::CYCLE_SOURCE.to_string());
+        self.generic_tool_run(ToolArgumentValues::SetupDayNightCycle(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Sets SoundService's ambient reverb, distance factor, doppler and rolloff scales, and places ambient sound zones: a box per zone with a looping sound by asset ID or Creator Store search, faded in while the player is inside by a generated LocalScript. Returns the previous SoundService settings, the zone parts and the script.",
+        output_schema = tool_results::output_schema::<tool_results::SetupAmbientSoundResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn setup_ambient_sound(
+        &self,
+        Parameters(mut args): Parameters<SetupAmbientSound>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let zones = args.zones.as_deref().unwrap_or_default();
+        let message = if args.ambient_reverb.is_none()
+            && args.distance_factor.is_none()
+            && args.doppler_scale.is_none()
+            && args.rolloff_scale.is_none()
+            && zones.is_empty()
+        {
+            Some("Pass SoundService settings or zones to set up".to_string())
+        } else if args
+            .ambient_reverb
+            .as_deref()
+            .is_some_and(|reverb| !ambient_zones::REVERB_TYPES.contains(&reverb))
+        {
+            Some(format!(
+                "ambient_reverb must be one of {}",
+                ambient_zones::REVERB_TYPES.join(", ")
+            ))
+        } else if args.distance_factor.is_some_and(|factor| factor <= 0.0) {
+            Some("distance_factor must be more than 0".to_string())
+        } else if args.doppler_scale.is_some_and(|scale| scale < 0.0)
+            || args.rolloff_scale.is_some_and(|scale| scale < 0.0)
+            || args.fade_seconds.is_some_and(|seconds| seconds < 0.0)
+        {
+            Some("doppler_scale, rolloff_scale and fade_seconds can't be negative".to_string())
+        } else {
+            zones.iter().find_map(AmbientZone::problem)
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        // Plugins have no way to search audio, so the server picks each zone's sound
+        for zone in args.zones.iter_mut().flatten() {
+            let Some(query) = zone.query.take() else {
+                continue;
+            };
+            match marketplace::search_audio(&query).await {
+                Ok(Some((asset_id, _))) => zone.asset_id = Some(asset_id),
+                Ok(None) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Zone {}: no audio found for {query:?}",
+                        zone.name
+                    ))]))
+                }
+                Err(e) => {
+                    let message = format!(
+                        "Zone {}: could not search the Creator Store for audio, pass an asset_id instead: {e:#}",
+                        zone.name
+                    );
+                    return Ok(CallToolResult::error(vec![Content::text(message)]));
+                }
+            }
+        }
+        if args.zones.as_ref().is_some_and(|zones| !zones.is_empty()) {
+            args.trigger_source = Some(ambient_zones::TRIGGER_SOURCE.to_string());
+        }
+        self.generic_tool_run(ToolArgumentValues::SetupAmbientSound(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Sets Workspace physics: gravity, air density, global wind, the height fallen parts are destroyed below, the physics stepping method, whether touches use collision groups, and animation retargeting and IKControl constraint support. Either every property passed is set or none is. Returns the previous values, which undo the change when passed back.",
+        output_schema = tool_results::output_schema::<tool_results::SetWorkspacePhysicsResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn set_workspace_physics(
+        &self,
+        Parameters(args): Parameters<SetWorkspacePhysics>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let modes = [
+            (
+                "physics_stepping_method",
+                &args.physics_stepping_method,
+                PHYSICS_STEPPING_METHODS,
+            ),
+            ("retargeting", &args.retargeting, FEATURE_MODES),
+            (
+                "ik_control_constraint_support",
+                &args.ik_control_constraint_support,
+                FEATURE_MODES,
+            ),
+        ];
+        let message = if args.gravity.is_none()
+            && args.air_density.is_none()
+            && args.global_wind.is_none()
+            && args.fallen_parts_destroy_height.is_none()
+            && args.touches_use_collision_groups.is_none()
+            && modes.iter().all(|(_, value, _)| value.is_none())
+        {
+            Some("Pass the physics properties to change".to_string())
+        } else if args.gravity.is_some_and(|gravity| gravity < 0.0)
+            || args.air_density.is_some_and(|density| density < 0.0)
+        {
+            Some("gravity and air_density can't be negative".to_string())
+        } else if args
+            .fallen_parts_destroy_height
+            .is_some_and(|height| height < MIN_FALLEN_PARTS_DESTROY_HEIGHT)
+        {
+            Some(format!(
+                "fallen_parts_destroy_height must be at least {MIN_FALLEN_PARTS_DESTROY_HEIGHT}"
+            ))
+        } else {
+            modes.iter().find_map(|(name, value, allowed)| {
+                value
+                    .as_deref()
+                    .is_some_and(|value| !allowed.contains(&value))
+                    .then(|| format!("{name} must be one of {}", allowed.join(", ")))
+            })
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::SetWorkspacePhysics(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Renames instances matched by path, wildcard pattern and class using a template with the old name and a sequence number, e.g. to turn 'Model', 'Model (1)', 'Model (2)' into 'Tree1', 'Tree2', 'Tree3'. Returns each old and new name.",
+        output_schema = tool_results::output_schema::<tool_results::RenameInstancesResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn rename_instances(
+        &self,
+        Parameters(args): Parameters<RenameInstances>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if args.paths.as_ref().is_none_or(Vec::is_empty) && args.pattern.is_none() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Pass the paths or a pattern of the instances to rename",
+            )]));
+        }
+        self.generic_tool_run(ToolArgumentValues::RenameInstances(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Finds instances by class, name, attribute values, tag and region, with a result limit and sort order. Returns the path, name, class and position of each match and how many matched in total. Use this to find what to edit before changing it.",
+        output_schema = tool_results::output_schema::<tool_results::SearchInstancesResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn search_instances(
+        &self,
+        Parameters(args): Parameters<SearchInstances>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SearchInstances(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Gets the instances selected in Studio with their path, name and class. Use this when the user refers to what they have selected.",
+        output_schema = tool_results::output_schema::<tool_results::GetSelectionResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_selection(
+        &self,
+        Parameters(args): Parameters<GetSelection>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetSelection(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Selects instances in Studio, replacing the current selection. Use this to point the user at what was built or found.",
+        output_schema = tool_results::output_schema::<tool_results::SetSelectionResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn set_selection(
+        &self,
+        Parameters(args): Parameters<SetSelection>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::SetSelection(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Marks a named checkpoint in Studio's undo history. Every tool call after it is one undo step, so undo_to_checkpoint can take back a whole multi-step session at once. Make one before a series of edits the user may want to reject.",
+        output_schema = tool_results::output_schema::<tool_results::CreateCheckpointResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn create_checkpoint(
+        &self,
+        Parameters(args): Parameters<CreateCheckpoint>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if args.name.trim().is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Checkpoint names can't be empty",
+            )]));
+        }
+        self.generic_tool_run(ToolArgumentValues::CreateCheckpoint(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Undoes everything done in Studio since a checkpoint made with create_checkpoint, as if the user pressed undo once per tool call. Stops without changing anything if it meets a change the user made by hand, unless include_user_changes is set. The undone steps stay on Studio's redo stack.",
+        output_schema = tool_results::output_schema::<tool_results::UndoToCheckpointResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn undo_to_checkpoint(
+        &self,
+        Parameters(args): Parameters<UndoToCheckpoint>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::UndoToCheckpoint(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Adds, removes or lists CollectionService tags on instances matched by path or pattern. Gameplay scripts find objects through their tags, e.g. CollectionService:GetTagged('KillBrick'). Returns each instance's tags after the change.",
+        output_schema = tool_results::output_schema::<tool_results::ManageTagsResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn manage_tags(
+        &self,
+        Parameters(args): Parameters<ManageTags>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !matches!(args.action.as_str(), "add" | "remove" | "list") {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Unknown action '{}', must be add, remove or list",
+                args.action
+            ))]));
+        }
+        if args.paths.as_ref().is_none_or(Vec::is_empty) && args.pattern.is_none() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Pass the paths or a pattern of the instances to tag",
+            )]));
+        }
+        self.generic_tool_run(ToolArgumentValues::ManageTags(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Gets, sets or removes attributes on instances matched by path or pattern. Attributes hold the values gameplay scripts read with GetAttribute, like a coin's worth or a door's key. Returns each instance's attributes after the change.",
+        output_schema = tool_results::output_schema::<tool_results::ManageAttributesResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn manage_attributes(
+        &self,
+        Parameters(args): Parameters<ManageAttributes>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if args.paths.as_ref().is_none_or(Vec::is_empty) && args.pattern.is_none() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Pass the paths or a pattern of the instances to change",
+            )]));
+        }
+        let missing = match args.action.as_str() {
+            "get" => None,
+            "set" => args
+                .values
+                .as_ref()
+                .is_none_or(JsonObject::is_empty)
+                .then_some("Pass the values to set"),
+            "remove" => args
+                .names
+                .as_ref()
+                .is_none_or(Vec::is_empty)
+                .then_some("Pass the names of the attributes to remove"),
+            action => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Unknown action '{action}', must be get, set or remove"
+                ))]));
+            }
+        };
+        if let Some(message) = missing {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::ManageAttributes(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Returns the source of a Script, LocalScript or ModuleScript, optionally only a range of lines. Includes the line count so large scripts can be read in parts, and a checksum to pass to patch_script. Use this rather than printing Source from run_code, which mangles long scripts.",
+        output_schema = tool_results::output_schema::<tool_results::ReadScriptSourceResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn read_script_source(
+        &self,
+        Parameters(args): Parameters<ReadScriptSource>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if let (Some(start), Some(end)) = (args.start_line, args.end_line) {
+            if start > end {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "start_line {start} is after end_line {end}"
+                ))]));
+            }
+        }
+        self.generic_tool_run(ToolArgumentValues::ReadScriptSource(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Replaces the whole source of a Script, LocalScript or ModuleScript, or creates the script when create is set. Returns a checksum of the source now in the script. Use read_script_source first to see the current code.",
+        output_schema = tool_results::output_schema::<tool_results::WriteScriptSourceResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn write_script_source(
+        &self,
+        Parameters(args): Parameters<WriteScriptSource>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if args.source.len() > MAX_SCRIPT_BYTES {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "The source is {} bytes, scripts can be at most {MAX_SCRIPT_BYTES}. Split it into ModuleScripts",
+                args.source.len()
+            ))]));
+        }
+        if let Some(class_name) = &args.class_name {
+            if !SCRIPT_CLASSES.contains(&class_name.as_str()) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{class_name} isn't a script class, use one of {}",
+                    SCRIPT_CLASSES.join(", ")
+                ))]));
+            }
+        }
+        self.generic_tool_run(ToolArgumentValues::WriteScriptSource(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Edits part of a Script, LocalScript or ModuleScript by replacing line ranges or applying a unified diff, instead of rewriting the whole source. Pass the checksum from read_script_source as base_checksum so edits based on an outdated read are refused. Returns the new checksum for the next edit.",
+        output_schema = tool_results::output_schema::<tool_results::PatchScriptResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
+    )]
+    async fn patch_script(
+        &self,
+        Parameters(mut args): Parameters<PatchScript>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let hunks = match (&args.edits, &args.diff) {
+            (Some(edits), None) => script_patch::from_edits(edits),
+            (None, Some(diff)) => script_patch::parse_diff(diff),
+            _ => Err("Pass either edits or a diff".to_string()),
+        };
+        match hunks {
+            Ok(hunks) => args.hunks = Some(hunks),
+            Err(message) => return Ok(CallToolResult::error(vec![Content::text(message)])),
+        }
+        self.generic_tool_run(ToolArgumentValues::PatchScript(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Lists the scripts in the place or under a path with their class, enabled state, line count and checksum. Use this to map out the code before reading or editing it.",
+        output_schema = tool_results::output_schema::<tool_results::ListScriptsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn list_scripts(
+        &self,
+        Parameters(args): Parameters<ListScripts>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::ListScripts(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Creates a Script, LocalScript or ModuleScript with the given source or from a template like a module skeleton or a RemoteEvent handler. Returns the path of the new script and a checksum for patch_script.",
+        output_schema = tool_results::output_schema::<tool_results::CreateScriptResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn create_script(
+        &self,
+        Parameters(mut args): Parameters<CreateScript>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if let Some(template) = &args.template {
+            if args.source.is_some() {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "Pass either a source or a template",
+                )]));
+            }
+            let template = match script_templates::find(template) {
+                Ok(template) => template,
+                Err(message) => return Ok(CallToolResult::error(vec![Content::text(message)])),
+            };
+            args.source = Some(template.render(&args.name));
+            if args.class_name.is_none() {
+                args.class_name = Some(template.class_name.to_string());
+            }
+        }
+        let source_len = args.source.as_ref().map_or(0, String::len);
+        if source_len > MAX_SCRIPT_BYTES {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "The source is {source_len} bytes, scripts can be at most {MAX_SCRIPT_BYTES}. Split it into ModuleScripts"
+            ))]));
+        }
+        if let Some(class_name) = &args.class_name {
+            if !SCRIPT_CLASSES.contains(&class_name.as_str()) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{class_name} isn't a script class, use one of {}",
+                    SCRIPT_CLASSES.join(", ")
+                ))]));
+            }
+        }
+        self.generic_tool_run(ToolArgumentValues::CreateScript(args), context)
+            .await
+    }
+
+    #[tool(
+        description = "Searches the source of every script, or those under a path, for text or a regular expression. Returns the path, line and column of each match with the lines around it. Use this to find where something is defined or used before editing.",
+        output_schema = tool_results::output_schema::<tool_results::SearchCodeResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn search_code(
+        &self,
+        Parameters(args): Parameters<SearchCode>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let pattern = if args.regex.unwrap_or(false) {
+            args.query.clone()
+        } else {
+            regex::escape(&args.query)
+        };
+        let pattern = match RegexBuilder::new(&pattern)
+            .case_insensitive(!args.case_sensitive.unwrap_or(false))
+            .build()
+        {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Invalid regular expression: {e}"
+                ))]))
+            }
+        };
+        let scripts = match self.script_sources(args.root, context).await? {
+            Ok(scripts) => scripts,
+            Err(failed) => return Ok(failed),
+        };
+        let result = code_search::search(
+            &scripts,
+            &pattern,
+            args.context_lines.unwrap_or(2) as usize,
+            args.max_results.unwrap_or(DEFAULT_CODE_MATCHES) as usize,
+        );
+        let reply = serde_json::to_string(&result)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("search_code", reply).await)
+    }
+
+    #[tool(
+        description = "Lints the scripts in the place, or those under a path, with selene and returns its errors and warnings grouped by script path with line and column. Use this to check generated code for mistakes like undefined globals, unused variables or shadowing.",
+        output_schema = tool_results::output_schema::<tool_results::LintScriptsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn lint_scripts(
+        &self,
+        Parameters(args): Parameters<LintScripts>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let scripts = match self.script_sources(args.root, context).await? {
+            Ok(scripts) => scripts,
+            Err(failed) => return Ok(failed),
+        };
+        let selene = &self.config.selene_path;
+        let result = match lint::lint(selene, &self.config.selene_std, &scripts).await {
+            Ok(result) => result,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+        };
+        let reply = serde_json::to_string(&result)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("lint_scripts", reply).await)
+    }
+
+    #[tool(
+        description = "Formats the scripts in the place, or those under a path, with StyLua and writes back the ones whose source changed. Scripts edited in Studio while formatting ran are left alone. Set dry_run to only list the scripts that would change. Scripts with syntax errors are reported as failed.",
+        output_schema = tool_results::output_schema::<tool_results::FormatScriptsResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn format_scripts(
+        &self,
+        Parameters(args): Parameters<FormatScripts>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let dry_run = args.dry_run.unwrap_or(false);
+        let scripts = match self.script_sources(args.root, context.clone()).await? {
+            Ok(scripts) => scripts,
+            Err(failed) => return Ok(failed),
+        };
+        let mut writes = Vec::new();
+        let mut failed = Vec::new();
+        for script in &scripts {
+            match format::format(&self.config.stylua_path, &script.source).await {
+                Ok(Ok(source)) if source != script.source => writes.push(ScriptWrite {
+                    path: script.path.clone(),
+                    source,
+                    base_checksum: script.checksum.clone(),
+                    chunks: None,
+                }),
+                Ok(Ok(_)) => {}
+                Ok(Err(error)) => failed.push(tool_results::FormatFailure {
+                    path: script.path.clone(),
+                    error,
+                }),
+                Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+            }
+        }
+        let written = if dry_run {
+            WrittenScripts {
+                written: writes.into_iter().map(|write| write.path).collect(),
+                skipped: Vec::new(),
+            }
+        } else {
+            match self.write_script_sources(writes, context).await? {
+                Ok(written) => written,
+                Err(failed) => return Ok(failed),
+            }
+        };
+        let result = tool_results::FormatScriptsResult {
+            success: failed.is_empty() && written.skipped.is_empty(),
+            dry_run,
+            scripts_checked: scripts.len(),
+            reformatted: written.written,
+            failed,
+            skipped: written.skipped,
+        };
+        let reply = serde_json::to_string(&result)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("format_scripts", reply).await)
+    }
+
+    #[tool(
+        description = "Reads the require calls of every script and returns a dependency graph: the modules each script requires, keyed by script path. Also lists require cycles, requires of paths with no ModuleScript, and requires whose target is only known at run time. Use this to understand how code is structured before moving or renaming modules.",
+        output_schema = tool_results::output_schema::<tool_results::ModuleDependenciesResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_module_dependencies(
+        &self,
+        Parameters(_): Parameters<GetModuleDependencies>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let scripts = match self.script_sources(None, context).await? {
+            Ok(scripts) => scripts,
+            Err(failed) => return Ok(failed),
+        };
+        let result = module_graph::graph(&scripts);
+        let reply = serde_json::to_string(&result)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("get_module_dependencies", reply).await)
+    }
+
+    #[tool(
+        description = "Renames a function, variable or module name in every script, or those under a path, and writes back the scripts that changed. Only whole names outside strings and comments are replaced, including in the {expressions} of interpolated strings, but scopes aren't tracked, so fields with the same name on other tables are renamed too. Use dry_run first to check the changed lines. Scripts edited in Studio while renaming ran are left alone.",
+        output_schema = tool_results::output_schema::<tool_results::RenameSymbolResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn rename_symbol(
+        &self,
+        Parameters(args): Parameters<RenameSymbol>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        for name in [&args.old_name, &args.new_name] {
+            if !luau_tokens::is_identifier(name) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{name:?} isn't a valid Luau name"
+                ))]));
+            }
+        }
+        if args.old_name == args.new_name {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "old_name and new_name are the same",
+            )]));
+        }
+        let dry_run = args.dry_run.unwrap_or(false);
+        let scripts = match self.script_sources(args.root, context.clone()).await? {
+            Ok(scripts) => scripts,
+            Err(failed) => return Ok(failed),
+        };
+        let mut writes = Vec::new();
+        let mut renamed_scripts = Vec::new();
+        for script in &scripts {
+            let Some(renamed) = rename::rename(&script.source, &args.old_name, &args.new_name)
+            else {
+                continue;
+            };
+            renamed_scripts.push(tool_results::RenamedScript {
+                path: script.path.clone(),
+                changes: renamed.count,
+                lines: dry_run.then_some(renamed.lines),
+            });
+            writes.push(ScriptWrite {
+                path: script.path.clone(),
+                source: renamed.source,
+                base_checksum: script.checksum.clone(),
+                chunks: None,
+            });
+        }
+        let skipped = if dry_run {
+            Vec::new()
+        } else {
+            match self.write_script_sources(writes, context).await? {
+                Ok(written) => written.skipped,
+                Err(failed) => return Ok(failed),
+            }
+        };
+        let result = tool_results::RenameSymbolResult {
+            success: skipped.is_empty(),
+            dry_run,
+            scripts_checked: scripts.len(),
+            total_changes: renamed_scripts.iter().map(|script| script.changes).sum(),
+            scripts: renamed_scripts,
+            skipped,
+        };
+        let reply = serde_json::to_string(&result)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("rename_symbol", reply).await)
+    }
+
+    #[tool(
+        description = "Builds terrain from a heightmap image, a PNG or EXR file on the server's machine or a base64 encoded image. The image is resampled to one terrain column per resolution studs over the region, with black at the bottom of the region and white at the top. Use this for real-world elevation data or hand-painted maps that noise can't reproduce.",
+        output_schema = tool_results::output_schema::<tool_results::ImportHeightmapResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn import_heightmap(
+        &self,
+        Parameters(args): Parameters<ImportHeightmap>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let image = match (&args.path, &args.image_base64) {
+            (Some(path), None) => tokio::fs::read(path)
+                .await
+                .map_err(|e| format!("Couldn't read {path}: {e}")),
+            (None, Some(encoded)) => {
+                // Data URLs are accepted as they are
+                let data = encoded
+                    .rsplit_once("base64,")
+                    .map_or(encoded.as_str(), |(_, data)| data);
+                BASE64_STANDARD
+                    .decode(data.trim())
+                    .map_err(|e| format!("image_base64 isn't valid base64: {e}"))
+            }
+            _ => Err("Pass either path or image_base64".to_string()),
+        };
+        let image = match image {
+            Ok(image) => image,
+            Err(message) => return Ok(CallToolResult::error(vec![Content::text(message)])),
+        };
+        let resolution = args.resolution.unwrap_or(DEFAULT_HEIGHTMAP_RESOLUTION);
+        let (min, max) = (&args.region.min, &args.region.max);
+        let finite = [min.x, min.y, min.z, max.x, max.y, max.z]
+            .iter()
+            .all(|value| value.is_finite());
+        if !finite || max.x < min.x || max.y <= min.y || max.z < min.z {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "The region's max corner must be above its min corner",
+            )]));
+        }
+        // Also turns away NaN, which no comparison holds for
+        if !(MIN_HEIGHTMAP_RESOLUTION..=f64::MAX).contains(&resolution) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "resolution must be a number of studs of at least {MIN_HEIGHTMAP_RESOLUTION}"
+            ))]));
+        }
+        // Counted as floats so a huge region can't overflow before it's turned away
+        let columns = ((max.x - min.x) / resolution).floor() + 1.0;
+        let rows = ((max.z - min.z) / resolution).floor() + 1.0;
+        if columns * rows > MAX_HEIGHTMAP_COLUMNS as f64 {
+            let count = |count: f64| {
+                if count < 1e9 {
+                    count.to_string()
+                } else {
+                    format!("{count:e}")
+                }
+            };
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "The region holds {} by {} columns at this resolution, the most one import fills is {MAX_HEIGHTMAP_COLUMNS}. Use a larger resolution or a smaller region",
+                count(columns),
+                count(rows)
+            ))]));
+        }
+        let (columns, rows) = (columns as usize, rows as usize);
+        let decoded = tokio::task::spawn_blocking(move || heightmap::decode(&image, columns, rows))
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let heights = match decoded {
+            Ok(heights) => heights,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+        };
+        let command = HeightmapColumns {
+            region: args.region,
+            material: args.material,
+            resolution,
+            columns,
+            rows,
+            heights: heightmap::encode(&heights),
+            water_level: args.water_level,
+            chunks: None,
+        };
+        self.generic_tool_run(ToolArgumentValues::ImportHeightmap(command), context)
+            .await
+    }
+
+    #[tool(
+        description = "Exports the terrain in a region to files on the server's machine: a 16-bit grayscale heightmap PNG of the top surface, black at the bottom of the region and white at the top, and a JSON material map of the top material of each 4 stud column. The heightmap can be edited and brought back with import_heightmap.",
+        output_schema = tool_results::output_schema::<tool_results::ExportTerrainResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn export_terrain(
+        &self,
+        Parameters(args): Parameters<ExportTerrain>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let path = std::path::PathBuf::from(&args.path);
+        let reply = match self
+            .run_command(ToolArgumentValues::ExportTerrain(args), context)
+            .await?
+        {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(failed),
+        };
+        // Anything else is the plugin explaining why it couldn't read the terrain
+        let Ok(terrain) = serde_json::from_str::<TerrainColumns>(&reply) else {
+            return Ok(CallToolResult::error(vec![Content::text(reply)]));
+        };
+        let written = tokio::task::spawn_blocking(move || terrain_export::write(&terrain, &path))
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let result = match written {
+            Ok(result) => result,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+        };
+        let reply = serde_json::to_string(&result)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("export_terrain", reply).await)
+    }
+
+    #[tool(
+        description = "Replaces one terrain material with another within a region, e.g. all Grass with Snow, keeping the terrain's shape. min_height and max_height limit it to a band of Y, such as snow only above a tree line. Replacing with Air removes that material.",
+        output_schema = tool_results::output_schema::<tool_results::ReplaceTerrainMaterialResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn replace_terrain_material(
+        &self,
+        Parameters(args): Parameters<ReplaceTerrainMaterial>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let problem = if args.from == args.to {
+            Some("from and to are the same material".to_string())
+        } else if args.from == "Air" {
+            Some("Air can't be replaced, use fill_terrain_region with replace_air".to_string())
+        } else {
+            match (args.min_height, args.max_height) {
+                (Some(min), Some(max)) if min > max => {
+                    Some(format!("min_height ({min}) is above max_height ({max})"))
+                }
+                _ => None,
+            }
+        };
+        if let Some(problem) = problem {
+            return Ok(CallToolResult::error(vec![Content::text(problem)]));
+        }
+        let commands = args
+            .region
+            .tiles(0.0)
+            .into_iter()
+            .map(|region| {
+                ToolArgumentValues::ReplaceTerrainMaterial(ReplaceTerrainMaterial {
+                    region,
+                    ..args.clone()
+                })
+            })
+            .collect();
+        self.run_tiled(commands, context).await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct GetModelBounds {
-    #[schemars(description = "Path to instance (e.g., 'Workspace.GrandCanyon.CanyonWalls')")]
-    path: String,
-}
+    #[tool(
+        description = "Carves a road or path through the terrain along a smooth curve through waypoints: clears the terrain above it, lays a flat bed of the path material under it, and can line it with guard rail parts. Use it to connect generated terrain to playable layouts.",
+        output_schema = tool_results::output_schema::<tool_results::CarvePathResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
+    )]
+    async fn carve_path(
+        &self,
+        Parameters(args): Parameters<CarvePath>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let problem = if args.waypoints.len() < 2 {
+            Some("A path needs at least two waypoints".to_string())
+        } else if args.width < 1.0 {
+            Some("width must be at least 1 stud".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Ok(CallToolResult::error(vec![Content::text(problem)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::CarvePath(args), context)
+            .await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct FindGaps {
-    #[schemars(description = "Path to first model/part")]
-    model_a: String,
-    #[schemars(description = "Path to second model/part")]
-    model_b: String,
-    #[schemars(description = "Maximum distance to consider a 'gap' (default: 2 studs)")]
-    threshold: Option<f64>,
-}
+    #[tool(
+        description = "Carves a river from one point to another: digs a rounded riverbed along a smooth curve, slopes the banks on both sides and fills the bed with water. The river follows waypoints if given, otherwise it meanders. Its surface runs at the Y of the points.",
+        output_schema = tool_results::output_schema::<tool_results::CarveRiverResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
+    )]
+    async fn carve_river(
+        &self,
+        Parameters(args): Parameters<CarveRiver>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let problem = if args.width.is_some_and(|width| width < 4.0) {
+            Some("width must be at least 4 studs".to_string())
+        } else if args.depth.is_some_and(|depth| depth < 2.0) {
+            Some("depth must be at least 2 studs".to_string())
+        } else if args.bank_width.is_some_and(|bank| bank < 0.0) {
+            Some("bank_width can't be negative".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Ok(CallToolResult::error(vec![Content::text(problem)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::CarveRiver(args), context)
+            .await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct CaptureViewport {
-    #[schemars(description = "Optional: Set camera position before capture")]
-    camera_position: Option<Position>,
-    #[schemars(description = "Optional: Set camera look-at target")]
-    camera_target: Option<Position>,
-    #[schemars(description = "Image format: 'png' or 'jpg' (informational only, actual format depends on manual screenshot)")]
-    format: Option<String>,
-}
+    #[tool(
+        description = "Saves the terrain in a region as a named stamp in a library kept by the server, to be placed again with place_terrain_stamp in this or any other place. Good for reusable hills, craters and cliffs. Stamps hold up to 262144 voxels of 4 studs.",
+        output_schema = tool_results::output_schema::<tool_results::SaveTerrainStampResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn save_terrain_stamp(
+        &self,
+        Parameters(args): Parameters<SaveTerrainStamp>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !terrain_stamps::is_valid_name(&args.name) {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Stamp names are up to 64 letters, digits, '-' and '_'",
+            )]));
+        }
+        let exists = terrain_stamps::exists(&args.name)
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))?;
+        if exists && !args.overwrite.unwrap_or(false) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "A stamp named '{}' already exists, pass overwrite to replace it",
+                args.name
+            ))]));
+        }
+        let name = args.name.clone();
+        let reply = match self
+            .run_command(ToolArgumentValues::SaveTerrainStamp(args), context)
+            .await?
+        {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(failed),
+        };
+        // Anything else is the plugin explaining why it couldn't read the terrain
+        let Ok(read) = serde_json::from_str::<StampRead>(&reply) else {
+            return Ok(CallToolResult::error(vec![Content::text(reply)]));
+        };
+        let path = match terrain_stamps::save(&name, &read.stamp).await {
+            Ok(path) => path,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+        };
+        let result = tool_results::SaveTerrainStampResult {
+            success: true,
+            name,
+            path: path.display().to_string(),
+            size: read.stamp.size,
+            solid_voxels: read.stamp.solid_voxels(),
+            materials: read.stamp.materials,
+            region: read.region,
+        };
+        let reply = serde_json::to_string(&result)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result("save_terrain_stamp", reply).await)
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct GetConsoleOutput {}
+    #[tool(
+        description = "Places a terrain stamp saved with save_terrain_stamp, with the centre of its bottom at a position and turned about Y by a multiple of 90 degrees. In add mode (the default) the stamp only adds terrain where it's fuller than what's there, so hills merge into the ground. Replace mode writes the stamp's empty space too, which is what craters need.",
+        output_schema = tool_results::output_schema::<tool_results::PlaceTerrainStampResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn place_terrain_stamp(
+        &self,
+        Parameters(args): Parameters<PlaceTerrainStamp>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let rotation = args.rotation.unwrap_or(0.0);
+        let mode = args.mode.unwrap_or_else(|| "add".to_string());
+        let problem = if rotation % 90.0 != 0.0 {
+            Some(format!(
+                "rotation must be a multiple of 90 degrees, not {rotation}"
+            ))
+        } else if mode != "add" && mode != "replace" {
+            Some(format!("mode must be add or replace, not '{mode}'"))
+        } else if !terrain_stamps::is_valid_name(&args.name) {
+            Some(format!("No terrain stamp named '{}'", args.name))
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Ok(CallToolResult::error(vec![Content::text(problem)]));
+        }
+        let stamp = match terrain_stamps::load(&args.name).await {
+            Ok(Some(stamp)) => stamp,
+            Ok(None) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "No terrain stamp named '{}', list_terrain_stamps shows the saved ones",
+                    args.name
+                ))]))
+            }
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(format!("{e:#}"))])),
+        };
+        let command = StampPlacement {
+            name: args.name,
+            position: args.position,
+            rotation: rotation.rem_euclid(360.0),
+            mode,
+            size: stamp.size,
+            materials: stamp.materials,
+            voxels: stamp.voxels,
+            chunks: None,
+        };
+        self.generic_tool_run(ToolArgumentValues::PlaceTerrainStamp(command), context)
+            .await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct GetStudioMode {}
+    #[tool(
+        description = "Lists the terrain stamps saved with save_terrain_stamp, with their size in 4 stud voxels and their materials.",
+        output_schema = tool_results::output_schema::<tool_results::ListTerrainStampsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn list_terrain_stamps(
+        &self,
+        Parameters(_): Parameters<ListTerrainStamps>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let stamps = terrain_stamps::list()
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))?;
+        let reply = serde_json::to_string(&tool_results::ListTerrainStampsResult { stamps })
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(tool_result("list_terrain_stamps", reply))
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct StartStopPlay {
-    #[schemars(description = "Mode to start or stop, must be start_play, stop, or run_server")]
-    mode: String,
-}
+    #[tool(
+        description = "Gets the bounding box of a Model or BasePart instance. Returns min, max, size, and center positions. Useful for calculating placement positions or determining object dimensions.",
+        output_schema = tool_results::output_schema::<tool_results::ModelBoundsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn get_model_bounds(
+        &self,
+        Parameters(args): Parameters<GetModelBounds>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::GetModelBounds(args), context)
+            .await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-struct RunScriptInPlayMode {
-    #[schemars(description = "Code to run")]
-    code: String,
-    #[schemars(description = "Timeout in seconds, defaults to 100 seconds")]
-    timeout: Option<u32>,
-    #[schemars(description = "Mode to run in, must be start_play or run_server")]
-    mode: String,
-}
+    #[tool(
+        description = "Finds gaps between two models or parts by raycasting from surface points of model_a toward model_b. Returns gap positions, distances, and nearest points on both models. Useful for detecting holes or misalignments between adjacent geometry. Limited to 50 gap results.",
+        output_schema = tool_results::output_schema::<tool_results::FindGapsResult>(),
+        annotations(read_only_hint = true)
+    )]
+    async fn find_gaps(
+        &self,
+        Parameters(args): Parameters<FindGaps>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.generic_tool_run(ToolArgumentValues::FindGaps(args), context)
+            .await
+    }
 
-#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
-enum ToolArgumentValues {
-    RunCode(RunCode),
-    InsertModel(InsertModel),
-    BatchInsertModels(BatchInsertModels),
-    BatchRunCode(BatchRunCode),
-    GenerateTerrain(GenerateTerrain),
-    FillTerrainRegion(FillTerrainRegion),
-    SculptTerrain(SculptTerrain),
-    ClearWorkspace(ClearWorkspace),
-    SaveScene(SaveScene),
-    LoadScene(LoadScene),
-    GetConsoleLogs(GetConsoleLogs),
-    GetWorkspaceStats(GetWorkspaceStats),
-    GetChildrenInfo(GetChildrenInfo),
-    GetModelBounds(GetModelBounds),
-    FindGaps(FindGaps),
-    CaptureViewport(CaptureViewport),
-    GetConsoleOutput(GetConsoleOutput),
-    StartStopPlay(StartStopPlay),
-    RunScriptInPlayMode(RunScriptInPlayMode),
-    GetStudioMode(GetStudioMode),
-}
-#[tool_router]
-impl RBXStudioServer {
-    pub fn new(state: PackedState) -> Self {
-        Self {
-            state,
-            tool_router: Self::tool_router(),
+    #[tool(
+        description = "Takes a screenshot of the Studio viewport and returns it as a PNG image along with the camera state. Optionally moves the camera first, to a position, to look at a target, or to frame a region. The camera stays where it was moved.",
+        output_schema = tool_results::output_schema::<tool_results::CaptureViewportResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn capture_viewport(
+        &self,
+        Parameters(args): Parameters<CaptureViewport>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let message = if args.format.as_deref().is_some_and(|format| format != "png") {
+            Some("format must be png".to_string())
+        } else if args.region.is_some() && args.camera_target.is_some() {
+            Some("Pass region or camera_target, not both".to_string())
+        } else if args
+            .region
+            .as_ref()
+            .is_some_and(|Region { min, max }| min.x > max.x || min.y > max.y || min.z > max.z)
+        {
+            Some("region.min can't be above region.max on any axis".to_string())
+        } else if args.max_size.is_some_and(|size| {
+            !(viewport_capture::MIN_MAX_SIZE..=viewport_capture::MAX_MAX_SIZE).contains(&size)
+        }) {
+            Some(format!(
+                "max_size must be between {} and {}",
+                viewport_capture::MIN_MAX_SIZE,
+                viewport_capture::MAX_MAX_SIZE
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
         }
+        let args = ToolArgumentValues::CaptureViewport(args);
+        let tool = args.tool_name();
+        let reply = match self.run_command(args, context).await? {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(failed),
+        };
+        let capture = serde_json::from_str::<Value>(&reply)
+            .ok()
+            .and_then(|reply| reply["captureId"].as_str()?.parse::<Uuid>().ok());
+        let mut result = self.paged_result(tool, reply).await;
+        if let Some(id) = capture {
+            result.content.push(match self.take_capture(id).await {
+                Ok(Some(png)) => Content::image(BASE64_STANDARD.encode(png), "image/png"),
+                Ok(None) => Content::text("The screenshot expired before it could be returned"),
+                Err(e) => Content::text(format!("Couldn't fetch the screenshot: {e:#}")),
+            });
+        }
+        Ok(result)
     }
 
     #[tool(
-        description = "Runs a command in Roblox Studio and returns the printed output. Can be used to both make changes and retrieve information"
+        description = "Get the console output from Roblox Studio.",
+        annotations(read_only_hint = true)
     )]
-    async fn run_code(
+    async fn get_console_output(
         &self,
-        Parameters(args): Parameters<RunCode>,
+        Parameters(args): Parameters<GetConsoleOutput>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::RunCode(args))
+        self.generic_tool_run(ToolArgumentValues::GetConsoleOutput(args), context)
             .await
     }
 
     #[tool(
-        description = "Inserts a model from the Roblox marketplace into the workspace. Returns the inserted model name."
+        description = "Start or stop play mode or run the server.",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
     )]
-    async fn insert_model(
+    async fn start_stop_play(
         &self,
-        Parameters(args): Parameters<InsertModel>,
+        Parameters(args): Parameters<StartStopPlay>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::InsertModel(args))
+        self.generic_tool_run(ToolArgumentValues::StartStopPlay(args), context)
             .await
     }
 
     #[tool(
-        description = "Inserts multiple models from the Roblox marketplace in a single call. Each model can have custom position, rotation, scale, name, and parent. Returns JSON with inserted count, failures, and instance paths."
+        description = "Run a script in play mode and automatically stop play after script finishes or timeout. Returns the output of the script.
+        Result format: { success: boolean, value: string, error: string, logs: { level: string, message: string, ts: number }[], errors: { level: string, message: string, ts: number }[], duration: number, isTimeout: boolean }",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
     )]
-    async fn batch_insert_models(
+    async fn run_script_in_play_mode(
         &self,
-        Parameters(args): Parameters<BatchInsertModels>,
+        Parameters(args): Parameters<RunScriptInPlayMode>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::BatchInsertModels(args))
+        self.generic_tool_run(ToolArgumentValues::RunScriptInPlayMode(args), context)
             .await
     }
 
     #[tool(
-        description = "Executes multiple Luau scripts sequentially with shared state between them. Scripts can store values in _G to pass data to subsequent scripts. Returns JSON with execution results for each script."
+        description = "Runs the place's tests in Run mode, so modules are required afresh each time: TestEZ .spec modules, optionally only those whose full name matches a pattern, or the checks of the scripts in TestService. Returns how many passed, failed and were skipped, with each test's status and failure messages.",
+        output_schema = tool_results::output_schema::<tool_results::RunTestsResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
     )]
-    async fn batch_run_code(
+    async fn run_tests(
         &self,
-        Parameters(args): Parameters<BatchRunCode>,
+        Parameters(mut args): Parameters<RunTests>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::BatchRunCode(args))
+        let message = if args
+            .framework
+            .as_deref()
+            .is_some_and(|framework| !test_runner::TEST_FRAMEWORKS.contains(&framework))
+        {
+            Some(format!(
+                "framework must be one of {}",
+                test_runner::TEST_FRAMEWORKS.join(", ")
+            ))
+        } else if args
+            .timeout
+            .is_some_and(|timeout| !(1..=MAX_TEST_TIMEOUT).contains(&timeout))
+        {
+            Some(format!("timeout must be between 1 and {MAX_TEST_TIMEOUT}"))
+        } else if args
+            .paths
+            .iter()
+            .flatten()
+            .any(|path| path.is_empty() || path.contains(','))
+        {
+            Some("paths can't be empty or contain commas".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        args.runner_source = Some(test_runner::RUNNER_SOURCE.to_string());
+        self.generic_tool_run(ToolArgumentValues::RunTests(args), context)
             .await
     }
 
     #[tool(
-        description = "Generates terrain using noise-based heightmaps. Supports flat, perlin, and ridged noise types. Can optionally fill water below a specified level."
+        description = "Starts a playtest in Studio, playing solo or running the server, and waits until the game is running. Returns when the session started. Read what the game prints with get_console_output while it runs, then end it with stop_playtest.",
+        output_schema = tool_results::output_schema::<tool_results::StartPlaytestResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
     )]
-    async fn generate_terrain(
+    async fn start_playtest(
         &self,
-        Parameters(args): Parameters<GenerateTerrain>,
+        Parameters(args): Parameters<StartPlaytest>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GenerateTerrain(args))
+        if let Some(mode) = args
+            .mode
+            .as_deref()
+            .filter(|mode| !PLAYTEST_MODES.contains(mode))
+        {
+            let message = format!(
+                "There is no {mode} mode, use one of {}",
+                PLAYTEST_MODES.join(", ")
+            );
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
+        self.generic_tool_run(ToolArgumentValues::StartPlaytest(args), context)
             .await
     }
 
     #[tool(
-        description = "Fills a terrain region with a specific material. Can optionally only fill empty space (air)."
+        description = "Stops the playtest started by start_playtest and waits until Studio is back in edit mode. Returns when the session started and ended and how long it ran.",
+        output_schema = tool_results::output_schema::<tool_results::StopPlaytestResult>(),
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
     )]
-    async fn fill_terrain_region(
+    async fn stop_playtest(
         &self,
-        Parameters(args): Parameters<FillTerrainRegion>,
+        Parameters(args): Parameters<StopPlaytest>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::FillTerrainRegion(args))
+        self.generic_tool_run(ToolArgumentValues::StopPlaytest(args), context)
             .await
     }
 
     #[tool(
-        description = "Sculpts terrain by raising, lowering, painting, or smoothing at specified points. Each point has position, radius, and strength."
+        description = "Get the current studio mode. Returns the studio mode. The result will be one of start_play, run_server, or stop.",
+        annotations(read_only_hint = true)
     )]
-    async fn sculpt_terrain(
+    async fn get_studio_mode(
         &self,
-        Parameters(args): Parameters<SculptTerrain>,
+        Parameters(args): Parameters<GetStudioMode>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::SculptTerrain(args))
+        self.generic_tool_run(ToolArgumentValues::GetStudioMode(args), context)
             .await
     }
 
     #[tool(
-        description = "Clears objects from the workspace. Can optionally preserve camera, terrain, and specific named instances. Can also clear only within a region."
+        description = "Lists the Roblox Studio windows connected to this server with their session ID, place name and place ID. Use select_studio_session to choose which one receives commands when several are open.",
+        annotations(read_only_hint = true)
     )]
-    async fn clear_workspace(
+    async fn list_studio_sessions(
         &self,
-        Parameters(args): Parameters<ClearWorkspace>,
+        Parameters(_): Parameters<ListStudioSessions>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::ClearWorkspace(args))
+        let sessions = self
+            .studio_sessions()
             .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::json(sessions)?]))
     }
 
     #[tool(
-        description = "Saves a snapshot of the current workspace to memory with a given name. Can optionally save only objects within a region or exclude specific objects."
+        description = "Selects the Roblox Studio session that receives all following commands from this client. Get session IDs from list_studio_sessions.",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
     )]
-    async fn save_scene(
+    async fn select_studio_session(
         &self,
-        Parameters(args): Parameters<SaveScene>,
+        Parameters(args): Parameters<SelectStudioSession>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::SaveScene(args))
+        let Some(id) = args.session_id else {
+            *self.selected_session.lock().await = None;
+            return Ok(CallToolResult::success(vec![Content::text(
+                "Cleared the selected Studio session",
+            )]));
+        };
+        let sessions = self
+            .studio_sessions()
             .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let Some(session) = sessions.into_iter().find(|session| session.id == id) else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "No Studio session with ID {id} is connected"
+            ))]));
+        };
+        *self.selected_session.lock().await = Some(id);
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Commands will now run in {}",
+            session
+                .place_name
+                .as_deref()
+                .unwrap_or("the selected session")
+        ))]))
     }
 
     #[tool(
-        description = "Loads a previously saved scene snapshot by name. Can apply position offset and optionally clear workspace before loading."
+        description = "Lists the commands queued for or running in Roblox Studio with their ID, tool name, status and age in seconds, running commands first and then the queue in the order Studio will get it. Use it to find out why a tool call is slow, and pass IDs in cancel to drop commands that are no longer needed.",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
     )]
-    async fn load_scene(
+    async fn get_pending_operations(
         &self,
-        Parameters(args): Parameters<LoadScene>,
+        Parameters(args): Parameters<GetPendingOperations>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::LoadScene(args))
+        let mut cancelled = Vec::new();
+        let mut not_found = Vec::new();
+        for id in args.cancel.unwrap_or_default() {
+            let found = match Uuid::parse_str(&id) {
+                Ok(uuid) => self
+                    .cancel_operation(uuid)
+                    .await
+                    .map_err(|e| ErrorData::internal_error(e.to_string(), None))?,
+                Err(_) => false,
+            };
+            if found {
+                cancelled.push(id);
+            } else {
+                not_found.push(id);
+            }
+        }
+        let operations = self
+            .pending_operations()
             .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "operations": operations,
+            "cancelled": cancelled,
+            "not_found": not_found,
+        }))?]))
     }
 
     #[tool(
-        description = "Retrieves console logs from Roblox Studio. Captures all print(), warn(), and error() output as well as Roblox engine messages. Supports polling with sequence numbers, level filtering, and pagination."
+        description = "Fetches the next page of a tool result that was too long to return at once. Pass the continuation token from the end of the previous page. Pages can be read again for 10 minutes.",
+        annotations(read_only_hint = true)
     )]
-    async fn get_console_logs(
+    async fn fetch_result_page(
         &self,
-        Parameters(args): Parameters<GetConsoleLogs>,
+        Parameters(args): Parameters<FetchResultPage>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GetConsoleLogs(args))
+        let parsed = args
+            .token
+            .split_once(':')
+            .and_then(|(id, offset)| Some((Uuid::parse_str(id).ok()?, offset.parse().ok()?)));
+        let Some((id, offset)) = parsed else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Invalid continuation token {}",
+                args.token
+            ))]));
+        };
+        let pages = self.result_pages.lock().await;
+        let Some(stored) = pages
+            .get(&id)
+            .filter(|stored| stored.at.elapsed() < RESULT_PAGE_TTL)
+        else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "This result is no longer kept, run the tool again",
+            )]));
+        };
+        if offset > stored.text.len() || !stored.text.is_char_boundary(offset) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Invalid continuation token {}",
+                args.token
+            ))]));
+        }
+        let page = result_page(id, &stored.text, offset, self.config.max_result_bytes);
+        Ok(CallToolResult::success(vec![Content::text(page)]))
+    }
+
+    /// Builds the result of a tool call. Replies over `max_result_bytes` are kept so the
+    /// client can read them with `fetch_result_page` and only the first page is returned.
+    async fn paged_result(&self, tool: &str, reply: String) -> CallToolResult {
+        let id = Uuid::new_v4();
+        let (result, kept) = first_page(tool, reply, id, self.config.max_result_bytes);
+        if let Some(text) = kept {
+            let mut pages = self.result_pages.lock().await;
+            pages.retain(|_, stored| stored.at.elapsed() < RESULT_PAGE_TTL);
+            let stored = StoredResult {
+                text,
+                at: Instant::now(),
+            };
+            pages.insert(id, stored);
+        }
+        result
+    }
+
+    /// Request to the instance that owns the plugin endpoint, used when this one is
+    /// only proxying.
+    fn owner_request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> color_eyre::Result<reqwest::RequestBuilder> {
+        let url = format!("{}{path}", self.config.local_url(self.config.port));
+        let mut request = tls::client(&self.config)?.request(method, url);
+        if let Some(token) = &self.config.auth_token {
+            request = request.header(AUTH_HEADER, token);
+        }
+        Ok(request)
+    }
+
+    /// Sessions connected to the plugin endpoint, fetched from the instance that owns it
+    /// when this one is only proxying.
+    async fn studio_sessions(&self) -> color_eyre::Result<Vec<StudioSession>> {
+        let sessions = self
+            .state
+            .call(|state| (!state.proxied).then(|| state.active_sessions()))
+            .await;
+        if let Some(sessions) = sessions {
+            return Ok(sessions);
+        }
+        let request = self.owner_request(reqwest::Method::GET, "/sessions")?;
+        Ok(request.send().await?.error_for_status()?.json().await?)
+    }
+
+    async fn pending_operations(&self) -> color_eyre::Result<Vec<PendingOperation>> {
+        let operations = self
+            .state
+            .call(|state| (!state.proxied).then(|| state.pending_operations()))
+            .await;
+        if let Some(operations) = operations {
+            return Ok(operations);
+        }
+        let request = self.owner_request(reqwest::Method::GET, "/queue")?;
+        Ok(request.send().await?.error_for_status()?.json().await?)
+    }
+
+    /// Takes a viewport capture uploaded by the plugin, from the instance that owns the
+    /// plugin endpoint when this one is only proxying.
+    async fn take_capture(&self, id: Uuid) -> color_eyre::Result<Option<Vec<u8>>> {
+        let capture = self
+            .state
+            .call(move |state| (!state.proxied).then(|| state.take_capture(id)))
+            .await;
+        if let Some(capture) = capture {
+            return Ok(capture);
+        }
+        let request = self.owner_request(reqwest::Method::GET, &format!("/capture/{id}"))?;
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.bytes().await?.to_vec()))
+    }
+
+    /// Cancels a command wherever it is queued. Returns false if no such command is waiting.
+    async fn cancel_operation(&self, id: Uuid) -> color_eyre::Result<bool> {
+        let cancelled = self
+            .state
+            .call(move |state| (!state.proxied).then(|| state.cancel_command(id)))
+            .await;
+        if let Some(cancelled) = cancelled {
+            return Ok(cancelled);
+        }
+        let request = self.owner_request(reqwest::Method::DELETE, &format!("/queue/{id}"))?;
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        response.error_for_status()?;
+        Ok(true)
+    }
+
+    /// Works out which Studio session a command goes to, refusing to guess when several
+    /// are connected and none was selected.
+    async fn target_session(&self) -> Result<Option<String>, String> {
+        let selected = self.selected_session.lock().await.clone();
+        let sessions = self
+            .studio_sessions()
             .await
+            .map_err(|e| format!("Unable to list Studio sessions: {e}"))?;
+        match selected {
+            Some(id) if sessions.iter().any(|session| session.id == id) => Ok(Some(id)),
+            Some(id) => Err(format!(
+                "Studio session {id} is no longer connected, select another one with select_studio_session"
+            )),
+            None if sessions.len() > 1 => Err(
+                "Several Studio sessions are connected, pick one with list_studio_sessions and select_studio_session"
+                    .to_string(),
+            ),
+            None => Ok(None),
+        }
+    }
+
+    /// The enabled tools the connected plugins can run. Everything is offered while no
+    /// plugin has registered, since there's no telling what it will support.
+    fn available_tools(&self) -> ToolRouter<Self> {
+        let mut router = self.tool_router.clone();
+        if let Some(commands) = self.state.plugin_commands().borrow().clone() {
+            router.map.retain(|name, _| {
+                SERVER_TOOLS.contains(&name.as_ref())
+                    || plugin_commands(name)
+                        .iter()
+                        .all(|command| commands.contains(command))
+            });
+        }
+        router
     }
 
-    #[tool(
-        description = "Gets statistics about the workspace including part count, model count, size distribution, and color distribution. Useful for analyzing scene complexity and visual composition."
-    )]
-    async fn get_workspace_stats(
-        &self,
-        Parameters(args): Parameters<GetWorkspaceStats>,
-    ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GetWorkspaceStats(args))
-            .await
+    /// Instance paths of the target Studio session, listed again once the last snapshot
+    /// is older than `INSTANCE_TREE_TTL`. Empty if Studio can't be reached.
+    async fn instance_tree(&self, context: RequestContext<RoleServer>) -> Arc<Vec<Vec<String>>> {
+        let session = self.selected_session.lock().await.clone();
+        if let Some(tree) = self.instance_tree.lock().await.as_ref() {
+            if tree.session == session && tree.at.elapsed() < INSTANCE_TREE_TTL {
+                return tree.paths.clone();
+            }
+        }
+        let args = ToolArgumentValues::ListInstancePaths(ListInstancePaths {
+            max_depth: INSTANCE_TREE_DEPTH,
+            limit: INSTANCE_TREE_LIMIT,
+        });
+        let reply = match self.run_command(args, context).await {
+            Ok(Ok(reply)) => reply,
+            Ok(Err(_)) | Err(_) => {
+                tracing::debug!("Couldn't list instance paths to complete");
+                return Arc::default();
+            }
+        };
+        let tree = match serde_json::from_str::<InstancePaths>(&reply) {
+            Ok(tree) => tree,
+            Err(e) => {
+                tracing::warn!("Unexpected list_instance_paths reply from the plugin: {e}");
+                return Arc::default();
+            }
+        };
+        if tree.truncated {
+            tracing::debug!(
+                "Place has over {INSTANCE_TREE_LIMIT} instances, completing the first ones"
+            );
+        }
+        let paths = Arc::new(tree.paths);
+        *self.instance_tree.lock().await = Some(InstanceTree {
+            session,
+            paths: paths.clone(),
+            at: Instant::now(),
+        });
+        paths
     }
 
-    #[tool(
-        description = "Gets information about all children of a specified instance. Returns name, className, and part count for each child. Optionally includes bounding box information (min, max, size, center coordinates). Useful for exploring scene hierarchy and understanding model composition."
-    )]
-    async fn get_children_info(
+    async fn generic_tool_run(
         &self,
-        Parameters(args): Parameters<GetChildrenInfo>,
+        args: ToolArgumentValues,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GetChildrenInfo(args))
-            .await
+        let tool = args.tool_name();
+        Ok(match self.run_command(args, context).await? {
+            Ok(reply) => self.paged_result(tool, reply).await,
+            Err(failed) => failed,
+        })
     }
 
-    #[tool(
-        description = "Gets the bounding box of a Model or BasePart instance. Returns min, max, size, and center positions. Useful for calculating placement positions or determining object dimensions."
-    )]
-    async fn get_model_bounds(
+    /// Saves the workspace as a revision of the safety snapshot scene, then runs a command
+    /// that removes instances and names the snapshot in its result so the change can be
+    /// undone. Nothing runs if the snapshot can't be taken.
+    async fn run_after_safety_snapshot(
         &self,
-        Parameters(args): Parameters<GetModelBounds>,
+        args: ToolArgumentValues,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GetModelBounds(args))
-            .await
+        if !self.config.safety_snapshots {
+            return self.generic_tool_run(args, context).await;
+        }
+        let tool = args.tool_name();
+        let failed = format!(
+            "Couldn't save the workspace to {} before {tool}, so nothing was changed. Set safety_snapshots = false in the server config to run it anyway",
+            scenes::SAFETY_SCENE
+        );
+        let save = ToolArgumentValues::SaveScene(SaveScene {
+            name: scenes::SAFETY_SCENE.to_string(),
+            region: None,
+            exclude_names: None,
+            description: Some(format!("Workspace before {tool}")),
+            tags: None,
+        });
+        // The snapshot is a command of its own, it must not answer retries of the call
+        let mut save_context = context.clone();
+        save_context.meta.remove(IDEMPOTENCY_KEY_META);
+        save_context.meta.remove(PROGRESS_TOKEN_META);
+        let saved = match self.run_command(save, save_context).await? {
+            Ok(reply) => reply,
+            Err(mut result) => {
+                result.content.insert(0, Content::text(failed));
+                return Ok(result);
+            }
+        };
+        let snapshot = match serde_json::from_str::<Value>(&saved) {
+            Ok(saved) if saved["success"] == Value::Bool(true) => tool_results::SafetySnapshot {
+                scene: scenes::SAFETY_SCENE.to_string(),
+                revision: saved["revision"]
+                    .as_u64()
+                    .and_then(|r| u32::try_from(r).ok()),
+            },
+            _ => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "{failed}: {saved}"
+                ))]))
+            }
+        };
+        let reply = match self.run_command(args, context).await? {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(failed),
+        };
+        let reply = match serde_json::from_str::<Value>(&reply) {
+            Ok(Value::Object(mut result)) => {
+                let snapshot = serde_json::to_value(snapshot)
+                    .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+                result.insert("safetySnapshot".to_string(), snapshot);
+                Value::Object(result).to_string()
+            }
+            _ => reply,
+        };
+        Ok(self.paged_result(tool, reply).await)
     }
 
-    #[tool(
-        description = "Finds gaps between two models or parts by raycasting from surface points of model_a toward model_b. Returns gap positions, distances, and nearest points on both models. Useful for detecting holes or misalignments between adjacent geometry. Limited to 50 gap results."
-    )]
-    async fn find_gaps(
-        &self,
-        Parameters(args): Parameters<FindGaps>,
-    ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::FindGaps(args))
-            .await
+    /// The filters of a marketplace search with the server's defaults filled in
+    fn marketplace_filters(&self, filters: Option<MarketplaceFilters>) -> MarketplaceFilters {
+        let mut filters = filters.unwrap_or_default();
+        filters
+            .verified_only
+            .get_or_insert(self.config.marketplace_verified_only);
+        filters
     }
 
-    #[tool(
-        description = "Positions the camera for viewport capture. Optionally sets camera position and look-at target. Returns the final camera state. Note: Actual screenshot capture requires manual action (Ctrl+Shift+S in Studio) or using Studio's File > Screenshot menu."
-    )]
-    async fn capture_viewport(
+    /// `marketplace_search` answered from an earlier search for the same query and
+    /// filters that asked for at least `limit` results, unless `force_refresh` is set
+    async fn cached_marketplace_search(
         &self,
-        Parameters(args): Parameters<CaptureViewport>,
-    ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::CaptureViewport(args))
-            .await
+        query: &str,
+        limit: u32,
+        filters: MarketplaceFilters,
+        force_refresh: bool,
+        context: RequestContext<RoleServer>,
+    ) -> Result<Result<tool_results::SearchMarketplaceResult, CallToolResult>, ErrorData> {
+        let ttl = self.config.marketplace_cache();
+        if ttl.is_zero() {
+            return self
+                .marketplace_search(query.to_string(), limit, filters, context)
+                .await;
+        }
+        let key = format!(
+            "{}\n{}",
+            query.trim().to_lowercase(),
+            serde_json::to_string(&filters).unwrap_or_default()
+        );
+        if !force_refresh {
+            let mut searches = self.marketplace_searches.lock().await;
+            searches.retain(|_, search| search.at.elapsed() < ttl);
+            if let Some(search) = searches.get(&key) {
+                // A search that found fewer than it asked for found everything there is
+                let exhausted = search.found.results.len() < search.limit as usize;
+                if search.limit >= limit || exhausted {
+                    let mut found = search.found.clone();
+                    found.results.truncate(limit as usize);
+                    found.cached = true;
+                    return Ok(Ok(found));
+                }
+            }
+        }
+        let found = match self
+            .marketplace_search(query.to_string(), limit, filters, context)
+            .await?
+        {
+            Ok(found) => found,
+            Err(failed) => return Ok(Err(failed)),
+        };
+        let search = CachedSearch {
+            limit,
+            found: found.clone(),
+            at: Instant::now(),
+        };
+        self.marketplace_searches.lock().await.insert(key, search);
+        Ok(Ok(found))
     }
 
-    #[tool(description = "Get the console output from Roblox Studio.")]
-    async fn get_console_output(
+    /// Searches the marketplace in Studio and adds what only the server can look up.
+    /// Favorites are only known here, so a minimum of them is applied to the most results
+    /// a search returns before keeping `limit` of them.
+    async fn marketplace_search(
         &self,
-        Parameters(args): Parameters<GetConsoleOutput>,
-    ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GetConsoleOutput(args))
-            .await
+        query: String,
+        limit: u32,
+        filters: MarketplaceFilters,
+        context: RequestContext<RoleServer>,
+    ) -> Result<Result<tool_results::SearchMarketplaceResult, CallToolResult>, ErrorData> {
+        let min_favorites = filters.min_favorites;
+        let search = SearchMarketplace {
+            query,
+            limit: Some(if min_favorites.is_some() {
+                MAX_MARKETPLACE_RESULTS
+            } else {
+                limit
+            }),
+            filters: Some(filters),
+            force_refresh: None,
+        };
+        let reply = match self
+            .run_command(ToolArgumentValues::SearchMarketplace(search), context)
+            .await?
+        {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(Err(failed)),
+        };
+        // Anything else is the plugin explaining why the search failed
+        let Ok(mut found) = serde_json::from_str::<tool_results::SearchMarketplaceResult>(&reply)
+        else {
+            return Ok(Err(CallToolResult::error(vec![Content::text(reply)])));
+        };
+        marketplace::enrich(&mut found.results).await;
+        for asset in &mut found.results {
+            asset.url = marketplace::store_url(asset.asset_id);
+        }
+        if let Some(min_favorites) = min_favorites {
+            // Assets whose favorites couldn't be looked up aren't trusted either
+            let before = found.results.len();
+            found
+                .results
+                .retain(|asset| asset.favorites.is_some_and(|count| count >= min_favorites));
+            found.filtered_out += (before - found.results.len()) as u64;
+        }
+        found.results.truncate(limit as usize);
+        Ok(Ok(found))
     }
 
-    #[tool(description = "Start or stop play mode or run the server.")]
-    async fn start_stop_play(
+    /// Runs a terrain job one tile at a time and merges the tiles' replies. Progress is
+    /// reported per tile, and the first tile to fail stops the job.
+    async fn run_tiled(
         &self,
-        Parameters(args): Parameters<StartStopPlay>,
+        mut commands: Vec<ToolArgumentValues>,
+        mut context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::StartStopPlay(args))
-            .await
+        if commands.len() <= 1 {
+            let Some(command) = commands.pop() else {
+                return Err(ErrorData::internal_error("No terrain tiles to run", None));
+            };
+            return self.generic_tool_run(command, context).await;
+        }
+        let tool = commands[0].tool_name();
+        let total = commands.len();
+        let progress_token = context.meta.get_progress_token();
+        // Tiles report their own progress, each from zero, so only whole tiles are reported
+        context.meta.remove(PROGRESS_TOKEN_META);
+        // Every tile is a different command, a retry must not get the first one's reply
+        context.meta.remove(IDEMPOTENCY_KEY_META);
+
+        let mut merged: Option<Value> = None;
+        for (index, command) in commands.into_iter().enumerate() {
+            if let Some(progress_token) = progress_token.clone() {
+                let notification = ProgressNotificationParam {
+                    progress_token,
+                    progress: index as f64,
+                    total: Some(total as f64),
+                    message: Some(format!("Tile {} of {total}", index + 1)),
+                };
+                if let Err(e) = context.peer.notify_progress(notification).await {
+                    tracing::debug!("Failed to send progress for {tool}: {e}");
+                }
+            }
+            let failed = format!(
+                "Tile {} of {total} failed after {index} finished",
+                index + 1
+            );
+            let reply = match self.run_command(command, context.clone()).await? {
+                Ok(reply) => reply,
+                Err(mut result) => {
+                    result.content.insert(0, Content::text(failed));
+                    return Ok(result);
+                }
+            };
+            let tile = match serde_json::from_str::<Value>(&reply) {
+                Ok(tile) if tile["success"] == Value::Bool(true) => tile,
+                _ => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "{failed}: {reply}"
+                    ))]))
+                }
+            };
+            match &mut merged {
+                Some(merged) => terrain_tiles::merge(merged, tile),
+                None => merged = Some(tile),
+            }
+        }
+        let reply = serde_json::to_string(&merged)
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(self.paged_result(tool, reply).await)
     }
 
-    #[tool(
-        description = "Run a script in play mode and automatically stop play after script finishes or timeout. Returns the output of the script.
-        Result format: { success: boolean, value: string, error: string, logs: { level: string, message: string, ts: number }[], errors: { level: string, message: string, ts: number }[], duration: number, isTimeout: boolean }"
-    )]
-    async fn run_script_in_play_mode(
+    /// Sources of the scripts under `root`, or the whole place, for tools that work on
+    /// them on the server
+    async fn script_sources(
         &self,
-        Parameters(args): Parameters<RunScriptInPlayMode>,
-    ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::RunScriptInPlayMode(args))
-            .await
+        root: Option<String>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<Result<Vec<ScriptSource>, CallToolResult>, ErrorData> {
+        let fetch = ToolArgumentValues::GetScriptSources(GetScriptSources { root });
+        let reply = match self.run_command(fetch, context).await? {
+            Ok(reply) => reply,
+            Err(failed) => return Ok(Err(failed)),
+        };
+        // Anything else is the plugin explaining why it couldn't read the scripts
+        match serde_json::from_str::<ScriptSources>(&reply) {
+            Ok(sources) => Ok(Ok(sources.scripts)),
+            Err(_) => Ok(Err(CallToolResult::error(vec![Content::text(reply)]))),
+        }
     }
 
-    #[tool(
-        description = "Get the current studio mode. Returns the studio mode. The result will be one of start_play, run_server, or stop."
-    )]
-    async fn get_studio_mode(
+    /// Writes back sources edited on the server, in as few commands as `write_batches`
+    /// allows. The call's idempotency key isn't passed on, since each batch would
+    /// otherwise be taken for a retry of the first.
+    async fn write_script_sources(
         &self,
-        Parameters(args): Parameters<GetStudioMode>,
-    ) -> Result<CallToolResult, ErrorData> {
-        self.generic_tool_run(ToolArgumentValues::GetStudioMode(args))
-            .await
+        writes: Vec<ScriptWrite>,
+        mut context: RequestContext<RoleServer>,
+    ) -> Result<Result<WrittenScripts, CallToolResult>, ErrorData> {
+        context.meta.remove(IDEMPOTENCY_KEY_META);
+        let mut written = WrittenScripts::default();
+        for scripts in write_batches(writes) {
+            let write = ToolArgumentValues::SetScriptSources(SetScriptSources { scripts });
+            let reply = match self.run_command(write, context.clone()).await? {
+                Ok(reply) => reply,
+                Err(failed) => return Ok(Err(failed)),
+            };
+            let Ok(batch) = serde_json::from_str::<WrittenScripts>(&reply) else {
+                return Ok(Err(CallToolResult::error(vec![Content::text(reply)])));
+            };
+            written.written.extend(batch.written);
+            written.skipped.extend(batch.skipped);
+        }
+        Ok(Ok(written))
     }
 
-    async fn generic_tool_run(
+    /// Runs a command in Studio and returns its reply, or the tool result to fail with
+    async fn run_command(
         &self,
         args: ToolArgumentValues,
-    ) -> Result<CallToolResult, ErrorData> {
-        let (command, id) = ToolArguments::new(args);
+        context: RequestContext<RoleServer>,
+    ) -> Result<Result<String, CallToolResult>, ErrorData> {
+        let session = match self.target_session().await {
+            Ok(session) => session,
+            Err(message) => return Ok(Err(CallToolResult::error(vec![Content::text(message)]))),
+        };
+        let tool = args.tool_name();
+        let timeout = args.timeout(&self.config);
+        let priority = self.config.tool_priority(tool, args.default_priority());
+        let idempotency_key = context
+            .meta
+            .get(IDEMPOTENCY_KEY_META)
+            .and_then(|key| key.as_str())
+            .map(str::to_string);
+        let (command, id) = ToolArguments::new(args, session, priority, idempotency_key);
         tracing::debug!("Running command: {:?}", command);
-        let (tx, mut rx) = mpsc::unbounded_channel::<Result<String>>();
-        let trigger = {
-            let mut state = self.state.lock().await;
-            state.process_queue.push_back(command);
-            state.output_map.insert(id, tx);
-            state.trigger.clone()
+        // Progress only reaches calls made on the instance that owns the plugin endpoint
+        let progress_token = context.meta.get_progress_token();
+        let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+        let wants_progress = progress_token.is_some();
+        let config = Arc::clone(&self.config);
+        let enqueued = self
+            .state
+            .call(move |state| {
+                let enqueued = state.enqueue(id, command, &config);
+                if wants_progress && matches!(enqueued, Enqueued::Waiting(_)) {
+                    state.progress.insert(id, progress_tx);
+                }
+                let connected = state.plugin_connected(&config);
+                (enqueued, connected, state.process_queue.len())
+            })
+            .await;
+        let mut rx = {
+            let (enqueued, connected, queue_depth) = enqueued;
+            match enqueued {
+                Enqueued::Waiting(rx) => rx,
+                Enqueued::Cached(result) => {
+                    tracing::debug!("Returning the earlier result of {tool}");
+                    return Ok(
+                        result.map_err(|err| CallToolResult::error(vec![Content::text(err)]))
+                    );
+                }
+                Enqueued::Duplicate(running) => {
+                    return Ok(Err(CallToolResult::error(vec![Content::text(
+                        duplicate_message(running),
+                    )])));
+                }
+                Enqueued::Full => {
+                    let reason = if connected { "busy" } else { "disconnected" };
+                    return Ok(Err(CallToolResult::error(vec![Content::text(format!(
+                        "Studio is {reason}: {queue_depth} commands are already waiting. Check that the plugin is connected and try again later"
+                    ))])));
+                }
+            }
+        };
+        let wait = async {
+            loop {
+                tokio::select! {
+                    result = &mut rx => return result,
+                    Some(update) = progress_rx.recv() => {
+                        let Some(progress_token) = progress_token.clone() else {
+                            continue;
+                        };
+                        let notification = ProgressNotificationParam {
+                            progress_token,
+                            progress: update.progress,
+                            total: update.total,
+                            message: update.message,
+                        };
+                        if let Err(e) = context.peer.notify_progress(notification).await {
+                            tracing::debug!("Failed to send progress for {tool}: {e}");
+                        }
+                    }
+                }
+            }
+        };
+        let Ok(result) = tokio::time::timeout(timeout, wait).await else {
+            let still_queued = self
+                .state
+                .call(move |state| state.abandon_command(id))
+                .await;
+            tracing::warn!("{tool} timed out after {}s", timeout.as_secs());
+            return Ok(Err(CallToolResult::error(vec![Content::json(json!({
+                "error": "timeout",
+                "tool": tool,
+                "timeout_secs": timeout.as_secs(),
+                "message": if still_queued {
+                    "Studio never picked up the command, check that the plugin is connected"
+                } else {
+                    "Studio took the command but didn't reply in time, it may still finish"
+                },
+            }))?])));
         };
-        trigger
-            .send(())
-            .map_err(|e| ErrorData::internal_error(format!("Unable to trigger send {e}"), None))?;
-        let result = rx
-            .recv()
+        let result =
+            result.map_err(|_| ErrorData::internal_error("Couldn't receive response", None))?;
+        tracing::debug!("Sending to MCP: {result:?}");
+        Ok(result.map_err(|err| CallToolResult::error(vec![Content::text(err.to_string())])))
+    }
+}
+
+fn tool_result(tool: &str, reply: String) -> CallToolResult {
+    match tool_results::classify(tool, &reply) {
+        Reply::Text => CallToolResult::success(vec![Content::text(reply)]),
+        Reply::Failed => CallToolResult::error(vec![Content::text(reply)]),
+        Reply::Malformed(message) => CallToolResult::error(vec![Content::text(message)]),
+        Reply::Structured(structured) => structured_result(reply, structured),
+    }
+}
+
+/// The result of a tool call holding the first page of `reply` under `id`, and the whole
+/// reply if it has more pages to keep. Typed replies are never split, since the client
+/// checks their structured content against the tool's output schema.
+fn first_page(
+    tool: &str,
+    reply: String,
+    id: Uuid,
+    page_size: usize,
+) -> (CallToolResult, Option<String>) {
+    if page_size == 0 || reply.len() <= page_size {
+        return (tool_result(tool, reply), None);
+    }
+    let (text, failed) = match tool_results::classify(tool, &reply) {
+        Reply::Structured(structured) => return (structured_result(reply, structured), None),
+        Reply::Text => (reply, false),
+        Reply::Failed => (reply, true),
+        Reply::Malformed(message) => (message, true),
+    };
+    let page = vec![Content::text(result_page(id, &text, 0, page_size))];
+    let result = if failed {
+        CallToolResult::error(page)
+    } else {
+        CallToolResult::success(page)
+    };
+    (result, Some(text))
+}
+
+fn structured_result(reply: String, structured: Value) -> CallToolResult {
+    CallToolResult {
+        content: vec![Content::text(reply)],
+        structured_content: Some(structured),
+        is_error: Some(false),
+        meta: None,
+    }
+}
+
+/// Seed for noise the plugin would otherwise pick at random, in the same range it uses
+fn random_seed() -> i32 {
+    (Uuid::new_v4().as_u128() % 10_000) as i32
+}
+
+/// The page of `text` starting at `offset`, followed by the token for the next page if
+/// there is more
+fn result_page(id: Uuid, text: &str, offset: usize, page_size: usize) -> String {
+    let mut end = (offset + page_size).min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    // A page always holds at least one character
+    if end == offset && end < text.len() {
+        end += 1;
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+    }
+    let page = &text[offset..end];
+    if end == text.len() {
+        return page.to_string();
+    }
+    format!(
+        "{page}\n\n[Showing bytes {offset}-{end} of {}. Call fetch_result_page with token \"{id}:{end}\" for the rest]",
+        text.len()
+    )
+}
+
+fn duplicate_message(running: Uuid) -> String {
+    format!(
+        "An earlier call with this idempotency key is still waiting on Studio as command {running}, check get_pending_operations"
+    )
+}
+
+/// Waits until a command for `session` is queued and takes it off the queue. Commands
+/// without a target session go to whichever plugin asks first. A `pushed` command goes
+/// to a WebSocket, so it also waits until it can run beside the ones sent before it.
+async fn next_command(
+    state: &PackedState,
+    config: &Config,
+    session: Option<&str>,
+    pushed: bool,
+) -> Result<ToolArguments> {
+    let mut waiter = state.call(|state| state.waiter.clone()).await;
+    loop {
+        let session = session.map(str::to_string);
+        if let Some(task) = state
+            .lease_next(session, config.lease_timeout(), pushed)
             .await
-            .ok_or(ErrorData::internal_error("Couldn't receive response", None))?;
         {
-            let mut state = self.state.lock().await;
-            state.output_map.remove_entry(&id);
+            return Ok(task);
         }
-        tracing::debug!("Sending to MCP: {result:?}");
-        match result {
-            Ok(result) => Ok(CallToolResult::success(vec![Content::text(result)])),
-            Err(err) => Ok(CallToolResult::error(vec![Content::text(err.to_string())])),
+        waiter.changed().await?
+    }
+}
+
+pub async fn request_handler(
+    State(state): State<PackedState>,
+    Extension(config): Extension<Arc<Config>>,
+    Query(query): Query<PluginQuery>,
+) -> Result<impl IntoResponse> {
+    let session = query.session.clone();
+    state
+        .call(move |state| state.plugin_seen(session.as_deref()))
+        .await;
+    let timeout = tokio::time::timeout(
+        config.long_poll_timeout(),
+        next_command(&state, &config, query.session.as_deref(), false),
+    )
+    .await;
+    let command = match timeout {
+        Ok(result) => result?,
+        _ => return Ok((StatusCode::LOCKED, String::new()).into_response()),
+    };
+    let Some(size) = query.batch else {
+        return Ok(Json(command.for_plugin()).into_response());
+    };
+    let batch = state
+        .call(move |state| state.lease_batch(command, query.session.as_deref(), size))
+        .await;
+    let batch: Vec<_> = batch.iter().map(ToolArguments::for_plugin).collect();
+    Ok(Json(batch).into_response())
+}
+
+pub async fn response_handler(
+    State(state): State<PackedState>,
+    Json(payload): Json<RunCommandResponse>,
+) -> Result<impl IntoResponse> {
+    tracing::debug!("Received reply from studio {payload:?}");
+    state
+        .call(move |state| state.deliver_response(payload))
+        .await
+}
+
+/// WebSocket channel for the plugin: commands are pushed as soon as they are queued
+/// and replies come back as `RunCommandResponse` frames on the same socket.
+pub async fn ws_handler(
+    State(state): State<PackedState>,
+    Extension(config): Extension<Arc<Config>>,
+    Query(query): Query<PluginQuery>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| plugin_socket_loop(state, config, query.session, socket))
+}
+
+async fn plugin_socket_loop(
+    state: PackedState,
+    config: Arc<Config>,
+    session: Option<String>,
+    mut socket: WebSocket,
+) {
+    tracing::info!("Studio plugin connected over WebSocket");
+    let mut heartbeat = tokio::time::interval(WS_HEARTBEAT_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                let seen = session.clone();
+                state.call(move |state| state.plugin_seen(seen.as_deref())).await;
+                if socket.send(Message::Ping(Default::default())).await.is_err() {
+                    break;
+                }
+            }
+            command = next_command(&state, &config, session.as_deref(), true) => {
+                let Ok(command) = command else { break };
+                let frame = match serde_json::to_string(&command.for_plugin()) {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize command: {e}");
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(frame.into())).await.is_err() {
+                    // Put the command back so the next plugin connection picks it up
+                    state.call(move |state| state.release(command)).await;
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                match serde_json::from_str::<RunCommandResponse>(&text) {
+                    Ok(payload) => {
+                        tracing::debug!("Received reply from studio {payload:?}");
+                        let delivered = state
+                            .call(move |state| state.deliver_response(payload))
+                            .await;
+                        if let Err(e) = delivered {
+                            tracing::error!("{e:?}");
+                        }
+                    }
+                    Err(e) => tracing::error!("Malformed reply from studio: {e}"),
+                }
+            }
         }
     }
+    tracing::info!("Studio plugin WebSocket disconnected");
 }
 
-pub async fn request_handler(State(state): State<PackedState>) -> Result<impl IntoResponse> {
-    let timeout = tokio::time::timeout(LONG_POLL_DURATION, async {
-        let mut waiter = { state.lock().await.waiter.clone() };
-        loop {
-            {
-                let mut state = state.lock().await;
-                if let Some(task) = state.process_queue.pop_front() {
-                    return Ok::<ToolArguments, Error>(task);
+/// Called by the plugin when it connects so MCP clients can tell Studio windows apart.
+pub async fn register_handler(
+    State(state): State<PackedState>,
+    Json(session): Json<StudioSession>,
+) -> impl IntoResponse {
+    tracing::info!(
+        "Studio session {} registered for place {:?}",
+        session.id,
+        session.place_name
+    );
+    state
+        .call(move |state| state.register_session(session))
+        .await;
+    StatusCode::NO_CONTENT
+}
+
+pub async fn sessions_handler(State(state): State<PackedState>) -> impl IntoResponse {
+    Json(state.call(|state| state.active_sessions()).await)
+}
+
+/// Takes change events for watched paths from the plugin and tells the subscribed
+/// clients those resources were updated.
+pub async fn changes_handler(
+    State(state): State<PackedState>,
+    Json(changes): Json<InstanceChanges>,
+) -> impl IntoResponse {
+    tracing::debug!("Received changes from studio {changes:?}");
+    let subscribers = state
+        .call(move |state| state.subscribers_of(&changes.paths))
+        .await;
+    let mut gone = Vec::new();
+    for (path, subscriber, peer) in subscribers {
+        let uri = format!("{RESOURCE_SCHEME}{path}");
+        if let Err(e) = peer
+            .notify_resource_updated(ResourceUpdatedNotificationParam { uri })
+            .await
+        {
+            tracing::debug!("Dropping subscription to {path}, the client is gone: {e}");
+            gone.push((path, subscriber));
+        }
+    }
+    if !gone.is_empty() {
+        state
+            .call(move |state| {
+                for (path, subscriber) in gone {
+                    state.unsubscribe(&path, subscriber);
                 }
+            })
+            .await;
+    }
+    StatusCode::NO_CONTENT
+}
+
+/// Relays the Studio console output the plugin streams to the MCP clients as log
+/// notifications.
+pub async fn log_handler(
+    State(state): State<PackedState>,
+    Json(logs): Json<StudioLogs>,
+) -> impl IntoResponse {
+    let listeners = state.call(|state| state.log_listeners()).await;
+    let mut gone = HashSet::new();
+    for entry in logs.entries {
+        let level = entry.logging_level();
+        for (listener, peer, min_level) in &listeners {
+            if (level as u8) < (*min_level as u8) || gone.contains(listener) {
+                continue;
+            }
+            let notification = LoggingMessageNotificationParam {
+                level,
+                logger: Some("studio".to_string()),
+                data: serde_json::Value::String(entry.message.clone()),
+            };
+            if let Err(e) = peer.notify_logging_message(notification).await {
+                tracing::debug!("No longer forwarding Studio output to a closed client: {e}");
+                gone.insert(*listener);
             }
-            waiter.changed().await?
         }
+    }
+    if !gone.is_empty() {
+        state
+            .call(move |state| {
+                state
+                    .log_listeners
+                    .retain(|listener, _| !gone.contains(listener))
+            })
+            .await;
+    }
+    StatusCode::NO_CONTENT
+}
+
+/// Takes progress updates from the plugin for commands it is still running.
+pub async fn progress_handler(
+    State(state): State<PackedState>,
+    Json(update): Json<ProgressUpdate>,
+) -> impl IntoResponse {
+    tracing::debug!("Received progress from studio {update:?}");
+    if state.call(move |state| state.report_progress(update)).await {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Serves a chunk of a script source or heightmap too large to send with its command.
+pub async fn chunk_handler(
+    State(state): State<PackedState>,
+    Path((id, index)): Path<(Uuid, usize)>,
+) -> impl IntoResponse {
+    match state.call(move |state| state.source_chunk(id, index)).await {
+        Some(chunk) => chunk.into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Stores a scene snapshot the plugin took with `SaveScene` as a new revision, so it
+/// outlives the Studio session, and replies with the revision's number.
+pub async fn scene_upload_handler(
+    Extension(config): Extension<Arc<Config>>,
+    Path(name): Path<String>,
+    Json(scene): Json<Value>,
+) -> Result<impl IntoResponse> {
+    if !scenes::is_valid_name(&name) {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid scene name").into_response());
+    }
+    let revision = scenes::save(&config, &name, &scene).await?;
+    tracing::info!("Saved scene {name} revision {revision}");
+    Ok(Json(json!({ "revision": revision })).into_response())
+}
+
+/// Takes the pixels of a viewport capture from the plugin and keeps them as a PNG for the
+/// `capture_viewport` call, replying with the ID its result names the capture by.
+pub async fn capture_upload_handler(
+    State(state): State<PackedState>,
+    Query(query): Query<CaptureQuery>,
+    pixels: axum::body::Bytes,
+) -> impl IntoResponse {
+    let max_size = query.max_size.unwrap_or(viewport_capture::DEFAULT_MAX_SIZE);
+    let encoded = tokio::task::spawn_blocking(move || {
+        viewport_capture::encode_png(query.width, query.height, pixels.to_vec(), max_size)
     })
     .await;
-    match timeout {
-        Ok(result) => Ok(Json(result?).into_response()),
-        _ => Ok((StatusCode::LOCKED, String::new()).into_response()),
-    }
+    let (png, width, height) = match encoded {
+        Ok(Ok(encoded)) => encoded,
+        Ok(Err(e)) => return (StatusCode::BAD_REQUEST, format!("{e:#}")).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let id = Uuid::new_v4();
+    state.call(move |state| state.store_capture(id, png)).await;
+    Json(json!({ "id": id, "width": width, "height": height })).into_response()
 }
 
-pub async fn response_handler(
+/// Hands a viewport capture to the instance proxying the `capture_viewport` call it was
+/// taken for.
+pub async fn capture_download_handler(
     State(state): State<PackedState>,
-    Json(payload): Json<RunCommandResponse>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.call(move |state| state.take_capture(id)).await {
+        Some(png) => ([(axum::http::header::CONTENT_TYPE, "image/png")], png).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Returns a revision of a scene snapshot stored by an earlier `SaveScene`, the latest
+/// unless the query picks one.
+pub async fn scene_download_handler(
+    Extension(config): Extension<Arc<Config>>,
+    Path(name): Path<String>,
+    Query(query): Query<SceneQuery>,
 ) -> Result<impl IntoResponse> {
-    tracing::debug!("Received reply from studio {payload:?}");
-    let mut state = state.lock().await;
-    let tx = state
-        .output_map
-        .remove(&payload.id)
-        .ok_or_eyre("Unknown ID")?;
-    Ok(tx.send(Ok(payload.response))?)
+    if !scenes::is_valid_name(&name) {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+    Ok(match scenes::load(&config, &name, query.revision).await? {
+        Some((revision, scene)) => {
+            Json(json!({ "revision": revision, "scene": scene })).into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    })
+}
+
+/// Lists the commands Studio is running and the ones still queued.
+pub async fn queue_handler(State(state): State<PackedState>) -> impl IntoResponse {
+    Json(state.call(|state| state.pending_operations()).await)
+}
+
+/// Cancels a queued or running command, failing the tool call waiting on it.
+pub async fn cancel_handler(
+    State(state): State<PackedState>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if state.call(move |state| state.cancel_command(id)).await {
+        tracing::info!("Cancelled command {id}");
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[derive(Serialize)]
+struct Health {
+    name: &'static str,
+    version: &'static str,
+    uptime_secs: u64,
+    /// Whether a Studio plugin polled or kept its WebSocket alive recently
+    plugin_connected: bool,
+    last_plugin_poll_secs: Option<u64>,
+    studio_sessions: usize,
+    queue_depth: usize,
+    pending_responses: usize,
+}
+
+/// Reports whether Studio is connected and how much work is waiting on it.
+pub async fn health_handler(
+    State(state): State<PackedState>,
+    Extension(config): Extension<Arc<Config>>,
+) -> impl IntoResponse {
+    let health = state
+        .call(move |state| Health {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            uptime_secs: state.started_at.elapsed().as_secs(),
+            plugin_connected: state.plugin_connected(&config),
+            last_plugin_poll_secs: state.last_plugin_poll.map(|at| at.elapsed().as_secs()),
+            studio_sessions: state.active_sessions().len(),
+            queue_depth: state.process_queue.len(),
+            pending_responses: state.output_map.len(),
+        })
+        .await;
+    Json(health)
 }
 
 pub async fn proxy_handler(
     State(state): State<PackedState>,
+    Extension(config): Extension<Arc<Config>>,
     Json(command): Json<ToolArguments>,
-) -> Result<impl IntoResponse> {
+) -> Result<Response> {
     let id = command.id.ok_or_eyre("Got proxy command with no id")?;
     tracing::debug!("Received request to proxy {command:?}");
-    let (tx, mut rx) = mpsc::unbounded_channel();
-    {
-        let mut state = state.lock().await;
-        state.process_queue.push_back(command);
-        state.output_map.insert(id, tx);
-    }
-    let response = rx.recv().await.ok_or_eyre("Couldn't receive response")??;
-    {
-        let mut state = state.lock().await;
-        state.output_map.remove_entry(&id);
-    }
+    let timeout = command.args.timeout(&config);
+    let enqueue_config = Arc::clone(&config);
+    let enqueued = state
+        .call(move |state| state.enqueue(id, command, &enqueue_config))
+        .await;
+    let rx = match enqueued {
+        Enqueued::Waiting(rx) => rx,
+        Enqueued::Cached(Ok(response)) => {
+            return Ok(Json(RunCommandResponse { response, id }).into_response())
+        }
+        Enqueued::Cached(Err(e)) => return Ok((StatusCode::BAD_GATEWAY, e).into_response()),
+        Enqueued::Duplicate(running) => {
+            return Ok((StatusCode::CONFLICT, duplicate_message(running)).into_response())
+        }
+        Enqueued::Full => return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response()),
+    };
+    let Ok(response) = tokio::time::timeout(timeout, rx).await else {
+        state.call(move |state| state.abandon_command(id)).await;
+        return Ok(StatusCode::GATEWAY_TIMEOUT.into_response());
+    };
+    let response = response.map_err(|_| eyre!("Couldn't receive response"))?;
+    // The command was cancelled or Studio stopped responding, pass the reason on
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => return Ok((StatusCode::BAD_GATEWAY, e.to_string()).into_response()),
+    };
     tracing::debug!("Sending back to dud: {response:?}");
-    Ok(Json(RunCommandResponse { response, id }))
+    Ok(Json(RunCommandResponse { response, id }).into_response())
+}
+
+/// Checks whether `port` is owned by another instance of this server rather than an
+/// unrelated program by asking it for its `/health` report.
+pub async fn is_mcp_server(config: &Config, port: u16) -> bool {
+    let Ok(client) = tls::client(config) else {
+        return false;
+    };
+    let res = client
+        .get(format!("{}/health", config.local_url(port)))
+        .timeout(PORT_PROBE_TIMEOUT)
+        .send()
+        .await;
+    let Ok(res) = res else {
+        return false;
+    };
+    let health = res.json::<serde_json::Value>().await;
+    matches!(health, Ok(health) if health["name"] == env!("CARGO_PKG_NAME"))
 }
 
-pub async fn dud_proxy_loop(state: PackedState, exit: Receiver<()>) {
-    let client = reqwest::Client::new();
+pub async fn dud_proxy_loop(state: PackedState, config: Arc<Config>, exit: Receiver<()>) {
+    let client = match tls::client(&config) {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("Can't proxy to the plugin server: {e:?}");
+            return;
+        }
+    };
 
-    let mut waiter = { state.lock().await.waiter.clone() };
+    let mut waiter = state.call(|state| state.waiter.clone()).await;
     while exit.is_empty() {
-        let entry = { state.lock().await.process_queue.pop_front() };
+        let entry = state
+            .call(|state| state.process_queue.pop_first(|_| true))
+            .await;
         if let Some(entry) = entry {
-            let res = client
-                .post(format!("http://127.0.0.1:{STUDIO_PLUGIN_PORT}/proxy"))
-                .json(&entry)
-                .send()
-                .await;
+            let mut request = client
+                .post(format!("{}/proxy", config.local_url(config.port)))
+                .json(&entry);
+            if let Some(token) = &config.auth_token {
+                request = request.header(AUTH_HEADER, token);
+            }
+            let res = request.send().await;
             if let Ok(res) = res {
-                let tx = {
-                    state
-                        .lock()
-                        .await
-                        .output_map
-                        .remove(&entry.id.unwrap())
-                        .unwrap()
+                let tx = match entry.id {
+                    Some(id) => state.call(move |state| state.output_map.remove(&id)).await,
+                    None => None,
                 };
-                let res = res
-                    .json::<RunCommandResponse>()
-                    .await
-                    .map(|r| r.response)
-                    .map_err(Into::into);
-                tx.send(res).unwrap();
+                // The tool call already timed out and stopped waiting
+                let Some(tx) = tx else {
+                    continue;
+                };
+                // The owner explains these failures in the body
+                let explained =
+                    matches!(res.status(), StatusCode::BAD_GATEWAY | StatusCode::CONFLICT);
+                let res = if explained {
+                    Err(eyre!(res.text().await.unwrap_or_default()))
+                } else {
+                    match res.error_for_status() {
+                        Ok(res) => res.json::<RunCommandResponse>().await.map(|r| r.response),
+                        Err(e) => Err(e),
+                    }
+                    .map_err(Into::into)
+                };
+                tx.send(res.map_err(Into::into)).ok();
             } else {
                 tracing::error!("Failed to proxy: {res:?}");
             };
@@ -709,3 +8444,90 @@ pub async fn dud_proxy_loop(state: PackedState, exit: Receiver<()>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script_list(count: usize) -> String {
+        let scripts: Vec<Value> = (0..count)
+            .map(|index| {
+                json!({
+                    "path": format!("ServerScriptService.Script{index}"),
+                    "className": "Script",
+                    "enabled": true,
+                    "lineCount": 10,
+                    "byteSize": 200,
+                    "checksum": "abc",
+                })
+            })
+            .collect();
+        json!({"success": true, "count": count, "scripts": scripts}).to_string()
+    }
+
+    #[test]
+    fn typed_reply_keeps_structured_content_past_the_page_size() {
+        let reply = script_list(50);
+        let (result, kept) = first_page("list_scripts", reply.clone(), Uuid::new_v4(), 256);
+        assert!(kept.is_none());
+        assert_eq!(result.is_error, Some(false));
+        let structured = result.structured_content.expect("structured content");
+        assert_eq!(structured["scripts"].as_array().map(Vec::len), Some(50));
+        assert_eq!(
+            result.content[0].as_text().map(|text| text.text.as_str()),
+            Some(reply.as_str())
+        );
+    }
+
+    #[test]
+    fn text_reply_is_paged() {
+        let reply = "line\n".repeat(100);
+        let id = Uuid::new_v4();
+        let (result, kept) = first_page("run_code", reply.clone(), id, 64);
+        assert_eq!(kept, Some(reply));
+        assert_eq!(result.is_error, Some(false));
+        assert!(result.structured_content.is_none());
+        let text = &result.content[0].as_text().expect("text page").text;
+        assert!(text.starts_with(&"line\n".repeat(12)));
+        assert!(text.contains(&format!("token \"{id}:64\"")));
+    }
+
+    #[test]
+    fn failed_reply_is_paged_as_an_error() {
+        let reply = json!({"success": false, "error": "x".repeat(500)}).to_string();
+        let (result, kept) = first_page("list_scripts", reply, Uuid::new_v4(), 64);
+        assert!(kept.is_some());
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[test]
+    fn malformed_typed_reply_is_an_error() {
+        let reply = json!({"success": true, "count": "three"}).to_string();
+        let result = tool_result("list_scripts", reply.clone());
+        assert_eq!(result.is_error, Some(true));
+        assert!(result.structured_content.is_none());
+        let text = &result.content[0].as_text().expect("error text").text;
+        assert!(text.contains("list_scripts") && text.ends_with(&reply));
+
+        let result = tool_result("list_scripts", "Script ran".to_string());
+        assert_eq!(result.is_error, Some(true));
+        let (result, kept) = first_page("list_scripts", reply.repeat(10), Uuid::new_v4(), 64);
+        assert_eq!(result.is_error, Some(true));
+        assert!(kept.is_some_and(|text| text.starts_with("Studio's reply to list_scripts")));
+    }
+
+    #[test]
+    fn untyped_reply_is_text() {
+        let result = tool_result("run_code", "Script ran".to_string());
+        assert_eq!(result.is_error, Some(false));
+        assert!(result.structured_content.is_none());
+    }
+
+    #[test]
+    fn short_reply_is_returned_whole() {
+        let reply = script_list(1);
+        let (result, kept) = first_page("list_scripts", reply, Uuid::new_v4(), 64 * 1024);
+        assert!(kept.is_none());
+        assert!(result.structured_content.is_some());
+    }
+}
@@ -0,0 +1,117 @@
+// Renaming for `rename_symbol`. Names are matched as whole tokens, so strings, comments
+// and longer names that contain the old one are left alone, while the expressions in
+// interpolated strings are renamed like any other code. Scopes aren't tracked: every use
+// of the name is renamed, including fields of the same name on other tables.
+
+use crate::luau_tokens::{self, Token};
+use crate::tool_results::RenamedLine;
+
+pub struct Renamed {
+    pub source: String,
+    /// Uses of the name that were replaced
+    pub count: usize,
+    /// Each changed line once
+    pub lines: Vec<RenamedLine>,
+}
+
+/// Replaces every use of the name `old` in `source` with `new`, or `None` if it isn't used
+pub fn rename(source: &str, old: &str, new: &str) -> Option<Renamed> {
+    let spans: Vec<_> = luau_tokens::tokenize(source)
+        .into_iter()
+        .filter(|(token, _)| matches!(token, Token::Name(name) if name == old))
+        .map(|(_, span)| span)
+        .collect();
+    if spans.is_empty() {
+        return None;
+    }
+
+    let mut renamed = String::with_capacity(source.len());
+    let mut copied = 0;
+    for span in &spans {
+        renamed.push_str(&source[copied..span.start]);
+        renamed.push_str(new);
+        copied = span.end;
+    }
+    renamed.push_str(&source[copied..]);
+
+    // Names can't span lines, so both sources have their lines at the same numbers
+    let before: Vec<&str> = source.lines().collect();
+    let after: Vec<&str> = renamed.lines().collect();
+    let mut lines: Vec<RenamedLine> = Vec::new();
+    for span in &spans {
+        if lines.last().is_some_and(|line| line.line == span.line) {
+            continue;
+        }
+        let index = span.line as usize - 1;
+        lines.push(RenamedLine {
+            line: span.line,
+            before: before[index].to_string(),
+            after: after[index].to_string(),
+        });
+    }
+    Some(Renamed {
+        source: renamed,
+        count: spans.len(),
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_whole_names_only() {
+        let source =
+            "local count = 1\n-- count here\nprint(\"count\", count, counter, [[count]])\n";
+        let renamed = rename(source, "count", "total").unwrap();
+        assert_eq!(
+            renamed.source,
+            "local total = 1\n-- count here\nprint(\"count\", total, counter, [[count]])\n"
+        );
+        assert_eq!(renamed.count, 2);
+        let lines: Vec<u32> = renamed.lines.iter().map(|line| line.line).collect();
+        assert_eq!(lines, [1, 3]);
+        assert_eq!(
+            renamed.lines[1].before,
+            "print(\"count\", count, counter, [[count]])"
+        );
+        assert_eq!(
+            renamed.lines[1].after,
+            "print(\"count\", total, counter, [[count]])"
+        );
+    }
+
+    #[test]
+    fn lists_each_line_once() {
+        let renamed = rename("x = x + x\ny = x", "x", "value").unwrap();
+        assert_eq!(renamed.count, 4);
+        assert_eq!(renamed.lines.len(), 2);
+        assert_eq!(renamed.lines[0].after, "value = value + value");
+        assert_eq!(renamed.lines[1].line, 2);
+    }
+
+    #[test]
+    fn renames_fields_and_keeps_multibyte_text() {
+        let renamed = rename("local s = \"héllo\" t.name = name", "name", "label").unwrap();
+        assert_eq!(renamed.source, "local s = \"héllo\" t.label = label");
+    }
+
+    #[test]
+    fn unused_name_is_none() {
+        assert!(rename("local names = {} -- name", "name", "label").is_none());
+    }
+
+    #[test]
+    fn renames_inside_interpolated_strings() {
+        let renamed = rename(
+            "print(`{count} of {max(count, 1)}`, \"{count}\")",
+            "count",
+            "total",
+        );
+        assert_eq!(
+            renamed.unwrap().source,
+            "print(`{total} of {max(total, 1)}`, \"{count}\")"
+        );
+    }
+}
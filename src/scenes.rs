@@ -0,0 +1,267 @@
+// Snapshots taken by `save_scene`. The plugin uploads each one after saving it, and the
+// server keeps it as a new revision in a directory per scene under `scenes_dir`, so
+// `load_scene` can go back to earlier revisions even after Studio or the server restarts.
+
+use crate::config::{config_dir, Config};
+use crate::tool_results::{SceneMatch, SceneRevision, SceneSummary};
+use color_eyre::eyre::{OptionExt, Result, WrapErr};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+
+const SCENES_DIR_NAME: &str = "scenes";
+/// Scene the workspace is saved to before a tool removes anything from it, reserved so
+/// `save_scene` can't overwrite it
+pub const SAFETY_SCENE: &str = "safety-snapshot";
+/// Longest scene name, which is also its directory name
+const MAX_NAME_LEN: usize = 64;
+
+/// The parts of a saved scene listed with its revisions
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SceneHeader {
+    object_count: u64,
+    /// When Studio took the snapshot, Unix time in seconds
+    timestamp: Option<u64>,
+    description: Option<String>,
+    tags: Vec<String>,
+}
+
+/// Whether `name` can name a saved scene. Names become directory names, so only letters,
+/// digits, spaces, `-` and `_` are allowed.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_')
+}
+
+/// Where scenes are kept, `scenes_dir` from the config or `scenes` next to the config file
+pub fn scenes_dir(config: &Config) -> Result<PathBuf> {
+    if let Some(dir) = &config.scenes_dir {
+        return Ok(dir.clone());
+    }
+    let dir = config_dir().ok_or_eyre("Could not find a config directory for saved scenes")?;
+    Ok(dir.join(SCENES_DIR_NAME))
+}
+
+fn revision_path(dir: &Path, revision: u32) -> PathBuf {
+    dir.join(format!("{revision}.json"))
+}
+
+/// Directory holding the revisions of `name`. A scene saved before revisions were kept,
+/// a single `<name>.json`, becomes its first revision.
+async fn scene_dir(config: &Config, name: &str) -> Result<PathBuf> {
+    let scenes = scenes_dir(config)?;
+    let dir = scenes.join(name);
+    let legacy = scenes.join(format!("{name}.json"));
+    if fs::try_exists(&legacy).await? && !fs::try_exists(&dir).await? {
+        fs::create_dir_all(&dir)
+            .await
+            .wrap_err_with(|| format!("Could not create {}", dir.display()))?;
+        fs::rename(&legacy, revision_path(&dir, 1))
+            .await
+            .wrap_err_with(|| format!("Could not move {}", legacy.display()))?;
+    }
+    Ok(dir)
+}
+
+/// Revisions saved in `dir`, oldest first
+async fn revisions(dir: &Path) -> Result<Vec<u32>> {
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).wrap_err_with(|| format!("Could not read {}", dir.display())),
+    };
+    let mut revisions = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        if let Some(revision) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_suffix(".json"))
+            .and_then(|revision| revision.parse().ok())
+        {
+            revisions.push(revision);
+        }
+    }
+    revisions.sort_unstable();
+    Ok(revisions)
+}
+
+/// Saves `scene` as the next revision of `name` and returns its number. The oldest
+/// revisions are dropped past `max_scene_revisions`.
+pub async fn save(config: &Config, name: &str, scene: &Value) -> Result<u32> {
+    let dir = scene_dir(config, name).await?;
+    fs::create_dir_all(&dir)
+        .await
+        .wrap_err_with(|| format!("Could not create {}", dir.display()))?;
+    let revision = revisions(&dir).await?.last().map_or(1, |last| last + 1);
+    let path = revision_path(&dir, revision);
+    fs::write(&path, serde_json::to_vec(scene)?)
+        .await
+        .wrap_err_with(|| format!("Could not write {}", path.display()))?;
+    if config.max_scene_revisions > 0 {
+        prune(config, name, config.max_scene_revisions).await?;
+    }
+    Ok(revision)
+}
+
+/// Picks a saved revision: the latest when `revision` is `None`, that revision when it's
+/// positive, and that many revisions before the latest when it's zero or negative
+fn pick(revisions: &[u32], revision: Option<i64>) -> Option<u32> {
+    match revision {
+        None => revisions.last().copied(),
+        Some(revision) if revision > 0 => {
+            let revision = u32::try_from(revision).ok()?;
+            revisions.contains(&revision).then_some(revision)
+        }
+        Some(back) => {
+            let back = usize::try_from(back.unsigned_abs()).ok()?;
+            let index = revisions.len().checked_sub(1)?.checked_sub(back)?;
+            Some(revisions[index])
+        }
+    }
+}
+
+/// Number of the revision of `name` picked by `revision`, `None` if there isn't one
+pub async fn resolve(config: &Config, name: &str, revision: Option<i64>) -> Result<Option<u32>> {
+    let dir = scene_dir(config, name).await?;
+    Ok(pick(&revisions(&dir).await?, revision))
+}
+
+/// The revision of `name` picked by `revision` and its number, `None` if there isn't one
+pub async fn load(
+    config: &Config,
+    name: &str,
+    revision: Option<i64>,
+) -> Result<Option<(u32, Value)>> {
+    let Some(revision) = resolve(config, name, revision).await? else {
+        return Ok(None);
+    };
+    let path = revision_path(&scene_dir(config, name).await?, revision);
+    let contents = fs::read(&path)
+        .await
+        .wrap_err_with(|| format!("Could not read {}", path.display()))?;
+    let scene = serde_json::from_slice(&contents)
+        .wrap_err_with(|| format!("Invalid saved scene {}", path.display()))?;
+    Ok(Some((revision, scene)))
+}
+
+/// Every revision of `name`, oldest first
+pub async fn history(config: &Config, name: &str) -> Result<Vec<SceneRevision>> {
+    let dir = scene_dir(config, name).await?;
+    let mut history = Vec::new();
+    for revision in revisions(&dir).await? {
+        let path = revision_path(&dir, revision);
+        let Ok(contents) = fs::read(&path).await else {
+            continue;
+        };
+        let header: SceneHeader = serde_json::from_slice(&contents).unwrap_or_default();
+        let saved_at = fs::metadata(&path)
+            .await
+            .ok()
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_secs());
+        history.push(SceneRevision {
+            revision,
+            saved_at,
+            captured_at: header.timestamp,
+            object_count: header.object_count,
+            bytes: contents.len() as u64,
+            description: header.description,
+            tags: header.tags,
+        });
+    }
+    Ok(history)
+}
+
+/// Every saved scene with its revisions, sorted by name
+pub async fn list(config: &Config) -> Result<Vec<SceneSummary>> {
+    let dir = scenes_dir(config)?;
+    let mut entries = match fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).wrap_err_with(|| format!("Could not read {}", dir.display())),
+    };
+    let mut names = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        let name = file_name.strip_suffix(".json").unwrap_or(file_name);
+        if is_valid_name(name) && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    let mut scenes = Vec::new();
+    for name in names {
+        let revisions = history(config, &name).await?;
+        if !revisions.is_empty() {
+            scenes.push(SceneSummary { name, revisions });
+        }
+    }
+    Ok(scenes)
+}
+
+/// Deletes all but the newest `keep` revisions of `name`, all of them when `keep` is 0.
+/// Returns the revisions removed and the ones kept.
+pub async fn prune(config: &Config, name: &str, keep: usize) -> Result<(Vec<u32>, Vec<u32>)> {
+    let dir = scene_dir(config, name).await?;
+    let mut kept = revisions(&dir).await?;
+    let removed: Vec<u32> = kept.drain(..kept.len().saturating_sub(keep)).collect();
+    for &revision in &removed {
+        let path = revision_path(&dir, revision);
+        fs::remove_file(&path)
+            .await
+            .wrap_err_with(|| format!("Could not delete {}", path.display()))?;
+    }
+    if kept.is_empty() && fs::try_exists(&dir).await? {
+        fs::remove_dir(&dir)
+            .await
+            .wrap_err_with(|| format!("Could not delete {}", dir.display()))?;
+    }
+    Ok((removed, kept))
+}
+
+/// Saved scenes whose latest revision has every tag in `tags` and mentions `text` in its
+/// name, description or tags, ignoring case. Newest captures first.
+pub async fn find(config: &Config, text: Option<&str>, tags: &[String]) -> Result<Vec<SceneMatch>> {
+    let text = text.map(str::to_lowercase);
+    let mut matches = Vec::new();
+    for scene in list(config).await? {
+        let revisions = scene.revisions.len();
+        let Some(latest) = scene.revisions.into_iter().last() else {
+            continue;
+        };
+        let has_tags = tags
+            .iter()
+            .all(|tag| latest.tags.iter().any(|own| own.eq_ignore_ascii_case(tag)));
+        let mentions = text.as_ref().is_none_or(|text| {
+            scene.name.to_lowercase().contains(text)
+                || latest
+                    .description
+                    .as_ref()
+                    .is_some_and(|description| description.to_lowercase().contains(text))
+                || latest
+                    .tags
+                    .iter()
+                    .any(|tag| tag.to_lowercase().contains(text))
+        });
+        if has_tags && mentions {
+            matches.push(SceneMatch {
+                name: scene.name,
+                revisions,
+                latest,
+            });
+        }
+    }
+    matches
+        .sort_by_key(|scene| std::cmp::Reverse(scene.latest.captured_at.or(scene.latest.saved_at)));
+    Ok(matches)
+}
@@ -0,0 +1,256 @@
+// Edits for `patch_script`. Line edits and unified diffs are both turned into hunks
+// before the command is queued, so a malformed diff fails straight away and the plugin
+// only has to check and splice line ranges.
+
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+const NO_HUNKS: &str = "The diff has no hunks, they start with a line like '@@ -10,3 +10,4 @@'";
+
+/// Replacement of a range of lines, as given to `patch_script`
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+pub struct LineEdit {
+    #[schemars(description = "First line to replace, counting from 1")]
+    pub start_line: u32,
+    #[schemars(description = "Last line to replace. Use start_line - 1 to insert before start_line without replacing anything")]
+    pub end_line: u32,
+    #[schemars(description = "Lines to put in place of the range, an empty string removes them")]
+    pub text: String,
+}
+
+/// Replacement of a range of lines, as sent to the plugin
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Hunk {
+    /// First line replaced, counting from 1
+    start_line: u32,
+    /// Last line replaced, one before `start_line` when the hunk only inserts
+    end_line: u32,
+    lines: Vec<String>,
+    /// What the range must hold for the hunk to apply, taken from a diff's context and
+    /// removed lines
+    expected: Option<Vec<String>>,
+}
+
+/// Turns line edits into hunks, checking that their ranges make sense and don't overlap
+pub fn from_edits(edits: &[LineEdit]) -> Result<Vec<Hunk>, String> {
+    let hunks = edits
+        .iter()
+        .map(|edit| {
+            let after = edit.end_line.checked_add(1);
+            if edit.start_line == 0 || after.is_none_or(|after| after < edit.start_line) {
+                return Err(format!(
+                    "Lines {} to {} aren't a valid range, lines count from 1 and end_line can be at most one before start_line",
+                    edit.start_line, edit.end_line
+                ));
+            }
+            Ok(Hunk {
+                start_line: edit.start_line,
+                end_line: edit.end_line,
+                lines: split_lines(&edit.text),
+                expected: None,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    in_order(hunks)
+}
+
+/// Parses the hunks of a unified diff. Anything before the first `@@` header, like the
+/// `---` and `+++` file names, is skipped. Line counts in the headers are ignored since
+/// the hunk bodies say the same thing and hand-written diffs often get them wrong.
+pub fn parse_diff(diff: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks = Vec::new();
+    let mut current: Option<(u32, Vec<String>, Vec<String>)> = None;
+    for (number, line) in diff.lines().enumerate() {
+        if line.starts_with("@@") {
+            if let Some(hunk) = current.take() {
+                hunks.push(diff_hunk(hunk)?);
+            }
+            let Some(start) = old_start(line) else {
+                return Err(format!(
+                    "Line {} of the diff isn't a valid hunk header: {line}",
+                    number + 1
+                ));
+            };
+            current = Some((start, Vec::new(), Vec::new()));
+            continue;
+        }
+        let Some((_, old, new)) = current.as_mut() else {
+            continue;
+        };
+        let mut chars = line.chars();
+        let marker = chars.next();
+        let rest = chars.as_str();
+        match marker {
+            // Editors often strip the space from empty context lines
+            None => {
+                old.push(String::new());
+                new.push(String::new());
+            }
+            Some(' ') => {
+                old.push(rest.to_string());
+                new.push(rest.to_string());
+            }
+            Some('-') => old.push(rest.to_string()),
+            Some('+') => new.push(rest.to_string()),
+            // `\ No newline at end of file`
+            Some('\\') => {}
+            _ => {
+                return Err(format!(
+                    "Line {} of the diff doesn't start with ' ', '-' or '+': {line}",
+                    number + 1
+                ))
+            }
+        }
+    }
+    let Some(last) = current else {
+        return Err(NO_HUNKS.to_string());
+    };
+    hunks.push(diff_hunk(last)?);
+    in_order(hunks)
+}
+
+/// Start of the old range in a header like `@@ -10,3 +10,4 @@`
+fn old_start(header: &str) -> Option<u32> {
+    let range = header.split_whitespace().nth(1)?.strip_prefix('-')?;
+    range.split(',').next()?.parse().ok()
+}
+
+fn diff_hunk((start, old, new): (u32, Vec<String>, Vec<String>)) -> Result<Hunk, String> {
+    let too_long = || "A hunk runs past the last line a script can have".to_string();
+    // Hunks that only add lines give the line they come after
+    if old.is_empty() {
+        return Ok(Hunk {
+            start_line: start.checked_add(1).ok_or_else(too_long)?,
+            end_line: start,
+            lines: new,
+            expected: Some(old),
+        });
+    }
+    if start == 0 {
+        return Err("A hunk that removes or keeps lines can't start at line 0".to_string());
+    }
+    Ok(Hunk {
+        start_line: start,
+        end_line: u32::try_from(old.len() - 1)
+            .ok()
+            .and_then(|extra| start.checked_add(extra))
+            .ok_or_else(too_long)?,
+        lines: new,
+        expected: Some(old),
+    })
+}
+
+/// Sorts hunks by position, failing if any two touch the same lines
+fn in_order(mut hunks: Vec<Hunk>) -> Result<Vec<Hunk>, String> {
+    if hunks.is_empty() {
+        return Err("There are no edits to apply".to_string());
+    }
+    hunks.sort_by_key(|hunk| (hunk.start_line, hunk.end_line));
+    for pair in hunks.windows(2) {
+        if pair[1].start_line <= pair[0].end_line || pair[1].start_line == pair[0].start_line {
+            return Err(format!(
+                "The edits of lines {}-{} and {}-{} overlap, combine them into one",
+                pair[0].start_line, pair[0].end_line, pair[1].start_line, pair[1].end_line
+            ));
+        }
+    }
+    Ok(hunks)
+}
+
+/// Lines of an edit's text, ignoring one trailing newline so `"a\nb\n"` is two lines
+fn split_lines(text: &str) -> Vec<String> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    text.split('\n').map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(start_line: u32, end_line: u32, text: &str) -> LineEdit {
+        LineEdit {
+            start_line,
+            end_line,
+            text: text.to_string(),
+        }
+    }
+
+    fn strings(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn diff_hunk_covers_old_lines() {
+        let hunks =
+            parse_diff("--- a\n+++ b\n@@ -3,2 +3,3 @@\n keep\n-old\n+new\n+more\n").unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].start_line, hunks[0].end_line), (3, 4));
+        assert_eq!(hunks[0].lines, strings(&["keep", "new", "more"]));
+        assert_eq!(hunks[0].expected, Some(strings(&["keep", "old"])));
+    }
+
+    #[test]
+    fn diff_insertion_goes_after_its_line() {
+        let hunks = parse_diff("@@ -5,0 +6,1 @@\n+added\n").unwrap();
+        assert_eq!((hunks[0].start_line, hunks[0].end_line), (6, 5));
+        assert_eq!(hunks[0].expected, Some(Vec::new()));
+    }
+
+    #[test]
+    fn diff_hunks_are_sorted() {
+        let hunks = parse_diff("@@ -20 +20 @@\n-b\n+B\n@@ -2 +2 @@\n-a\n+A\n").unwrap();
+        let ranges: Vec<_> = hunks
+            .iter()
+            .map(|hunk| (hunk.start_line, hunk.end_line))
+            .collect();
+        assert_eq!(ranges, [(2, 2), (20, 20)]);
+    }
+
+    #[test]
+    fn diff_keeps_stripped_empty_context() {
+        let hunks = parse_diff("@@ -1,3 +1,3 @@\n a\n\n-b\n+c\n").unwrap();
+        assert_eq!(hunks[0].expected, Some(strings(&["a", "", "b"])));
+        assert_eq!(hunks[0].lines, strings(&["a", "", "c"]));
+    }
+
+    #[test]
+    fn malformed_diffs_are_rejected() {
+        assert_eq!(parse_diff("just text\n").unwrap_err(), NO_HUNKS);
+        assert!(parse_diff("@@ bogus @@\n a\n").is_err());
+        assert!(parse_diff("@@ -0,1 +0,1 @@\n-a\n").is_err());
+        assert!(parse_diff("@@ -1 +1 @@\n*a\n").is_err());
+        // A multibyte first character isn't taken for an empty context line
+        assert!(parse_diff("@@ -1 +1 @@\né\n").is_err());
+        assert!(parse_diff(&format!("@@ -{} +1 @@\n+a\n", u32::MAX)).is_err());
+        assert!(parse_diff(&format!("@@ -{} +1 @@\n a\n b\n", u32::MAX)).is_err());
+    }
+
+    #[test]
+    fn edits_become_hunks() {
+        let hunks = from_edits(&[edit(10, 12, "x\ny\n"), edit(3, 2, "inserted")]).unwrap();
+        assert_eq!((hunks[0].start_line, hunks[0].end_line), (3, 2));
+        assert_eq!(hunks[0].lines, strings(&["inserted"]));
+        assert_eq!((hunks[1].start_line, hunks[1].end_line), (10, 12));
+        assert_eq!(hunks[1].lines, strings(&["x", "y"]));
+        assert_eq!(hunks[1].expected, None);
+    }
+
+    #[test]
+    fn invalid_edit_ranges_are_rejected() {
+        assert!(from_edits(&[]).is_err());
+        assert!(from_edits(&[edit(0, 1, "a")]).is_err());
+        assert!(from_edits(&[edit(5, 3, "a")]).is_err());
+        assert!(from_edits(&[edit(u32::MAX, u32::MAX, "a")]).is_err());
+    }
+
+    #[test]
+    fn overlapping_edits_are_rejected() {
+        assert!(from_edits(&[edit(1, 5, "a"), edit(5, 6, "b")]).is_err());
+        assert!(from_edits(&[edit(4, 3, "a"), edit(4, 3, "b")]).is_err());
+        assert!(from_edits(&[edit(4, 3, "a"), edit(4, 4, "b")]).is_err());
+        assert!(from_edits(&[edit(1, 4, "a"), edit(5, 6, "b")]).is_ok());
+    }
+}
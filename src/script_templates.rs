@@ -0,0 +1,116 @@
+// Starting points for `create_script`. `{name}` in a template is replaced by the name of
+// the script being created, made into a valid Luau identifier.
+
+pub struct ScriptTemplate {
+    pub name: &'static str,
+    /// Class created when the call doesn't pick one
+    pub class_name: &'static str,
+    source: &'static str,
+}
+
+pub const SCRIPT_TEMPLATES: &[ScriptTemplate] = &[
+    ScriptTemplate {
+        name: "module",
+        class_name: "ModuleScript",
+        source: r#"local {name} = {}
+
+return {name}
+"#,
+    },
+    ScriptTemplate {
+        name: "class",
+        class_name: "ModuleScript",
+        source: r#"local {name} = {}
+{name}.__index = {name}
+
+export type {name} = typeof(setmetatable({} :: {}, {name}))
+
+function {name}.new(): {name}
+	local self = setmetatable({}, {name})
+	return self
+end
+
+function {name}.Destroy(self: {name}) end
+
+return {name}
+"#,
+    },
+    ScriptTemplate {
+        name: "server",
+        class_name: "Script",
+        source: r#"local Players = game:GetService("Players")
+
+local function onPlayerAdded(player: Player) end
+
+Players.PlayerAdded:Connect(onPlayerAdded)
+for _, player in Players:GetPlayers() do
+	task.spawn(onPlayerAdded, player)
+end
+"#,
+    },
+    ScriptTemplate {
+        name: "client",
+        class_name: "LocalScript",
+        source: r#"local Players = game:GetService("Players")
+
+local player = Players.LocalPlayer
+local character = player.Character or player.CharacterAdded:Wait()
+"#,
+    },
+    ScriptTemplate {
+        name: "remote_event_handler",
+        class_name: "Script",
+        source: r#"local ReplicatedStorage = game:GetService("ReplicatedStorage")
+
+local remote = Instance.new("RemoteEvent")
+remote.Name = "{name}"
+remote.Parent = ReplicatedStorage
+
+remote.OnServerEvent:Connect(function(player: Player, ...) end)
+"#,
+    },
+    ScriptTemplate {
+        name: "remote_function_handler",
+        class_name: "Script",
+        source: r#"local ReplicatedStorage = game:GetService("ReplicatedStorage")
+
+local remote = Instance.new("RemoteFunction")
+remote.Name = "{name}"
+remote.Parent = ReplicatedStorage
+
+remote.OnServerInvoke = function(player: Player, ...)
+	return nil
+end
+"#,
+    },
+];
+
+/// The template called `name`, or a message listing the ones there are
+pub fn find(name: &str) -> Result<&'static ScriptTemplate, String> {
+    SCRIPT_TEMPLATES
+        .iter()
+        .find(|template| template.name == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = SCRIPT_TEMPLATES
+                .iter()
+                .map(|template| template.name)
+                .collect();
+            format!(
+                "There is no {name} template, use one of {}",
+                names.join(", ")
+            )
+        })
+}
+
+impl ScriptTemplate {
+    pub fn render(&self, script_name: &str) -> String {
+        let mut identifier: String = script_name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+        if !identifier.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            identifier.insert(0, '_');
+        }
+        self.source.replace("{name}", &identifier)
+    }
+}
@@ -0,0 +1,100 @@
+// Skies `set_skybox` can apply by name instead of six asset IDs. Textures are content URLs,
+// so a preset can use the textures that ship with Studio as well as uploaded images.
+
+use std::collections::BTreeMap;
+
+/// Sky properties holding the six faces, in the order presets list them
+const FACE_PROPERTIES: [&str; 6] = [
+    "SkyboxBk", "SkyboxDn", "SkyboxFt", "SkyboxLf", "SkyboxRt", "SkyboxUp",
+];
+
+pub struct SkyPreset {
+    pub name: &'static str,
+    /// Back, down, front, left, right and up
+    faces: [&'static str; 6],
+    sun_texture: &'static str,
+    moon_texture: &'static str,
+}
+
+pub const SKY_PRESETS: &[SkyPreset] = &[
+    SkyPreset {
+        name: "default",
+        faces: [
+            "rbxassetid://6444884337",
+            "rbxassetid://6444884785",
+            "rbxassetid://6444884337",
+            "rbxassetid://6444884337",
+            "rbxassetid://6444884337",
+            "rbxassetid://6412503613",
+        ],
+        sun_texture: "rbxassetid://6196665106",
+        moon_texture: "rbxassetid://6444320592",
+    },
+    SkyPreset {
+        name: "classic",
+        faces: [
+            "rbxasset://textures/sky/sky512_bk.tex",
+            "rbxasset://textures/sky/sky512_dn.tex",
+            "rbxasset://textures/sky/sky512_ft.tex",
+            "rbxasset://textures/sky/sky512_lf.tex",
+            "rbxasset://textures/sky/sky512_rt.tex",
+            "rbxasset://textures/sky/sky512_up.tex",
+        ],
+        sun_texture: "rbxasset://sky/sun.jpg",
+        moon_texture: "rbxasset://sky/moon.jpg",
+    },
+];
+
+impl SkyPreset {
+    /// The Sky texture properties the preset sets, keyed by property name
+    pub fn textures(&self) -> BTreeMap<String, String> {
+        let mut textures: BTreeMap<_, _> = FACE_PROPERTIES
+            .iter()
+            .zip(self.faces)
+            .map(|(property, face)| (property.to_string(), face.to_string()))
+            .collect();
+        textures.insert("SunTextureId".to_string(), self.sun_texture.to_string());
+        textures.insert("MoonTextureId".to_string(), self.moon_texture.to_string());
+        textures
+    }
+}
+
+/// The face properties of a Sky set to `faces`, given back, down, front, left, right and up
+pub fn faces(faces: [&str; 6]) -> Result<BTreeMap<String, String>, String> {
+    FACE_PROPERTIES
+        .iter()
+        .zip(faces)
+        .map(|(property, face)| Ok((property.to_string(), content_url(face)?)))
+        .collect()
+}
+
+pub fn find(name: &str) -> Result<&'static SkyPreset, String> {
+    SKY_PRESETS
+        .iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = SKY_PRESETS.iter().map(|preset| preset.name).collect();
+            format!("There is no {name} sky, use one of {}", names.join(", "))
+        })
+}
+
+/// Content URL of a texture given as an asset ID or a URL. An empty texture stays empty,
+/// which leaves that part of the sky blank.
+pub fn content_url(texture: &str) -> Result<String, String> {
+    let texture = texture.trim();
+    if !texture.is_empty() && texture.bytes().all(|byte| byte.is_ascii_digit()) {
+        Ok(format!("rbxassetid://{texture}"))
+    } else if texture.is_empty()
+        || texture.starts_with("rbxassetid://")
+        || texture.starts_with("rbxasset://")
+        || texture.starts_with("rbxthumb://")
+        || texture.starts_with("http://")
+        || texture.starts_with("https://")
+    {
+        Ok(texture.to_string())
+    } else {
+        Err(format!(
+            "{texture} isn't an asset ID or a content URL like rbxassetid://123"
+        ))
+    }
+}
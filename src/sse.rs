@@ -0,0 +1,123 @@
+use crate::config::Config;
+use crate::rbx_studio_server::{PackedState, RBXStudioServer};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::Json;
+use futures::channel::mpsc;
+use futures::{stream, Stream, StreamExt};
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use rmcp::ServiceExt;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+const SSE_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+type SessionMap = Arc<Mutex<HashMap<Uuid, mpsc::UnboundedSender<ClientJsonRpcMessage>>>>;
+
+#[derive(Clone)]
+struct SseState {
+    app_state: PackedState,
+    config: Arc<Config>,
+    sessions: SessionMap,
+    shutdown: CancellationToken,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageQuery {
+    session_id: Uuid,
+}
+
+/// Removes the session once the client drops the event stream, which closes the
+/// inbound channel and lets the MCP service for that session shut down.
+struct SessionGuard {
+    id: Uuid,
+    sessions: SessionMap,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        let id = self.id;
+        let sessions = Arc::clone(&self.sessions);
+        tokio::spawn(async move {
+            sessions.lock().await.remove(&id);
+        });
+    }
+}
+
+/// Routes for the legacy HTTP+SSE MCP transport: clients open `GET /sse`, receive an
+/// `endpoint` event, and POST their JSON-RPC messages to `/message?sessionId=...`.
+pub fn router(
+    app_state: PackedState,
+    config: Arc<Config>,
+    shutdown: CancellationToken,
+) -> axum::Router {
+    axum::Router::new()
+        .route("/sse", get(sse_handler))
+        .route("/message", post(message_handler))
+        .with_state(SseState {
+            app_state,
+            config,
+            sessions: Arc::default(),
+            shutdown,
+        })
+}
+
+async fn sse_handler(
+    State(state): State<SseState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let id = Uuid::new_v4();
+    let (client_tx, client_rx) = mpsc::unbounded::<ClientJsonRpcMessage>();
+    let (server_tx, server_rx) = mpsc::unbounded::<ServerJsonRpcMessage>();
+    state.sessions.lock().await.insert(id, client_tx);
+    tracing::info!("SSE client connected: {id}");
+
+    let server = RBXStudioServer::new(state.app_state, state.config);
+    tokio::spawn(async move {
+        match server.serve((server_tx, client_rx)).await {
+            Ok(service) => {
+                service.waiting().await.ok();
+            }
+            Err(e) => tracing::error!("SSE session {id} failed to initialize: {e:?}"),
+        }
+        tracing::info!("SSE client disconnected: {id}");
+    });
+
+    let guard = SessionGuard {
+        id,
+        sessions: state.sessions,
+    };
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("/message?sessionId={id}"));
+    let messages = server_rx
+        .map(move |message| {
+            let _guard = &guard;
+            Event::default().event("message").json_data(message)
+        })
+        .take_until(state.shutdown.cancelled_owned());
+
+    Sse::new(stream::once(async { Ok(endpoint) }).chain(messages))
+        .keep_alive(KeepAlive::new().interval(SSE_KEEP_ALIVE_INTERVAL))
+}
+
+async fn message_handler(
+    State(state): State<SseState>,
+    Query(query): Query<MessageQuery>,
+    Json(message): Json<ClientJsonRpcMessage>,
+) -> impl IntoResponse {
+    let tx = state.sessions.lock().await.get(&query.session_id).cloned();
+    match tx {
+        Some(tx) if tx.unbounded_send(message).is_ok() => StatusCode::ACCEPTED,
+        Some(_) => StatusCode::GONE,
+        None => StatusCode::NOT_FOUND,
+    }
+}
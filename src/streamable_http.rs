@@ -0,0 +1,38 @@
+use crate::config::Config;
+use crate::rbx_studio_server::{PackedState, RBXStudioServer};
+use rmcp::transport::streamable_http_server::{
+    session::local::{LocalSessionManager, SessionConfig},
+    StreamableHttpServerConfig, StreamableHttpService,
+};
+use std::sync::Arc;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// How long a session survives without any client activity. Clients that reconnect
+/// within this window resume their session, including tool calls still waiting on Studio.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Routes for the Streamable HTTP MCP transport, served at `/mcp`. Every session gets
+/// its own `RBXStudioServer` backed by the shared plugin queue.
+pub fn router(
+    app_state: PackedState,
+    config: Arc<Config>,
+    shutdown: CancellationToken,
+) -> axum::Router {
+    let session_manager = LocalSessionManager {
+        session_config: SessionConfig {
+            keep_alive: Some(SESSION_IDLE_TIMEOUT),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let service = StreamableHttpService::new(
+        move || Ok(RBXStudioServer::new(app_state.clone(), Arc::clone(&config))),
+        Arc::new(session_manager),
+        StreamableHttpServerConfig {
+            cancellation_token: shutdown,
+            ..Default::default()
+        },
+    );
+    axum::Router::new().nest_service("/mcp", service)
+}
@@ -0,0 +1,594 @@
+// Rooms for `build_structure` and buildings for `generate_building`. Walls are cut into
+// plain blocks around their doors and windows before the command is queued, so an opening
+// that doesn't fit fails straight away and the plugin only has to create and group the
+// blocks.
+
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+pub const WALLS: &[&str] = &["front", "back", "left", "right"];
+pub const OPENING_KINDS: &[&str] = &["door", "window"];
+/// Height of a window's bottom edge above the floor when none is given
+const DEFAULT_WINDOW_SILL: f64 = 3.0;
+/// Blocks thinner than this along the wall are left out rather than made as slivers
+const MIN_PIECE_LENGTH: f64 = 0.01;
+pub const MAX_BUILDING_FLOORS: u32 = 40;
+/// Most blocks `generate_building` sends Studio, which small window spacings can run up
+const MAX_BUILDING_PIECES: usize = 10_000;
+/// Tallest step of a building's stairs, and how deep each step is
+const MAX_STEP_RISE: f64 = 1.0;
+const STEP_TREAD: f64 = 1.5;
+const GLASS_THICKNESS: f64 = 0.2;
+/// Windows keep at least this far from the sides of the door
+const DOOR_MARGIN: f64 = 1.0;
+
+/// A door or window cut out of a wall, as given to `build_structure`
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+pub struct Opening {
+    #[schemars(description = "Wall to cut it out of: front (-z), back (+z), left (-x) or right (+x)")]
+    pub wall: String,
+    #[schemars(description = "door (default) or window")]
+    pub kind: Option<String>,
+    #[schemars(description = "Distance of its centre from the middle of the wall, along +x on the front and back walls and +z on the left and right walls (default: 0)")]
+    pub offset: Option<f64>,
+    #[schemars(description = "Width in studs")]
+    pub width: f64,
+    #[schemars(description = "Height in studs")]
+    pub height: f64,
+    #[schemars(description = "Height of its bottom edge above the floor (default: 0 for doors, 3 for windows)")]
+    pub bottom: Option<f64>,
+}
+
+/// Windows set into every wall of every storey, as given to `generate_building`
+#[derive(Debug, Default, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+pub struct WindowSpec {
+    #[schemars(description = "Width of each window in studs (default: 4)")]
+    pub width: Option<f64>,
+    #[schemars(description = "Height of each window in studs (default: 5)")]
+    pub height: Option<f64>,
+    #[schemars(description = "Height of the bottom edge above each floor (default: 3)")]
+    pub sill: Option<f64>,
+    #[schemars(description = "Distance between the centres of neighbouring windows along a wall (default: 10)")]
+    pub spacing: Option<f64>,
+}
+
+/// The front door of a building, as given to `generate_building`
+#[derive(Debug, Default, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+pub struct DoorSpec {
+    #[schemars(description = "Wall it is in: front (-z), back (+z), left (-x) or right (+x) (default: front)")]
+    pub wall: Option<String>,
+    #[schemars(description = "Width in studs (default: 6)")]
+    pub width: Option<f64>,
+    #[schemars(description = "Height in studs (default: 8)")]
+    pub height: Option<f64>,
+    #[schemars(description = "Distance of its centre from the middle of the wall, along +x on the front and back walls and +z on the left and right walls (default: 0)")]
+    pub offset: Option<f64>,
+}
+
+/// A block of the structure, as sent to the plugin. Positions are of the block's centre
+/// relative to the middle of the footprint at ground level.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Piece {
+    name: String,
+    /// What the block is, `wall`, `floor`, `ceiling`, `roof`, `stairs` or `window`, which
+    /// picks its material from a building's palette
+    role: String,
+    /// Model inside the structure to put the block in, the structure itself when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    size: [f64; 3],
+    position: [f64; 3],
+}
+
+impl Piece {
+    fn new(name: impl Into<String>, role: &str, size: [f64; 3], position: [f64; 3]) -> Self {
+        Piece {
+            name: name.into(),
+            role: role.to_string(),
+            group: None,
+            size,
+            position,
+        }
+    }
+
+    /// The block between two opposite corners
+    fn between(name: impl Into<String>, role: &str, low: [f64; 3], high: [f64; 3]) -> Self {
+        let size = [0, 1, 2].map(|axis| high[axis] - low[axis]);
+        let position = [0, 1, 2].map(|axis| (low[axis] + high[axis]) / 2.0);
+        Piece::new(name, role, size, position)
+    }
+}
+
+/// Outer dimensions and parts of a rectangular room
+pub struct Room<'a> {
+    pub width: f64,
+    pub depth: f64,
+    /// Height of the walls, from the top of the floor to the bottom of the ceiling
+    pub height: f64,
+    pub thickness: f64,
+    pub floor: bool,
+    pub ceiling: bool,
+    pub walls: &'a [String],
+    pub openings: &'a [Opening],
+}
+
+/// A wall laid out along one axis, `along_x` for the front and back walls
+struct Wall {
+    name: &'static str,
+    along_x: bool,
+    /// Where the wall sits on the other axis
+    across: f64,
+    /// Extent along the wall, the front and back walls cover the corners
+    start: f64,
+    end: f64,
+}
+
+impl Room<'_> {
+    fn wall(&self, wall: &str) -> Wall {
+        let (half_width, half_depth) = (self.width / 2.0, self.depth / 2.0);
+        let inset = self.thickness / 2.0;
+        let (name, along_x, across) = match wall {
+            "front" => ("Front", true, -half_depth + inset),
+            "back" => ("Back", true, half_depth - inset),
+            "left" => ("Left", false, -half_width + inset),
+            _ => ("Right", false, half_width - inset),
+        };
+        let (start, end) = if along_x {
+            (-half_width, half_width)
+        } else {
+            (-half_depth + self.thickness, half_depth - self.thickness)
+        };
+        Wall {
+            name,
+            along_x,
+            across,
+            start,
+            end,
+        }
+    }
+
+    /// Cuts the room into blocks, checking that every opening fits its wall and that
+    /// openings on the same wall don't overlap
+    pub fn pieces(&self) -> Result<Vec<Piece>, String> {
+        if self.thickness <= 0.0 {
+            return Err("thickness must be more than 0".to_string());
+        }
+        if self.height <= 0.0 {
+            return Err("height must be more than 0".to_string());
+        }
+        if self.width <= self.thickness * 2.0 || self.depth <= self.thickness * 2.0 {
+            return Err("width and depth must be more than twice the thickness".to_string());
+        }
+        if let Some(wall) = self
+            .walls
+            .iter()
+            .find(|wall| !WALLS.contains(&wall.as_str()))
+        {
+            return Err(format!(
+                "Unknown wall {wall}, walls are {}",
+                WALLS.join(", ")
+            ));
+        }
+
+        let mut pieces = Vec::new();
+        let base = if self.floor { self.thickness } else { 0.0 };
+        if self.floor {
+            pieces.push(Piece::new(
+                "Floor",
+                "floor",
+                [self.width, self.thickness, self.depth],
+                [0.0, self.thickness / 2.0, 0.0],
+            ));
+        }
+
+        for (index, opening) in self.openings.iter().enumerate() {
+            if !self.walls.contains(&opening.wall) {
+                return Err(format!(
+                    "Opening {} is in the {} wall, which isn't being built",
+                    index + 1,
+                    opening.wall
+                ));
+            }
+            if opening
+                .kind
+                .as_deref()
+                .is_some_and(|kind| !OPENING_KINDS.contains(&kind))
+            {
+                return Err(format!(
+                    "Opening {}: kind must be one of {}",
+                    index + 1,
+                    OPENING_KINDS.join(", ")
+                ));
+            }
+        }
+
+        for wall_name in WALLS {
+            if !self.walls.iter().any(|wall| wall == wall_name) {
+                continue;
+            }
+            let wall = self.wall(wall_name);
+            // Openings stay clear of the corners so the walls still meet
+            let (first, last) = if wall.along_x {
+                (wall.start + self.thickness, wall.end - self.thickness)
+            } else {
+                (wall.start, wall.end)
+            };
+            let mut cuts = Vec::new();
+            for (index, opening) in self.openings.iter().enumerate() {
+                if opening.wall != *wall_name {
+                    continue;
+                }
+                let number = index + 1;
+                let bottom = opening.bottom.unwrap_or(match opening.kind.as_deref() {
+                    Some("window") => DEFAULT_WINDOW_SILL,
+                    _ => 0.0,
+                });
+                let centre = opening.offset.unwrap_or(0.0);
+                let (low, high) = (centre - opening.width / 2.0, centre + opening.width / 2.0);
+                if opening.width <= 0.0 || opening.height <= 0.0 {
+                    return Err(format!(
+                        "Opening {number}: width and height must be more than 0"
+                    ));
+                }
+                if bottom < 0.0 || bottom + opening.height > self.height {
+                    return Err(format!(
+                        "Opening {number} reaches from {bottom} to {} studs up, outside the {} stud walls",
+                        bottom + opening.height,
+                        self.height
+                    ));
+                }
+                if low < first || high > last {
+                    return Err(format!(
+                        "Opening {number} runs past the end of the {} wall",
+                        opening.wall
+                    ));
+                }
+                cuts.push((low, high, bottom, bottom + opening.height, number));
+            }
+            cuts.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for pair in cuts.windows(2) {
+                if pair[1].0 < pair[0].1 {
+                    return Err(format!(
+                        "Openings {} and {} overlap in the {} wall",
+                        pair[0].4, pair[1].4, wall_name
+                    ));
+                }
+            }
+
+            let mut block = |name: String, from: f64, to: f64, low: f64, high: f64| {
+                if to - from < MIN_PIECE_LENGTH || high - low < MIN_PIECE_LENGTH {
+                    return;
+                }
+                let (middle, length) = ((from + to) / 2.0, to - from);
+                let (y, height) = (base + (low + high) / 2.0, high - low);
+                let (size, position) = if wall.along_x {
+                    ([length, height, self.thickness], [middle, y, wall.across])
+                } else {
+                    ([self.thickness, height, length], [wall.across, y, middle])
+                };
+                pieces.push(Piece::new(name, "wall", size, position));
+            };
+            let mut cursor = wall.start;
+            for &(low, high, bottom, top, _) in &cuts {
+                block(format!("{}Wall", wall.name), cursor, low, 0.0, self.height);
+                block(format!("{}Sill", wall.name), low, high, 0.0, bottom);
+                block(format!("{}Lintel", wall.name), low, high, top, self.height);
+                cursor = high;
+            }
+            block(
+                format!("{}Wall", wall.name),
+                cursor,
+                wall.end,
+                0.0,
+                self.height,
+            );
+        }
+
+        if self.ceiling {
+            pieces.push(Piece::new(
+                "Ceiling",
+                "ceiling",
+                [self.width, self.thickness, self.depth],
+                [0.0, base + self.height + self.thickness / 2.0, 0.0],
+            ));
+        }
+        Ok(pieces)
+    }
+}
+
+/// A building of storeys stacked on a rectangular footprint
+pub struct Building<'a> {
+    pub width: f64,
+    pub depth: f64,
+    pub floors: u32,
+    /// Height of each storey's walls, from its floor to the slab above
+    pub floor_height: f64,
+    pub thickness: f64,
+    pub stairs: bool,
+    pub stair_width: f64,
+    pub windows: &'a WindowSpec,
+    pub door: &'a DoorSpec,
+}
+
+impl Building<'_> {
+    /// Cuts the building into blocks: a slab and walls for each storey, with the upper
+    /// slabs open over the stairs, and a roof. The stairs switch back between two lanes
+    /// along the right wall, so each flight lands beside the start of the next.
+    pub fn pieces(&self) -> Result<Vec<Piece>, String> {
+        let thickness = self.thickness;
+        let window_width = self.windows.width.unwrap_or(4.0);
+        let window_height = self.windows.height.unwrap_or(5.0);
+        let sill = self.windows.sill.unwrap_or(3.0);
+        let spacing = self.windows.spacing.unwrap_or(10.0);
+        let door_wall = self.door.wall.as_deref().unwrap_or("front");
+        let door_width = self.door.width.unwrap_or(6.0);
+        let door_height = self.door.height.unwrap_or(8.0);
+        let door_offset = self.door.offset.unwrap_or(0.0);
+        let all_walls: Vec<String> = WALLS.iter().map(|wall| wall.to_string()).collect();
+        // Length of each wall inside the corners, where openings can go
+        let inner = |wall: &str| match wall {
+            "front" | "back" => self.width - thickness * 2.0,
+            _ => self.depth - thickness * 2.0,
+        };
+
+        if !(1..=MAX_BUILDING_FLOORS).contains(&self.floors) {
+            return Err(format!(
+                "floors must be between 1 and {MAX_BUILDING_FLOORS}"
+            ));
+        }
+        if self.floor_height <= 0.0 || thickness <= 0.0 {
+            return Err("floor_height and wall_thickness must be more than 0".to_string());
+        }
+        if self.width <= thickness * 2.0 || self.depth <= thickness * 2.0 {
+            return Err("width and depth must be more than twice the wall thickness".to_string());
+        }
+        if window_width <= 0.0 || window_height <= 0.0 || sill < 0.0 {
+            return Err(
+                "Windows need a width and height more than 0 and a sill of at least 0".to_string(),
+            );
+        }
+        if window_width >= spacing {
+            return Err("Windows must be narrower than their spacing".to_string());
+        }
+        if sill + window_height > self.floor_height {
+            return Err(format!(
+                "Windows reach {} studs up, past the {} stud storeys",
+                sill + window_height,
+                self.floor_height
+            ));
+        }
+        if !WALLS.contains(&door_wall) {
+            return Err(format!(
+                "The door's wall must be one of {}",
+                WALLS.join(", ")
+            ));
+        }
+        if door_width <= 0.0 || door_height <= 0.0 || door_height > self.floor_height {
+            return Err(format!(
+                "The door must be more than 0 wide and between 0 and {} studs tall",
+                self.floor_height
+            ));
+        }
+        if door_offset.abs() + door_width / 2.0 > inner(door_wall) / 2.0 {
+            return Err(format!(
+                "The door runs past the end of the {door_wall} wall"
+            ));
+        }
+
+        // Floor to floor, a storey's walls and the slab above them
+        let storey = self.floor_height + thickness;
+        let steps = (storey / MAX_STEP_RISE).ceil() as usize;
+        let run = steps as f64 * STEP_TREAD;
+        let stairs = self.stairs && self.floors > 1;
+        if stairs
+            && (self.stair_width * 2.0 > inner("front")
+                || run + self.stair_width * 2.0 > inner("left"))
+        {
+            return Err(format!(
+                "The stairs need {} by {} studs inside the walls. Make the footprint bigger, the stairs narrower or the storeys lower, or leave the stairs out",
+                self.stair_width * 2.0,
+                run + self.stair_width * 2.0
+            ));
+        }
+        // Two lanes against the right wall, up towards +z in the first and back down the
+        // second, with a landing the width of the stairs at both ends
+        let right = self.width / 2.0 - thickness;
+        let lanes = [
+            [right - self.stair_width * 2.0, right - self.stair_width],
+            [right - self.stair_width, right],
+        ];
+        let start = -self.depth / 2.0 + thickness + self.stair_width;
+        let end = start + run;
+
+        // Every window puts a glass pane on every floor, so the panes alone are a lower
+        // bound on the part count, checked before any piece is laid out
+        let counts: Vec<usize> = WALLS
+            .iter()
+            .map(|wall| (inner(wall) / spacing).floor() as usize)
+            .collect();
+        let panes = counts
+            .iter()
+            .fold(0usize, |total, count| total.saturating_add(*count))
+            .saturating_mul(self.floors as usize);
+        if panes > MAX_BUILDING_PIECES {
+            return Err(format!(
+                "The building would take at least {panes} parts, more than {MAX_BUILDING_PIECES}. Space the windows further apart or build fewer floors"
+            ));
+        }
+
+        let mut windows = Vec::new();
+        for (wall, &count) in WALLS.iter().zip(&counts) {
+            for index in 0..count {
+                let offset = (index as f64 - (count as f64 - 1.0) / 2.0) * spacing;
+                windows.push(Opening {
+                    wall: wall.to_string(),
+                    kind: Some("window".to_string()),
+                    offset: Some(offset),
+                    width: window_width,
+                    height: window_height,
+                    bottom: Some(sill),
+                });
+            }
+        }
+
+        let mut pieces = Vec::new();
+        for level in 0..self.floors {
+            let group = format!("Floor{}", level + 1);
+            let bottom = f64::from(level) * storey;
+            let top = bottom + thickness;
+            let mut storey_pieces = Vec::new();
+
+            // The slab is open over the lane of the flight coming up from below
+            let hole =
+                (stairs && level > 0).then(|| (lanes[(level as usize - 1) % 2], [start, end]));
+            storey_pieces.extend(self.slab("Floor", "floor", bottom, top, hole));
+
+            let openings: Vec<Opening> = if level == 0 {
+                let door = Opening {
+                    wall: door_wall.to_string(),
+                    kind: Some("door".to_string()),
+                    offset: Some(door_offset),
+                    width: door_width,
+                    height: door_height,
+                    bottom: Some(0.0),
+                };
+                let (low, high) = (
+                    door_offset - door_width / 2.0 - DOOR_MARGIN,
+                    door_offset + door_width / 2.0 + DOOR_MARGIN,
+                );
+                std::iter::once(door)
+                    .chain(
+                        windows
+                            .iter()
+                            .filter(|window| {
+                                let offset = window.offset.unwrap_or(0.0);
+                                window.wall != door_wall
+                                    || offset + window.width / 2.0 <= low
+                                    || offset - window.width / 2.0 >= high
+                            })
+                            .cloned(),
+                    )
+                    .collect()
+            } else {
+                windows.clone()
+            };
+            let room = Room {
+                width: self.width,
+                depth: self.depth,
+                height: self.floor_height,
+                thickness,
+                floor: false,
+                ceiling: false,
+                walls: &all_walls,
+                openings: &openings,
+            };
+            for mut piece in room.pieces()? {
+                piece.position[1] += top;
+                storey_pieces.push(piece);
+            }
+            for window in openings
+                .iter()
+                .filter(|opening| opening.kind.as_deref() == Some("window"))
+            {
+                let wall = room.wall(&window.wall);
+                let offset = window.offset.unwrap_or(0.0);
+                let y = top + sill + window_height / 2.0;
+                let (size, position) = if wall.along_x {
+                    (
+                        [window_width, window_height, GLASS_THICKNESS],
+                        [offset, y, wall.across],
+                    )
+                } else {
+                    (
+                        [GLASS_THICKNESS, window_height, window_width],
+                        [wall.across, y, offset],
+                    )
+                };
+                storey_pieces.push(Piece::new(
+                    format!("{}Window", wall.name),
+                    "window",
+                    size,
+                    position,
+                ));
+            }
+
+            if stairs && level + 1 < self.floors {
+                let lane = lanes[level as usize % 2];
+                let rise = storey / steps as f64;
+                for step in 1..=steps {
+                    let (near, far) = if level % 2 == 0 {
+                        (
+                            start + (step - 1) as f64 * STEP_TREAD,
+                            start + step as f64 * STEP_TREAD,
+                        )
+                    } else {
+                        (
+                            end - step as f64 * STEP_TREAD,
+                            end - (step - 1) as f64 * STEP_TREAD,
+                        )
+                    };
+                    storey_pieces.push(Piece::between(
+                        "Step",
+                        "stairs",
+                        [lane[0], top, near],
+                        [lane[1], top + step as f64 * rise, far],
+                    ));
+                }
+            }
+
+            for mut piece in storey_pieces {
+                piece.group = Some(group.clone());
+                pieces.push(piece);
+            }
+        }
+
+        let roof = f64::from(self.floors) * storey;
+        for mut piece in self.slab("Roof", "roof", roof, roof + thickness, None) {
+            piece.group = Some("Roof".to_string());
+            pieces.push(piece);
+        }
+
+        if pieces.len() > MAX_BUILDING_PIECES {
+            return Err(format!(
+                "The building would take {} parts, more than {MAX_BUILDING_PIECES}. Space the windows further apart or build fewer floors",
+                pieces.len()
+            ));
+        }
+        Ok(pieces)
+    }
+
+    /// A slab over the whole footprint between heights `low` and `high`, left open over
+    /// the `hole` spanning the given x and z ranges
+    fn slab(
+        &self,
+        name: &str,
+        role: &str,
+        low: f64,
+        high: f64,
+        hole: Option<([f64; 2], [f64; 2])>,
+    ) -> Vec<Piece> {
+        let (half_width, half_depth) = (self.width / 2.0, self.depth / 2.0);
+        let Some(([left, right], [front, back])) = hole else {
+            return vec![Piece::between(
+                name,
+                role,
+                [-half_width, low, -half_depth],
+                [half_width, high, half_depth],
+            )];
+        };
+        [
+            ([-half_width, -half_depth], [half_width, front]),
+            ([-half_width, back], [half_width, half_depth]),
+            ([-half_width, front], [left, back]),
+            ([right, front], [half_width, back]),
+        ]
+        .into_iter()
+        .filter(|(near, far)| {
+            far[0] - near[0] >= MIN_PIECE_LENGTH && far[1] - near[1] >= MIN_PIECE_LENGTH
+        })
+        .map(|(near, far)| {
+            Piece::between(name, role, [near[0], low, near[1]], [far[0], high, far[1]])
+        })
+        .collect()
+    }
+}
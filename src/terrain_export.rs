@@ -0,0 +1,107 @@
+// Files written by `export_terrain`. Heights go in a 16-bit grayscale PNG that
+// `import_heightmap` reads back, and materials in a JSON grid next to it.
+
+use crate::tool_results::{ExportTerrainResult, RegionBounds};
+use color_eyre::eyre::{eyre, OptionExt, Result, WrapErr};
+use image::{ImageBuffer, ImageFormat, Luma};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Reply of the plugin's `ExportTerrain`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerrainColumns {
+    columns: u32,
+    rows: u32,
+    resolution: f64,
+    region: RegionBounds,
+    /// Top of the terrain in each column row by row, the bottom of the region where
+    /// there's none
+    heights: Vec<f64>,
+    materials: Vec<String>,
+    /// Index into `materials` counting from 1 for each column, 0 where there's no terrain
+    material_indices: Vec<usize>,
+}
+
+/// Material map written next to the heightmap
+#[derive(Serialize)]
+struct MaterialMap<'a> {
+    region: &'a RegionBounds,
+    resolution: f64,
+    columns: u32,
+    rows: u32,
+    /// Materials the grid refers to, with `Air` first for columns without terrain
+    palette: Vec<&'a str>,
+    /// Index into `palette` of each column, one row per step along Z
+    grid: Vec<&'a [usize]>,
+}
+
+/// Writes `terrain` as a heightmap PNG at `path` and a material map beside it, named
+/// like `island.materials.json` for `island.png`
+pub fn write(terrain: &TerrainColumns, path: &Path) -> Result<ExportTerrainResult> {
+    let count = terrain.columns as usize * terrain.rows as usize;
+    if terrain.heights.len() != count || terrain.material_indices.len() != count {
+        return Err(eyre!(
+            "Studio sent {} heights and {} materials for {count} columns",
+            terrain.heights.len(),
+            terrain.material_indices.len()
+        ));
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Couldn't create {}", dir.display()))?;
+    }
+
+    let (bottom, top) = (terrain.region.min.y, terrain.region.max.y);
+    let pixels: Vec<u16> = terrain
+        .heights
+        .iter()
+        .map(|height| {
+            let level = ((height - bottom) / (top - bottom)).clamp(0.0, 1.0);
+            (level * u16::MAX as f64).round() as u16
+        })
+        .collect();
+    let image = ImageBuffer::<Luma<u16>, _>::from_raw(terrain.columns, terrain.rows, pixels)
+        .ok_or_eyre("The heightmap doesn't match its size")?;
+    image
+        .save_with_format(path, ImageFormat::Png)
+        .wrap_err_with(|| format!("Couldn't write {}", path.display()))?;
+
+    let mut palette = vec!["Air"];
+    palette.extend(terrain.materials.iter().map(String::as_str));
+    let map = MaterialMap {
+        region: &terrain.region,
+        resolution: terrain.resolution,
+        columns: terrain.columns,
+        rows: terrain.rows,
+        palette,
+        grid: terrain
+            .material_indices
+            .chunks(terrain.columns.max(1) as usize)
+            .collect(),
+    };
+    let map_path = path.with_extension("materials.json");
+    std::fs::write(&map_path, serde_json::to_vec(&map)?)
+        .wrap_err_with(|| format!("Couldn't write {}", map_path.display()))?;
+
+    let mut materials = BTreeMap::new();
+    for &index in &terrain.material_indices {
+        if let Some(name) = index
+            .checked_sub(1)
+            .and_then(|index| terrain.materials.get(index))
+        {
+            *materials.entry(name.clone()).or_default() += 1;
+        }
+    }
+    Ok(ExportTerrainResult {
+        success: true,
+        heightmap_path: path.display().to_string(),
+        material_map_path: map_path.display().to_string(),
+        columns: terrain.columns,
+        rows: terrain.rows,
+        resolution: terrain.resolution,
+        region: terrain.region.clone(),
+        materials,
+    })
+}
@@ -0,0 +1,137 @@
+// Terrain stamps saved by `save_terrain_stamp` and placed by `place_terrain_stamp`. Each
+// stamp is a JSON file in the `stamps` directory next to the config file, so the library
+// is shared by every place and kept between runs.
+
+use crate::config::config_dir;
+use crate::tool_results::{RegionBounds, TerrainStampSummary};
+use color_eyre::eyre::{OptionExt, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::fs;
+
+const STAMPS_DIR_NAME: &str = "stamps";
+/// Longest stamp name, which is also its file name
+const MAX_NAME_LEN: usize = 64;
+
+/// Voxels of a saved terrain region, as read by the plugin's `SaveTerrainStamp`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Stamp {
+    /// Voxels along X, Y and Z
+    pub size: [u32; 3],
+    /// Material names `voxels` refers to
+    pub materials: Vec<String>,
+    /// Four hex digits per voxel, the index into `materials` counting from 0 and the
+    /// occupancy from 00 to ff, with X outermost and Z innermost
+    pub voxels: String,
+}
+
+impl Stamp {
+    /// Voxels holding any terrain other than air
+    pub fn solid_voxels(&self) -> usize {
+        let air = self.materials.iter().position(|name| name == "Air");
+        self.voxels
+            .as_bytes()
+            .chunks_exact(4)
+            .filter(|voxel| {
+                let hex = |digits: &[u8]| {
+                    std::str::from_utf8(digits)
+                        .ok()
+                        .and_then(|digits| usize::from_str_radix(digits, 16).ok())
+                };
+                hex(&voxel[2..]).is_some_and(|occupancy| occupancy > 0) && hex(&voxel[..2]) != air
+            })
+            .count()
+    }
+}
+
+/// Reply of the plugin's `SaveTerrainStamp`
+#[derive(Debug, Deserialize)]
+pub struct StampRead {
+    #[serde(flatten)]
+    pub stamp: Stamp,
+    /// The region read, grown to whole voxels
+    pub region: RegionBounds,
+}
+
+/// Whether `name` can name a stamp. Names become file names, so only letters, digits,
+/// `-` and `_` are allowed.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn stamps_dir() -> Result<PathBuf> {
+    let dir = config_dir().ok_or_eyre("Could not find a config directory for terrain stamps")?;
+    Ok(dir.join(STAMPS_DIR_NAME))
+}
+
+fn stamp_path(name: &str) -> Result<PathBuf> {
+    Ok(stamps_dir()?.join(format!("{name}.json")))
+}
+
+/// Saves `stamp` as `name`, replacing any stamp of that name, and returns its path
+pub async fn save(name: &str, stamp: &Stamp) -> Result<PathBuf> {
+    let path = stamp_path(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .await
+            .wrap_err_with(|| format!("Could not create {}", dir.display()))?;
+    }
+    fs::write(&path, serde_json::to_vec(stamp)?)
+        .await
+        .wrap_err_with(|| format!("Could not write {}", path.display()))?;
+    Ok(path)
+}
+
+/// The stamp saved as `name`, `None` if there isn't one
+pub async fn load(name: &str) -> Result<Option<Stamp>> {
+    let path = stamp_path(name)?;
+    let contents = match fs::read(&path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).wrap_err_with(|| format!("Could not read {}", path.display())),
+    };
+    let stamp = serde_json::from_slice(&contents)
+        .wrap_err_with(|| format!("Invalid terrain stamp {}", path.display()))?;
+    Ok(Some(stamp))
+}
+
+pub async fn exists(name: &str) -> Result<bool> {
+    Ok(fs::try_exists(stamp_path(name)?).await?)
+}
+
+/// Every saved stamp sorted by name. Files that aren't stamps are skipped.
+pub async fn list() -> Result<Vec<TerrainStampSummary>> {
+    let dir = stamps_dir()?;
+    let mut entries = match fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).wrap_err_with(|| format!("Could not read {}", dir.display())),
+    };
+    let mut stamps = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let Some(name) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".json"))
+            .filter(|name| is_valid_name(name))
+        else {
+            continue;
+        };
+        let Ok(Some(stamp)) = load(name).await else {
+            continue;
+        };
+        stamps.push(TerrainStampSummary {
+            name: name.to_string(),
+            size: stamp.size,
+            materials: stamp.materials,
+        });
+    }
+    stamps.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(stamps)
+}
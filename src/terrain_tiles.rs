@@ -0,0 +1,167 @@
+// Large terrain jobs run as one plugin command per tile, so Studio never freezes on a
+// single huge command and each tile gets the whole tool timeout. The tiles' replies are
+// merged into one result shaped like a single command's.
+
+use serde_json::{Map, Value};
+
+/// Width and depth of a tile in studs, a multiple of the 4 stud voxel grid
+pub const TILE_STUDS: f64 = 512.0;
+
+/// Gap between the tiles of tools that fill a column at both ends of their range, one
+/// column of the 4 stud grid
+pub const COLUMN_GAP: f64 = 4.0;
+
+/// Counts in the tiles' replies that add up to the count for the whole job
+const SUMMED_FIELDS: &[&str] = &[
+    "voxelsGenerated",
+    "voxelsReplaced",
+    "columns",
+    "vegetationPlaced",
+    "vegetation",
+    "voxelsCarved",
+    "entrances",
+];
+
+/// Splits `min..max` into spans of at most `TILE_STUDS`. Tools that visit both ends of
+/// their range end each span `gap` short of the next so no column is visited twice.
+pub fn split(min: f64, max: f64, gap: f64) -> Vec<(f64, f64)> {
+    let mut spans = Vec::new();
+    let mut start = min;
+    loop {
+        let end = start + TILE_STUDS;
+        if end >= max {
+            spans.push((start, max));
+            return spans;
+        }
+        spans.push((start, end - gap));
+        start = end;
+    }
+}
+
+/// Adds the reply of another tile to `into`: counts are summed, regions grown to cover
+/// both, lists like the biomes merged item by item and anything else kept from the
+/// first tile
+pub fn merge(into: &mut Value, tile: Value) {
+    match (into, tile) {
+        (Value::Object(into), Value::Object(tile)) => {
+            for (key, value) in tile {
+                match into.get_mut(&key) {
+                    Some(existing) if key == "region" => merge_region(existing, &value),
+                    Some(existing) if SUMMED_FIELDS.contains(&key.as_str()) => {
+                        sum(existing, &value)
+                    }
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        into.insert(key, value);
+                    }
+                }
+            }
+            resize(into);
+        }
+        (Value::Array(into), Value::Array(tile)) if into.len() == tile.len() => {
+            for (existing, value) in into.iter_mut().zip(tile) {
+                merge(existing, value);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn sum(into: &mut Value, value: &Value) {
+    if let (Some(a), Some(b)) = (into.as_u64(), value.as_u64()) {
+        *into = Value::from(a + b);
+    } else if let (Some(a), Some(b)) = (into.as_f64(), value.as_f64()) {
+        *into = Value::from(a + b);
+    }
+}
+
+fn merge_region(into: &mut Value, region: &Value) {
+    for (corner, pick) in [("min", f64::min as fn(f64, f64) -> f64), ("max", f64::max)] {
+        for axis in ["x", "y", "z"] {
+            let theirs = region[corner][axis].as_f64();
+            if let (Some(ours), Some(theirs)) = (into[corner][axis].as_f64(), theirs) {
+                into[corner][axis] = Value::from(pick(ours, theirs));
+            }
+        }
+    }
+}
+
+/// Keeps a `size` next to a merged `region` in step with it
+fn resize(object: &mut Map<String, Value>) {
+    let Some(region) = object.get("region").cloned() else {
+        return;
+    };
+    if let Some(size) = object.get_mut("size").filter(|size| size.is_object()) {
+        for axis in ["x", "y", "z"] {
+            if let (Some(min), Some(max)) =
+                (region["min"][axis].as_f64(), region["max"][axis].as_f64())
+            {
+                size[axis] = Value::from(max - min);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn small_range_is_one_span() {
+        assert_eq!(split(-100.0, 100.0, COLUMN_GAP), [(-100.0, 100.0)]);
+        assert_eq!(split(0.0, TILE_STUDS, COLUMN_GAP), [(0.0, TILE_STUDS)]);
+    }
+
+    #[test]
+    fn spans_leave_the_gap_between_them() {
+        assert_eq!(
+            split(-600.0, 600.0, COLUMN_GAP),
+            [(-600.0, -92.0), (-88.0, 420.0), (424.0, 600.0)]
+        );
+        assert_eq!(split(0.0, 1024.0, 0.0), [(0.0, 512.0), (512.0, 1024.0)]);
+    }
+
+    #[test]
+    fn merge_sums_counts_and_grows_regions() {
+        let mut result = json!({
+            "success": true,
+            "voxelsGenerated": 10,
+            "columns": 1.5,
+            "region": {"min": {"x": 0, "y": 0, "z": 0}, "max": {"x": 512, "y": 40, "z": 512}},
+            "size": {"x": 512, "y": 40, "z": 512},
+            "biomes": [{"name": "Plains", "vegetation": 2}],
+        });
+        merge(
+            &mut result,
+            json!({
+                "success": true,
+                "voxelsGenerated": 5,
+                "columns": 2.5,
+                "region": {"min": {"x": 512, "y": -8, "z": 0}, "max": {"x": 1024, "y": 30, "z": 512}},
+                "size": {"x": 512, "y": 38, "z": 512},
+                "biomes": [{"name": "Plains", "vegetation": 3}],
+                "warning": "steep",
+            }),
+        );
+        assert_eq!(
+            result,
+            json!({
+                "success": true,
+                "voxelsGenerated": 15,
+                "columns": 4.0,
+                "region": {"min": {"x": 0.0, "y": -8.0, "z": 0.0}, "max": {"x": 1024.0, "y": 40.0, "z": 512.0}},
+                "size": {"x": 1024.0, "y": 48.0, "z": 512.0},
+                "biomes": [{"name": "Plains", "vegetation": 5}],
+                "warning": "steep",
+            })
+        );
+    }
+
+    #[test]
+    fn merge_keeps_first_tile_otherwise() {
+        let mut result = json!({"message": "first", "items": [1, 2]});
+        merge(&mut result, json!({"message": "second", "items": [3]}));
+        assert_eq!(result, json!({"message": "first", "items": [1, 2]}));
+    }
+}
@@ -0,0 +1,196 @@
+// The Script `run_tests` runs the place's tests with. It is put in ServerScriptService
+// and the place is started in Run mode, so each run requires the specs afresh, then it
+// hands the results back through `StudioTestService:EndTest`. Its settings are
+// attributes on the script.
+
+/// Test frameworks the runner knows
+pub const TEST_FRAMEWORKS: &[&str] = &["testez", "testservice"];
+
+pub const RUNNER_SOURCE: &str = r#"-- Runs the place's TestEZ specs or TestService tests and ends the test session with the
+-- results. Framework, Roots, Pattern, TestEZPath and Timeout are read from attributes.
+
+local RunService = game:GetService("RunService")
+local StudioTestService = game:GetService("StudioTestService")
+local TestService = game:GetService("TestService")
+
+if not RunService:IsRunning() then
+	return
+end
+
+local DEFAULT_ROOTS = { "ReplicatedStorage", "ServerScriptService", "ServerStorage", "StarterPlayer", "Workspace" }
+
+local startTime = os.clock()
+local ended = false
+
+local function finish(results: { [string]: any })
+	if ended then
+		return
+	end
+	ended = true
+	results.duration = os.clock() - startTime
+	StudioTestService:EndTest(results)
+end
+
+local function fail(message: string)
+	finish({ success = false, error = message })
+end
+
+local function resolve(path: string): Instance?
+	local current: Instance? = game
+	for _, name in string.split(path, ".") do
+		if not current then
+			return nil
+		end
+		if name == "game" then
+			continue
+		end
+		current = if name == "workspace" then workspace else current:FindFirstChild(name)
+	end
+	return current
+end
+
+local function findTestEZ(): ModuleScript?
+	local path = script:GetAttribute("TestEZPath")
+	if path and path ~= "" then
+		local module = resolve(path)
+		return if module and module:IsA("ModuleScript") then module else nil
+	end
+	for _, descendant in game:GetDescendants() do
+		if descendant:IsA("ModuleScript") and descendant.Name == "TestEZ" then
+			return descendant
+		end
+	end
+	return nil
+end
+
+local function roots(): { Instance }
+	local list = {}
+	local paths = script:GetAttribute("Roots")
+	for _, path in if paths and paths ~= "" then string.split(paths, ",") else DEFAULT_ROOTS do
+		local root = resolve(path)
+		if root then
+			table.insert(list, root)
+		end
+	end
+	return list
+end
+
+local function hasSpecs(list: { Instance }): boolean
+	for _, root in list do
+		for _, descendant in root:GetDescendants() do
+			if descendant:IsA("ModuleScript") and string.match(descendant.Name, "%.spec$") then
+				return true
+			end
+		end
+	end
+	return false
+end
+
+-- Flattens TestEZ's result tree into one entry per `it` block
+local function collectTestEZ(node, names: { string }, tests: { any })
+	local phrase = node.planNode and node.planNode.phrase
+	local path = table.clone(names)
+	if phrase then
+		table.insert(path, phrase)
+	end
+	if #node.children == 0 and node.planNode and node.planNode.type == "It" then
+		table.insert(tests, {
+			name = table.concat(path, " "),
+			status = if node.status == "Success" then "passed" elseif node.status == "Skipped" then "skipped" else "failed",
+			errors = node.errors,
+		})
+	end
+	for _, child in node.children do
+		collectTestEZ(child, path, tests)
+	end
+end
+
+local function runTestEZ(testEZ: ModuleScript, list: { Instance }, pattern: string?)
+	local TestEZ = require(testEZ) :: any
+	local reporter = { report = function() end }
+	local results = TestEZ.TestBootstrap:run(list, reporter, { testNamePattern = pattern })
+	local tests = {}
+	for _, child in results.children do
+		collectTestEZ(child, {}, tests)
+	end
+	finish({
+		success = results.failureCount == 0 and #results.errors == 0,
+		framework = "testez",
+		passed = results.successCount,
+		failed = results.failureCount,
+		skipped = results.skippedCount,
+		tests = tests,
+		errors = results.errors,
+	})
+end
+
+local function runTestService(pattern: string?)
+	local tests = {}
+	local function record(passed: boolean, text: string, source: Instance?, line: number?)
+		local sourceName = if source then source:GetFullName() else nil
+		local name = if sourceName then sourceName .. ": " .. text else text
+		if pattern and not string.match(name, pattern) then
+			return
+		end
+		table.insert(tests, {
+			name = name,
+			status = if passed then "passed" else "failed",
+			script = sourceName,
+			line = line,
+		})
+	end
+	-- Each TestService:Check and TestService:Require is one result
+	TestService.ServerCollectConditionalResult:Connect(record)
+	TestService:Run()
+
+	local passed, failed = 0, 0
+	for _, test in tests do
+		if test.status == "passed" then
+			passed += 1
+		else
+			failed += 1
+		end
+	end
+	finish({
+		success = failed == 0,
+		framework = "testservice",
+		passed = passed,
+		failed = failed,
+		skipped = 0,
+		tests = tests,
+		errors = {},
+	})
+end
+
+task.delay(script:GetAttribute("Timeout") or 120, function()
+	fail("The tests didn't finish within " .. (script:GetAttribute("Timeout") or 120) .. " seconds")
+end)
+
+local ok, err = pcall(function()
+	local framework = script:GetAttribute("Framework")
+	local pattern = script:GetAttribute("Pattern")
+	if pattern == "" then
+		pattern = nil
+	end
+	local list = roots()
+	if framework == nil or framework == "" then
+		framework = if findTestEZ() and hasSpecs(list) then "testez" elseif #TestService:GetChildren() > 0 then "testservice" else nil
+		if not framework then
+			error("No TestEZ specs or TestService tests found", 0)
+		end
+	end
+
+	if framework == "testez" then
+		local testEZ = findTestEZ()
+		if not testEZ then
+			error("No TestEZ module found, pass testez_path", 0)
+		end
+		runTestEZ(testEZ, list, pattern)
+	else
+		runTestService(pattern)
+	end
+end)
+if not ok then
+	fail(tostring(err))
+end
+"#;
@@ -0,0 +1,107 @@
+use crate::config::Config;
+use axum::serve::Listener;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+use tokio_rustls::rustls::crypto::aws_lc_rs;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+/// Connections that don't finish the handshake in time are dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connections done with the handshake that can wait for axum to pick them up
+const HANDSHAKED_BACKLOG: usize = 64;
+
+/// Builds a TLS acceptor from the PEM certificate chain and private key in the config
+pub fn load_acceptor(cert: &Path, key: &Path) -> Result<TlsAcceptor> {
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| eyre!("Could not read TLS certificate {}: {e}", cert.display()))?;
+    let key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|e| eyre!("Could not read TLS key {}: {e}", key.display()))?;
+    let config = ServerConfig::builder_with_provider(Arc::new(aws_lc_rs::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .wrap_err("Invalid TLS certificate or key")?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// HTTP client for talking to another instance of this server over loopback. It trusts
+/// only our own certificate, whatever names it was issued for, so a LAN certificate
+/// without a `127.0.0.1` entry still works.
+pub fn client(config: &Config) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(cert) = &config.tls_cert {
+        let pem = std::fs::read(cert)
+            .wrap_err_with(|| format!("Could not read TLS certificate {}", cert.display()))?;
+        builder = builder
+            .tls_certs_only(reqwest::Certificate::from_pem_bundle(&pem)?)
+            .tls_danger_accept_invalid_hostnames(true);
+    }
+    Ok(builder.build()?)
+}
+
+/// TCP listener that completes a TLS handshake before handing connections to axum. The
+/// handshakes run in their own tasks so a slow client can't hold up the others.
+pub struct TlsListener {
+    streams: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: io::Result<SocketAddr>,
+}
+
+impl TlsListener {
+    pub fn new(mut listener: TcpListener, acceptor: TlsAcceptor) -> Self {
+        let local_addr = listener.local_addr();
+        let (sender, streams) = mpsc::channel(HANDSHAKED_BACKLOG);
+        tokio::spawn(async move {
+            loop {
+                // Stops accepting once axum has dropped the listener
+                let (stream, addr) = tokio::select! {
+                    accepted = Listener::accept(&mut listener) => accepted,
+                    _ = sender.closed() => break,
+                };
+                let acceptor = acceptor.clone();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => _ = sender.send((stream, addr)).await,
+                        Ok(Err(e)) => tracing::debug!("TLS handshake with {addr} failed: {e}"),
+                        Err(_) => tracing::debug!("TLS handshake with {addr} timed out"),
+                    }
+                });
+            }
+        });
+        Self {
+            streams,
+            local_addr,
+        }
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.streams.recv().await {
+            Some(accepted) => accepted,
+            // The accept task only ends once this listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        match &self.local_addr {
+            Ok(addr) => Ok(*addr),
+            Err(e) => Err(io::Error::new(e.kind(), e.to_string())),
+        }
+    }
+}
@@ -0,0 +1,1831 @@
+use rmcp::model::JsonObject;
+use rmcp::schemars;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+// Typed shapes of the JSON the plugin replies with, returned to clients as structured
+// content next to the text of the reply.
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+pub struct Vector3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+pub struct RegionBounds {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct Bounds {
+    min: Vector3,
+    max: Vector3,
+    size: Vector3,
+    center: Vector3,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct InsertedInstance {
+    index: u64,
+    name: String,
+    path: String,
+    /// Where the model's pivot ended up, after any grounding and nudging
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<Vector3>,
+    /// Moved sideways to keep clear of the models placed before it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nudged: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct InsertFailure {
+    index: u64,
+    error: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct BatchInsertResult {
+    /// False if any model failed to insert
+    success: bool,
+    inserted: u64,
+    failed: Vec<InsertFailure>,
+    instances: Vec<InsertedInstance>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ScriptResult {
+    index: u64,
+    success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct BatchRunResult {
+    /// False if any script failed
+    success: bool,
+    executed: u64,
+    results: Vec<ScriptResult>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateTerrainResult {
+    success: bool,
+    voxels_generated: u64,
+    region: RegionBounds,
+    material: String,
+    heightmap_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    caves: Option<CavesCarved>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CavesCarved {
+    /// 4 stud voxels turned into tunnels
+    voxels_carved: u64,
+    /// Entrance shafts that reached terrain
+    entrances: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct BiomeSummary {
+    name: String,
+    /// Columns where this biome was the strongest
+    columns: u64,
+    /// Trees placed in it
+    vegetation: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateBiomesResult {
+    success: bool,
+    columns: u64,
+    vegetation_placed: u64,
+    /// Seed used, to make the same terrain again
+    seed: i64,
+    region: RegionBounds,
+    biomes: Vec<BiomeSummary>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    caves: Option<CavesCarved>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FillTerrainResult {
+    success: bool,
+    region: RegionBounds,
+    size: Vector3,
+    material: String,
+    replace_air_only: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SculptTerrainResult {
+    success: bool,
+    points_processed: u64,
+    mode: String,
+}
+
+/// Added by the server to the results of tools that remove instances
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SafetySnapshot {
+    /// Saved scene holding the workspace as it was before the tool ran, load it to undo
+    /// the change
+    pub scene: String,
+    /// Revision of the scene, missing if the server couldn't store it and it's only kept in
+    /// Studio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearWorkspaceResult {
+    success: bool,
+    removed_count: u64,
+    preserved_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    safety_snapshot: Option<SafetySnapshot>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSceneResult {
+    success: bool,
+    name: String,
+    object_count: u64,
+    saved_scenes: u64,
+    /// Whether the server stored the snapshot on disk
+    #[serde(default)]
+    persisted: bool,
+    /// Revision the server stored it as, counting from 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revision: Option<u32>,
+    /// Why the snapshot couldn't be stored, it is still kept in Studio until it closes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    persist_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadSceneResult {
+    success: bool,
+    name: String,
+    loaded_count: u64,
+    failed_count: u64,
+    /// Revision loaded, missing for a scene only kept in Studio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revision: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<Vector3>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    safety_snapshot: Option<SafetySnapshot>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneInstance {
+    /// Dotted path from the workspace, siblings sharing a name are numbered like `Part[2]`
+    path: String,
+    class_name: String,
+    /// Instances below this one, added or removed along with it
+    descendants: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct PropertyChange {
+    property: String,
+    /// Missing if the property wasn't saved for the instance before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    from: Option<Value>,
+    /// Missing if the property isn't saved for the instance any more
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModifiedInstance {
+    path: String,
+    class_name: String,
+    changes: Vec<PropertyChange>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffScenesResult {
+    success: bool,
+    from: String,
+    /// Missing when compared with the live workspace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to: Option<String>,
+    /// Only the top of each added subtree is listed
+    added: Vec<SceneInstance>,
+    /// Only the top of each removed subtree is listed
+    removed: Vec<SceneInstance>,
+    modified: Vec<ModifiedInstance>,
+    unchanged_count: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportModelFileResult {
+    success: bool,
+    parent: String,
+    /// Paths of the top level instances inserted
+    inserted: Vec<String>,
+    /// Instances created, counting descendants
+    instance_count: u64,
+    /// Instances of classes that can't be created, skipped with their descendants
+    failed_instances: u64,
+    /// Properties Studio refused to set, e.g. read-only ones
+    failed_properties: u64,
+    /// Properties of types plugins can't set, left at their defaults
+    #[serde(default)]
+    unsupported_properties: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneRevision {
+    pub revision: u32,
+    /// When the server stored it, Unix time in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saved_at: Option<u64>,
+    /// When Studio took the snapshot, Unix time in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured_at: Option<u64>,
+    pub object_count: u64,
+    /// Size of the snapshot on disk
+    pub bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SceneSummary {
+    pub name: String,
+    /// Oldest first
+    pub revisions: Vec<SceneRevision>,
+}
+
+/// Built on the server from the saved scenes
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ListSceneRevisionsResult {
+    pub scenes: Vec<SceneSummary>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SceneMatch {
+    pub name: String,
+    /// Revisions kept of the scene
+    pub revisions: usize,
+    /// The revision searched, the latest
+    pub latest: SceneRevision,
+}
+
+/// Built on the server from the saved scenes
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct FindScenesResult {
+    /// Newest first
+    pub scenes: Vec<SceneMatch>,
+    /// Scenes matched past the limit, left out
+    pub truncated: usize,
+}
+
+/// Built on the server from the saved scenes
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct PruneSceneHistoryResult {
+    pub name: String,
+    pub removed: Vec<u32>,
+    pub kept: Vec<u32>,
+}
+
+/// Built on the server from Open Cloud's replies
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadAssetResult {
+    /// False when moderation rejected the asset
+    pub success: bool,
+    pub asset_id: u64,
+    /// Open Cloud asset type, `Model`, `Audio` or `Decal`
+    pub asset_type: String,
+    pub name: String,
+    /// `Approved`, `Rejected`, or `Reviewing` when moderation hadn't decided in time
+    pub moderation_state: String,
+    /// Tool that inserts the asset given its ID
+    pub insert_with: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketplaceAsset {
+    /// Pass to insert_model as asset_id to insert this asset
+    pub asset_id: u64,
+    pub name: String,
+    pub creator: String,
+    /// Details of the creator and the asset, only looked up when filtering on them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator_id: Option<u64>,
+    /// `User` or `Group`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    /// ISO 8601 date the asset was created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// Missing if Roblox couldn't be asked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favorites: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    /// Page of the asset on the Creator Store
+    #[serde(default)]
+    pub url: String,
+}
+
+/// Built on the server from the plugin's search results
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMarketplaceResult {
+    pub success: bool,
+    pub query: String,
+    /// Matches the marketplace has for the query, most aren't returned
+    pub total_count: u64,
+    /// In the marketplace's order, the first is what insert_model would pick
+    pub results: Vec<MarketplaceAsset>,
+    /// Results looked at and left out by the filters
+    #[serde(default)]
+    pub filtered_out: u64,
+    /// Reused from an earlier search for the same query and filters
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// Built on the server from the scene snapshot the plugin sends
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSceneResult {
+    pub success: bool,
+    pub path: String,
+    /// `rbxm` or `rbxmx`
+    pub format: String,
+    /// Instances written, counting descendants
+    pub instance_count: usize,
+    /// Size of the file written
+    pub bytes: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct LogEntry {
+    seq: u64,
+    /// Seconds since the plugin started
+    timestamp: f64,
+    /// `info`, `warn` or `error`
+    level: String,
+    source: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleLogsResult {
+    success: bool,
+    logs: Vec<LogEntry>,
+    /// Pass as `since_sequence` to get only newer logs
+    current_sequence: u64,
+    has_more: bool,
+    /// Whether logs were dropped from the buffer before they were read
+    overflow: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct OutputEntry {
+    /// Unix time in seconds
+    time: f64,
+    /// `info`, `warn` or `error`
+    level: String,
+    message: String,
+    /// Full name of the script the message came from, when it names one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    script: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stack: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputLogResult {
+    success: bool,
+    /// Oldest first
+    entries: Vec<OutputEntry>,
+    /// Whether older matching entries were left out for the limit
+    truncated: bool,
+    /// Time of the oldest entry the plugin still keeps, output before it is gone
+    oldest_time: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicsStats {
+    /// Average milliseconds a physics step took over the sample
+    step_time_ms: Option<f64>,
+    peak_step_time_ms: Option<f64>,
+    /// Average milliseconds of the Heartbeat step, where scripts run
+    heartbeat_time_ms: Option<f64>,
+    peak_heartbeat_time_ms: Option<f64>,
+    fps: f64,
+    primitives: Option<u64>,
+    /// Primitives not anchored or asleep, which physics simulates each step
+    moving_primitives: Option<u64>,
+    contacts: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStats {
+    data_send_kbps: Option<f64>,
+    data_receive_kbps: Option<f64>,
+    physics_send_kbps: Option<f64>,
+    physics_receive_kbps: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct MemoryCategory {
+    /// Name of the DeveloperMemoryTag, e.g. `PhysicsParts` or `GraphicsTexture`
+    category: String,
+    mb: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    total_mb: f64,
+    /// Largest first, unused categories left out
+    categories: Vec<MemoryCategory>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceStatsResult {
+    success: bool,
+    /// Whether a playtest was running, physics and network figures are idle in edit mode
+    running: bool,
+    sample_seconds: f64,
+    instance_count: Option<u64>,
+    physics: PhysicsStats,
+    network: NetworkStats,
+    memory: MemoryUsage,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerMemory {
+    /// A Workspace model or folder, a storage service, or `Workspace` for everything else
+    /// directly in Workspace
+    path: String,
+    class_name: String,
+    instances: u64,
+    parts: u64,
+    scripts: u64,
+    /// Bytes of script source
+    source_bytes: u64,
+    /// Distinct mesh and texture asset IDs used in the container
+    unique_meshes: u64,
+    unique_textures: u64,
+    /// Rough size from the counts above, for comparing containers
+    estimated_mb: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptMemory {
+    count: u64,
+    source_bytes: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TerrainMemory {
+    /// Voxels holding a material
+    cells: u64,
+    estimated_mb: f64,
+    /// What Studio reports in use for `TerrainVoxels`
+    reported_mb: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryReportResult {
+    success: bool,
+    total_mb: f64,
+    /// Largest first, unused categories left out
+    categories: Vec<MemoryCategory>,
+    /// Heaviest first
+    containers: Vec<ContainerMemory>,
+    /// Whether lighter containers were left out for the limit
+    truncated: bool,
+    /// Scripts in all containers, listed or not
+    scripts: ScriptMemory,
+    terrain: TerrainMemory,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeStats {
+    min: Vector3,
+    max: Vector3,
+    mean: Vector3,
+    std_dev: Vector3,
+    uniformity_score: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct DominantColor {
+    /// RGB from 0 to 255
+    color: Vec<f64>,
+    percentage: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorStats {
+    unique_colors: u64,
+    dominant_colors: Vec<DominantColor>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStatsResult {
+    success: bool,
+    part_count: u64,
+    model_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size_stats: Option<SizeStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color_stats: Option<ColorStats>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildInfo {
+    name: String,
+    class_name: String,
+    part_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bounds: Option<Bounds>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildrenInfoResult {
+    success: bool,
+    path: String,
+    parent_name: String,
+    parent_class_name: String,
+    child_count: u64,
+    children: Vec<ChildInfo>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceNode {
+    name: String,
+    class_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    properties: Option<JsonObject>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    attributes: Option<JsonObject>,
+    children: Vec<InstanceNode>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceTreeResult {
+    success: bool,
+    path: String,
+    node_count: u64,
+    /// Whether instances were left out to stay under `max_nodes`
+    truncated: bool,
+    tree: InstanceNode,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInstanceResult {
+    success: bool,
+    /// Full path of the new instance
+    path: String,
+    name: String,
+    class_name: String,
+    /// Why each property that couldn't be set failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    failed_properties: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertDecalOrTextureResult {
+    success: bool,
+    kind: String,
+    asset_id: u64,
+    /// Full paths of the instances created, one per face
+    paths: Vec<String>,
+    /// Image the decals or textures show, missing for surface appearances
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    texture: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertAudioResult {
+    success: bool,
+    /// Full path of the new Sound
+    path: String,
+    asset_id: u64,
+    /// False if the audio didn't load in time, e.g. because the place may not use it
+    loaded: bool,
+    /// Length of the audio in seconds, 0 if it didn't load
+    time_length: f64,
+    /// Seconds of preview playing in Studio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preview_seconds: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Import3dFileResult {
+    success: bool,
+    /// Full path of the inserted instance
+    path: String,
+    /// `upload`, or `editable_mesh` when the mesh was built in Studio and won't be saved
+    /// with the place
+    method: String,
+    size: Vector3,
+    /// MeshParts inserted
+    mesh_parts: u64,
+    /// Set when the file was uploaded
+    asset_id: Option<u64>,
+    moderation_state: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePartResult {
+    success: bool,
+    /// Full path of the new part
+    path: String,
+    /// `Part`, `WedgePart` or `CornerWedgePart`
+    class_name: String,
+    /// Size after Roblox's adjustments, spheres are kept round
+    size: Vector3,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildStructureResult {
+    success: bool,
+    /// Full path of the Model holding the parts
+    path: String,
+    /// Parts the walls, floor and ceiling were made of
+    parts: u64,
+    /// Size of the Model's bounding box
+    size: Vector3,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateConstraintResult {
+    success: bool,
+    /// Full path of the new constraint
+    path: String,
+    class_name: String,
+    /// Paths of the attachments on part0 and part1, empty for welds
+    attachments: Vec<String>,
+    /// Why each property that couldn't be set failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    failed_properties: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateBuildingResult {
+    success: bool,
+    /// Full path of the Model holding the building
+    path: String,
+    /// Paths of the Models for each storey from the ground up, then the roof
+    storeys: Vec<String>,
+    parts: u64,
+    /// Size of the building's bounding box
+    size: Vector3,
+}
+
+/// Faces of a skybox as content URLs, named like the `set_skybox` arguments
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SkyboxFaces {
+    back: String,
+    down: String,
+    front: String,
+    left: String,
+    right: String,
+    up: String,
+}
+
+/// Settings of a Sky, named like the `set_skybox` arguments so they can be passed back
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SkyboxSettings {
+    faces: SkyboxFaces,
+    sun_texture_id: String,
+    moon_texture_id: String,
+    sun_angular_size: f64,
+    moon_angular_size: f64,
+    celestial_bodies_shown: bool,
+    star_count: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SetSkyboxResult {
+    success: bool,
+    /// Full path of the Sky
+    path: String,
+    /// Whether there was no Sky in Lighting before, delete it to undo the call
+    created: bool,
+    /// Settings of the Sky before the call, absent when it was created
+    previous: Option<SkyboxSettings>,
+}
+
+/// Atmosphere and fog settings, named like the `set_atmosphere` arguments so they can be
+/// passed back. Colors are hex.
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct AtmosphereSettings {
+    /// Whether Lighting had an Atmosphere, the Atmosphere settings are absent if not
+    atmosphere: bool,
+    density: Option<f64>,
+    offset: Option<f64>,
+    color: Option<String>,
+    decay: Option<String>,
+    glare: Option<f64>,
+    haze: Option<f64>,
+    fog_start: f64,
+    fog_end: f64,
+    fog_color: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SetAtmosphereResult {
+    success: bool,
+    /// Full path of the Atmosphere, absent when it was removed
+    path: Option<String>,
+    /// Whether there was no Atmosphere in Lighting before
+    created: bool,
+    /// Settings before the call
+    previous: AtmosphereSettings,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct BloomSettings {
+    enabled: bool,
+    intensity: f64,
+    size: f64,
+    threshold: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct DepthOfFieldSettings {
+    enabled: bool,
+    far_intensity: f64,
+    focus_distance: f64,
+    in_focus_radius: f64,
+    near_intensity: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ColorCorrectionSettings {
+    enabled: bool,
+    brightness: f64,
+    contrast: f64,
+    saturation: f64,
+    /// Hex color
+    tint_color: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SunRaysSettings {
+    enabled: bool,
+    intensity: f64,
+    spread: f64,
+}
+
+/// Settings of each effect in Lighting after the call, named like the `set_post_effects`
+/// arguments. Effects Lighting doesn't have are absent.
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SetPostEffectsResult {
+    success: bool,
+    bloom: Option<BloomSettings>,
+    depth_of_field: Option<DepthOfFieldSettings>,
+    color_correction: Option<ColorCorrectionSettings>,
+    sun_rays: Option<SunRaysSettings>,
+    /// Classes of the effects the call added to Lighting
+    created: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertMeshResult {
+    success: bool,
+    /// Full path of the new MeshPart
+    path: String,
+    mesh_id: u64,
+    size: Vector3,
+    /// Size the mesh was uploaded at
+    mesh_size: Vector3,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SkippedInstance {
+    path: String,
+    reason: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteInstancesResult {
+    success: bool,
+    dry_run: bool,
+    /// Paths of the instances deleted, or that would be on a dry run
+    matched: Vec<String>,
+    removed_count: u64,
+    /// Paths and patterns that matched nothing
+    not_found: Vec<String>,
+    skipped: Vec<SkippedInstance>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    safety_snapshot: Option<SafetySnapshot>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct CloneInstanceResult {
+    success: bool,
+    source: String,
+    count: u64,
+    /// Paths of the copies in order
+    paths: Vec<String>,
+    /// False if the original isn't a part or model, so the offsets were ignored
+    moved: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct GridCounts {
+    x: u64,
+    y: u64,
+    z: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ArrayDuplicateResult {
+    success: bool,
+    source: String,
+    count: u64,
+    /// Paths of the copies in grid order, x first, then z, then y
+    paths: Vec<String>,
+    /// Cells along each axis, the original's included
+    grid: GridCounts,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct PlaceAlongPathResult {
+    success: bool,
+    source: String,
+    count: u64,
+    /// Paths of the copies in order along the path
+    paths: Vec<String>,
+    /// Length of the path in studs
+    length: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct MovedInstance {
+    from: String,
+    to: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct InstanceFailure {
+    path: String,
+    error: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct MoveInstancesResult {
+    /// False if any instance couldn't be moved
+    success: bool,
+    moved: Vec<MovedInstance>,
+    failed: Vec<InstanceFailure>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamedInstance {
+    /// Path before the rename
+    path: String,
+    old_name: String,
+    new_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameInstancesResult {
+    success: bool,
+    dry_run: bool,
+    renamed: Vec<RenamedInstance>,
+    /// Paths and patterns that matched nothing
+    not_found: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FoundInstance {
+    path: String,
+    name: String,
+    class_name: String,
+    /// Set for parts and models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<Vector3>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SearchInstancesResult {
+    success: bool,
+    /// How many instances matched before the limit
+    total: u64,
+    count: u64,
+    truncated: bool,
+    results: Vec<FoundInstance>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectedInstance {
+    path: String,
+    name: String,
+    class_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct GetSelectionResult {
+    success: bool,
+    count: u64,
+    selection: Vec<SelectedInstance>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSelectionResult {
+    success: bool,
+    /// Full paths of the instances now selected
+    selected: Vec<String>,
+    not_found: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct CreateCheckpointResult {
+    success: bool,
+    name: String,
+    /// Checkpoints that can be undone to, oldest first
+    checkpoints: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct UndoToCheckpointResult {
+    success: bool,
+    name: String,
+    /// Undo steps taken back, one per tool call or change made in Studio
+    undone: u64,
+    /// Checkpoints left, the ones made after this one are gone
+    checkpoints: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct TaggedInstance {
+    path: String,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct AlignedInstance {
+    path: String,
+    /// Pivot after the move
+    position: Vector3,
+    /// Distance moved in studs
+    moved: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignInstancesResult {
+    success: bool,
+    mode: String,
+    instances: Vec<AlignedInstance>,
+    not_found: Vec<String>,
+    /// Matched instances that aren't parts or models, left where they are
+    skipped: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SnappedInstance {
+    path: String,
+    /// Pivot after the move
+    position: Vector3,
+    /// Studs moved up, negative when it came down
+    moved: f64,
+    /// `Terrain` or the path of the part it rests on
+    ground: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RaycastResult {
+    success: bool,
+    /// Whether the ray hit anything, the rest is left out when it didn't
+    hit: bool,
+    /// Path of the part or `Workspace.Terrain` that was hit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<Vector3>,
+    /// Unit normal of the surface at the hit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    normal: Option<Vector3>,
+    /// Material of the part, or of the terrain voxel that was hit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    material: Option<String>,
+    /// Studs from the origin to the hit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    distance: Option<f64>,
+    /// Filter paths that didn't resolve to an instance
+    #[serde(default)]
+    not_found: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionPart {
+    path: String,
+    class_name: String,
+    /// Path of the outermost model the part is in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    position: Vector3,
+    size: Vector3,
+    /// Studs from the center of the box or sphere to the part's position
+    distance: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRegionResult {
+    success: bool,
+    /// Closest first
+    parts: Vec<RegionPart>,
+    /// Parts matching the filters, including any left out for max_results
+    total: u64,
+    truncated: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapToGroundResult {
+    success: bool,
+    instances: Vec<SnappedInstance>,
+    /// Instances with nothing below them, left where they are
+    missed: Vec<String>,
+    not_found: Vec<String>,
+    /// Matched instances that aren't parts or models
+    skipped: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PaintInstancesResult {
+    success: bool,
+    /// Parts found to paint
+    matched: u64,
+    /// Parts that looked different before, the rest already matched
+    changed: u64,
+    not_found: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceLightsResult {
+    success: bool,
+    /// Paths of the lights created
+    lights: Vec<String>,
+    not_found: Vec<String>,
+    /// Matched instances with no part to put a light in
+    skipped: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct CreateParticleEmitterResult {
+    success: bool,
+    /// Full path of the new emitter
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SetupWeatherResult {
+    success: bool,
+    /// Full path of the weather Model
+    path: String,
+    /// Full path of the driver script
+    script: String,
+    /// Full path of the thunder sound, absent without thunder or when no sound was found
+    thunder: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SetupDayNightCycleResult {
+    success: bool,
+    /// Full path of the script
+    path: String,
+}
+
+/// SoundService settings, named like the `setup_ambient_sound` arguments so they can be
+/// passed back
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SoundServiceSettings {
+    ambient_reverb: String,
+    distance_factor: f64,
+    doppler_scale: f64,
+    rolloff_scale: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SetupAmbientSoundResult {
+    success: bool,
+    /// SoundService settings before the call
+    previous: SoundServiceSettings,
+    /// Full paths of the zone parts
+    zones: Vec<String>,
+    /// Full path of the zone trigger script, absent without zones
+    script: Option<String>,
+}
+
+/// Workspace physics properties, named like the `set_workspace_physics` arguments so they
+/// can be passed back
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct WorkspacePhysicsSettings {
+    gravity: f64,
+    air_density: f64,
+    global_wind: Vector3,
+    fallen_parts_destroy_height: f64,
+    physics_stepping_method: String,
+    touches_use_collision_groups: bool,
+    retargeting: String,
+    ik_control_constraint_support: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SetWorkspacePhysicsResult {
+    success: bool,
+    /// Properties before the call
+    previous: WorkspacePhysicsSettings,
+    /// Properties after the call
+    current: WorkspacePhysicsSettings,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct TestOutcome {
+    /// Full name of the test, its `describe` blocks and `it` for TestEZ or the script and
+    /// check text for TestService
+    name: String,
+    /// `passed`, `failed` or `skipped`
+    status: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    script: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct RunTestsResult {
+    /// Whether every test passed
+    success: bool,
+    /// `testez` or `testservice`
+    framework: String,
+    passed: u32,
+    failed: u32,
+    skipped: u32,
+    tests: Vec<TestOutcome>,
+    /// Errors outside any test, such as specs that failed to load
+    errors: Vec<String>,
+    /// Seconds the tests took
+    duration: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StartPlaytestResult {
+    success: bool,
+    mode: String,
+    /// ISO 8601 time the game started running
+    started_at: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StopPlaytestResult {
+    success: bool,
+    mode: String,
+    /// ISO 8601 times the game started and stopped running
+    started_at: String,
+    ended_at: String,
+    duration_seconds: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ManageTagsResult {
+    success: bool,
+    action: String,
+    /// Every matched instance with its tags after the change
+    instances: Vec<TaggedInstance>,
+    not_found: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct AttributedInstance {
+    path: String,
+    /// Absent when the instance has none
+    attributes: Option<JsonObject>,
+    /// Attributes that couldn't be set, with the reason
+    failed: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ManageAttributesResult {
+    success: bool,
+    action: String,
+    /// Every matched instance with its attributes after the change
+    instances: Vec<AttributedInstance>,
+    not_found: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadScriptSourceResult {
+    success: bool,
+    path: String,
+    class_name: String,
+    /// Lines in the whole script
+    line_count: u64,
+    /// Size of the whole script in bytes
+    byte_size: u64,
+    start_line: u64,
+    /// Last line returned, lower than requested when the byte limit was reached
+    end_line: u64,
+    truncated: bool,
+    /// Checksum of the whole script, to pass as `patch_script`'s base
+    checksum: String,
+    source: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteScriptSourceResult {
+    success: bool,
+    path: String,
+    class_name: String,
+    /// Whether the script was created by this call
+    created: bool,
+    line_count: u64,
+    byte_size: u64,
+    /// FNV-1a hash of the source now in the script, as 8 hex digits
+    checksum: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchScriptResult {
+    success: bool,
+    path: String,
+    /// Number of hunks applied
+    applied: u64,
+    line_count: u64,
+    byte_size: u64,
+    previous_checksum: String,
+    /// Checksum of the patched source, the base for the next edit
+    checksum: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptInfo {
+    path: String,
+    class_name: String,
+    /// Absent for ModuleScripts, which run when required
+    enabled: Option<bool>,
+    line_count: u64,
+    byte_size: u64,
+    checksum: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ListScriptsResult {
+    success: bool,
+    count: u64,
+    scripts: Vec<ScriptInfo>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateScriptResult {
+    success: bool,
+    path: String,
+    class_name: String,
+    line_count: u64,
+    /// Checksum of the source, the base for `patch_script`
+    checksum: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct CodeMatch {
+    pub path: String,
+    /// Line of the match, counting from 1
+    pub line: usize,
+    /// Character the match starts at, counting from 1
+    pub column: usize,
+    pub text: String,
+    /// Lines before the match, nearest last
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Built on the server from the sources the plugin sends
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchCodeResult {
+    pub success: bool,
+    pub scripts_searched: usize,
+    /// Matching lines in all scripts, including those past the result limit
+    pub total: usize,
+    pub truncated: bool,
+    pub matches: Vec<CodeMatch>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LintDiagnostic {
+    /// error or warning
+    pub severity: String,
+    /// selene lint name, e.g. unused_variable
+    pub code: String,
+    pub message: String,
+    /// Lines and columns count from 1
+    pub line: u32,
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ScriptDiagnostics {
+    pub path: String,
+    pub diagnostics: Vec<LintDiagnostic>,
+}
+
+/// Built on the server by running selene over the sources the plugin sends
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LintScriptsResult {
+    pub success: bool,
+    pub scripts_linted: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    /// Only scripts selene reported something for
+    pub scripts: Vec<ScriptDiagnostics>,
+}
+
+/// Script `format_scripts` couldn't format, usually because of a syntax error
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct FormatFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Script a bulk write left alone, e.g. because it changed in Studio since it was read
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct SkippedScript {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Built on the server by running StyLua over the sources the plugin sends
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatScriptsResult {
+    pub success: bool,
+    pub dry_run: bool,
+    pub scripts_checked: usize,
+    /// Scripts whose source changed, or would change on a dry run
+    pub reformatted: Vec<String>,
+    pub failed: Vec<FormatFailure>,
+    pub skipped: Vec<SkippedScript>,
+}
+
+/// `require` call whose target couldn't be followed
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct RequireProblem {
+    /// Script making the call
+    pub path: String,
+    pub line: u32,
+    /// Instance path the call resolves to, or its argument as written
+    pub target: String,
+    pub reason: String,
+}
+
+/// Built on the server by reading the `require` calls in the sources the plugin sends
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleDependenciesResult {
+    pub success: bool,
+    pub scripts_analyzed: usize,
+    /// Modules each script requires, keyed by script path
+    pub dependencies: BTreeMap<String, Vec<String>>,
+    /// Each starts and ends with the same module
+    pub cycles: Vec<Vec<String>>,
+    pub missing: Vec<RequireProblem>,
+    /// Calls with a target only known at run time
+    pub unresolved: Vec<RequireProblem>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct RenamedLine {
+    pub line: u32,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct RenamedScript {
+    pub path: String,
+    /// Uses of the name replaced in this script
+    pub changes: usize,
+    /// The changed lines, only on a dry run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<RenamedLine>>,
+}
+
+/// Built on the server from the sources the plugin sends
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameSymbolResult {
+    pub success: bool,
+    pub dry_run: bool,
+    pub scripts_checked: usize,
+    pub total_changes: usize,
+    /// Scripts that use the name
+    pub scripts: Vec<RenamedScript>,
+    /// Scripts that use the name but weren't written
+    pub skipped: Vec<SkippedScript>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportHeightmapResult {
+    success: bool,
+    /// Size of the grid the image was resampled to, along X and Z
+    columns: u64,
+    rows: u64,
+    /// Columns tall enough to hold terrain
+    columns_filled: u64,
+    region: RegionBounds,
+    material: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceTerrainMaterialResult {
+    success: bool,
+    from: String,
+    to: String,
+    /// 4 stud voxels whose material changed
+    voxels_replaced: u64,
+    /// The region searched after the height band, grown to whole voxels
+    region: RegionBounds,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CarvePathResult {
+    success: bool,
+    /// Length of the curve in studs
+    length: f64,
+    width: f64,
+    material: String,
+    /// Guard rail parts placed, counting both sides
+    guard_rails: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CarveRiverResult {
+    success: bool,
+    /// Length of the river's course in studs
+    length: f64,
+    width: f64,
+    depth: f64,
+    bank_width: f64,
+    seed: i64,
+}
+
+/// Built on the server once the stamp is saved
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveTerrainStampResult {
+    pub success: bool,
+    pub name: String,
+    /// File the stamp was saved to on the server's machine
+    pub path: String,
+    /// Voxels along X, Y and Z
+    pub size: [u32; 3],
+    /// Voxels holding terrain
+    pub solid_voxels: usize,
+    pub materials: Vec<String>,
+    /// The region saved, grown to whole voxels
+    pub region: RegionBounds,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceTerrainStampResult {
+    success: bool,
+    name: String,
+    /// Degrees the stamp was turned about Y
+    rotation: f64,
+    mode: String,
+    /// Voxels the stamp changed
+    voxels_written: u64,
+    /// Where the stamp went, on the voxel grid
+    region: RegionBounds,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct TerrainStampSummary {
+    pub name: String,
+    /// Voxels along X, Y and Z
+    pub size: [u32; 3],
+    pub materials: Vec<String>,
+}
+
+/// Built on the server from the stamp library
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ListTerrainStampsResult {
+    pub stamps: Vec<TerrainStampSummary>,
+}
+
+/// Built on the server from the terrain columns the plugin reads
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTerrainResult {
+    pub success: bool,
+    pub heightmap_path: String,
+    pub material_map_path: String,
+    /// Size of the heightmap, along X and Z
+    pub columns: u32,
+    pub rows: u32,
+    /// Studs per column
+    pub resolution: f64,
+    /// The region read, grown to whole voxels. The bottom is black in the heightmap and
+    /// the top white
+    pub region: RegionBounds,
+    /// Columns topped by each material
+    pub materials: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ModelBoundsResult {
+    success: bool,
+    bounds: Bounds,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Gap {
+    position: Vector3,
+    distance: f64,
+    nearest_in_a: Vector3,
+    nearest_in_b: Vector3,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GapSearch {
+    threshold: f64,
+    points_checked: u64,
+    parts_in_a: u64,
+    parts_in_b: u64,
+    max_gaps_reached: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FindGapsResult {
+    success: bool,
+    has_gaps: bool,
+    gap_count: u64,
+    gaps: Vec<Gap>,
+    metadata: GapSearch,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraState {
+    position: Vector3,
+    look_at: Vector3,
+    look_vector: Vector3,
+    field_of_view: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureViewportResult {
+    success: bool,
+    camera: CameraState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    requested_format: Option<String>,
+    /// Why no screenshot was taken, absent when one was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    camera_updated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous_position: Option<Vector3>,
+    /// ID of the screenshot returned as image content, for the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capture_id: Option<String>,
+    /// Size of the returned image in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<ImageSize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct ImageSize {
+    width: u32,
+    height: u32,
+}
+
+/// Output schema of a tool replying with `T`
+pub fn output_schema<T: schemars::JsonSchema + 'static>() -> Arc<JsonObject> {
+    rmcp::handler::server::tool::schema_for_output::<T>()
+        .unwrap_or_else(|e| panic!("Invalid output schema: {e}"))
+}
+
+/// What a plugin reply to a tool turned out to be
+pub enum Reply {
+    /// Plain text, or a tool without a typed result
+    Text,
+    /// `{"success": false, "error": ...}`, the tool failed
+    Failed,
+    /// A reply to a tool with a typed result that doesn't match it, explained for the
+    /// client since there's no structured content to return
+    Malformed(String),
+    /// The typed result of the tool
+    Structured(Value),
+}
+
+fn typed<T: DeserializeOwned + Serialize>(value: Value) -> serde_json::Result<Value> {
+    serde_json::to_value(serde_json::from_value::<T>(value)?)
+}
+
+/// Reads the reply of `tool` into its typed result. Replies that don't match, e.g. from
+/// an older plugin, are malformed rather than text, as the tool promises structured
+/// content.
+pub fn classify(tool: &str, reply: &str) -> Reply {
+    let parse = match tool {
+        "batch_insert_models" => typed::<BatchInsertResult>,
+        "batch_run_code" => typed::<BatchRunResult>,
+        "generate_terrain" => typed::<GenerateTerrainResult>,
+        "generate_biomes" => typed::<GenerateBiomesResult>,
+        "fill_terrain_region" => typed::<FillTerrainResult>,
+        "sculpt_terrain" => typed::<SculptTerrainResult>,
+        "clear_workspace" => typed::<ClearWorkspaceResult>,
+        "save_scene" => typed::<SaveSceneResult>,
+        "load_scene" => typed::<LoadSceneResult>,
+        "diff_scenes" => typed::<DiffScenesResult>,
+        "export_scene" => typed::<ExportSceneResult>,
+        "search_marketplace" => typed::<SearchMarketplaceResult>,
+        "import_model_file" => typed::<ImportModelFileResult>,
+        "list_scene_revisions" => typed::<ListSceneRevisionsResult>,
+        "find_scenes" => typed::<FindScenesResult>,
+        "upload_asset" => typed::<UploadAssetResult>,
+        "prune_scene_history" => typed::<PruneSceneHistoryResult>,
+        "get_console_logs" => typed::<ConsoleLogsResult>,
+        "get_output_log" => typed::<OutputLogResult>,
+        "get_performance_stats" => typed::<PerformanceStatsResult>,
+        "get_memory_report" => typed::<MemoryReportResult>,
+        "get_workspace_stats" => typed::<WorkspaceStatsResult>,
+        "get_children_info" => typed::<ChildrenInfoResult>,
+        "get_instance_tree" => typed::<InstanceTreeResult>,
+        "create_instance" => typed::<CreateInstanceResult>,
+        "insert_decal_or_texture" => typed::<InsertDecalOrTextureResult>,
+        "insert_audio" => typed::<InsertAudioResult>,
+        "insert_mesh" => typed::<InsertMeshResult>,
+        "create_part" => typed::<CreatePartResult>,
+        "create_constraint" => typed::<CreateConstraintResult>,
+        "build_structure" => typed::<BuildStructureResult>,
+        "generate_building" => typed::<GenerateBuildingResult>,
+        "set_skybox" => typed::<SetSkyboxResult>,
+        "set_atmosphere" => typed::<SetAtmosphereResult>,
+        "set_post_effects" => typed::<SetPostEffectsResult>,
+        "import_3d_file" => typed::<Import3dFileResult>,
+        "delete_instances" => typed::<DeleteInstancesResult>,
+        "clone_instance" => typed::<CloneInstanceResult>,
+        "array_duplicate" => typed::<ArrayDuplicateResult>,
+        "place_along_path" => typed::<PlaceAlongPathResult>,
+        "move_instances" => typed::<MoveInstancesResult>,
+        "align_instances" => typed::<AlignInstancesResult>,
+        "raycast" => typed::<RaycastResult>,
+        "query_region" => typed::<QueryRegionResult>,
+        "snap_to_ground" => typed::<SnapToGroundResult>,
+        "paint_instances" => typed::<PaintInstancesResult>,
+        "place_lights" => typed::<PlaceLightsResult>,
+        "create_particle_emitter" => typed::<CreateParticleEmitterResult>,
+        "setup_weather" => typed::<SetupWeatherResult>,
+        "setup_day_night_cycle" => typed::<SetupDayNightCycleResult>,
+        "setup_ambient_sound" => typed::<SetupAmbientSoundResult>,
+        "set_workspace_physics" => typed::<SetWorkspacePhysicsResult>,
+        "run_tests" => typed::<RunTestsResult>,
+        "start_playtest" => typed::<StartPlaytestResult>,
+        "stop_playtest" => typed::<StopPlaytestResult>,
+        "rename_instances" => typed::<RenameInstancesResult>,
+        "search_instances" => typed::<SearchInstancesResult>,
+        "get_selection" => typed::<GetSelectionResult>,
+        "set_selection" => typed::<SetSelectionResult>,
+        "create_checkpoint" => typed::<CreateCheckpointResult>,
+        "undo_to_checkpoint" => typed::<UndoToCheckpointResult>,
+        "manage_tags" => typed::<ManageTagsResult>,
+        "manage_attributes" => typed::<ManageAttributesResult>,
+        "read_script_source" => typed::<ReadScriptSourceResult>,
+        "write_script_source" => typed::<WriteScriptSourceResult>,
+        "patch_script" => typed::<PatchScriptResult>,
+        "list_scripts" => typed::<ListScriptsResult>,
+        "create_script" => typed::<CreateScriptResult>,
+        "search_code" => typed::<SearchCodeResult>,
+        "lint_scripts" => typed::<LintScriptsResult>,
+        "format_scripts" => typed::<FormatScriptsResult>,
+        "get_module_dependencies" => typed::<ModuleDependenciesResult>,
+        "rename_symbol" => typed::<RenameSymbolResult>,
+        "import_heightmap" => typed::<ImportHeightmapResult>,
+        "export_terrain" => typed::<ExportTerrainResult>,
+        "replace_terrain_material" => typed::<ReplaceTerrainMaterialResult>,
+        "carve_path" => typed::<CarvePathResult>,
+        "carve_river" => typed::<CarveRiverResult>,
+        "save_terrain_stamp" => typed::<SaveTerrainStampResult>,
+        "place_terrain_stamp" => typed::<PlaceTerrainStampResult>,
+        "list_terrain_stamps" => typed::<ListTerrainStampsResult>,
+        "get_model_bounds" => typed::<ModelBoundsResult>,
+        "find_gaps" => typed::<FindGapsResult>,
+        "capture_viewport" => typed::<CaptureViewportResult>,
+        _ => return Reply::Text,
+    };
+    let malformed = |e: serde_json::Error| {
+        tracing::warn!("Unexpected {tool} reply from the plugin: {e}");
+        Reply::Malformed(format!(
+            "Studio's reply to {tool} doesn't have the shape of its result ({e}), the plugin may be out of date. The reply was:\n{reply}"
+        ))
+    };
+    let value = match serde_json::from_str::<Value>(reply) {
+        Ok(value) => value,
+        Err(e) => return malformed(e),
+    };
+    // Batch tools report partial failures with `success: false` but no top level error
+    if value["success"] == Value::Bool(false) && value["error"].is_string() {
+        return Reply::Failed;
+    }
+    match parse(value) {
+        Ok(structured) => Reply::Structured(structured),
+        Err(e) => malformed(e),
+    }
+}
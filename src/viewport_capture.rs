@@ -0,0 +1,41 @@
+// Screenshots taken by `capture_viewport`. Plugins can only read the raw pixels of a
+// capture, so the plugin uploads those and the PNG returned to the client is made here.
+
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use image::imageops::{self, FilterType};
+use image::{ImageFormat, RgbaImage};
+use std::io::Cursor;
+
+/// Longest side of the image `capture_viewport` returns unless asked otherwise, and the
+/// bounds it can be asked for. Plugins can't read images larger than 1024 pixels.
+pub const DEFAULT_MAX_SIZE: u32 = 768;
+pub const MIN_MAX_SIZE: u32 = 64;
+pub const MAX_MAX_SIZE: u32 = 1024;
+/// Largest upload, the RGBA pixels of a 1024 by 1024 capture
+pub const MAX_CAPTURE_BYTES: usize = 1024 * 1024 * 4;
+
+/// Encodes `width` by `height` RGBA pixels as a PNG, scaled down so neither side is longer
+/// than `max_size`. Returns the PNG with its width and height.
+pub fn encode_png(
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    max_size: u32,
+) -> Result<(Vec<u8>, u32, u32)> {
+    let image = RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| eyre!("The capture isn't {width} by {height} RGBA pixels"))?;
+    let longest = width.max(height);
+    let image = if longest > max_size && max_size > 0 {
+        let scale = f64::from(max_size) / f64::from(longest);
+        let scaled = |side: u32| ((f64::from(side) * scale).round() as u32).max(1);
+        imageops::resize(&image, scaled(width), scaled(height), FilterType::Triangle)
+    } else {
+        image
+    };
+    let (width, height) = image.dimensions();
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .wrap_err("Couldn't encode the capture as a PNG")?;
+    Ok((png, width, height))
+}
@@ -0,0 +1,94 @@
+// The driver script `setup_weather` puts in the weather Model. Everything it needs is
+// read from the Model's attributes, so the weather can be changed while the game runs by
+// setting `Weather` and `Intensity`, or tuned in Studio without touching the script.
+
+/// Weathers the driver knows, `storm` is heavy rain with thunder
+pub const WEATHERS: &[&str] = &["clear", "rain", "snow", "storm"];
+
+pub const DRIVER_SOURCE: &str = r#"-- Drives the weather of the Model this script is in. Set the Model's Weather attribute
+-- to clear, rain, snow or storm to change it, and Intensity from 0 to 1 for how heavy it
+-- is. TransitionTime, Fog, Thunder, ThunderMin, ThunderMax, CycleMinutes and
+-- CycleWeathers tune the rest.
+
+local Lighting = game:GetService("Lighting")
+local TweenService = game:GetService("TweenService")
+
+local model = script.Parent
+local rain = model:WaitForChild("RainEmitter"):WaitForChild("Rain") :: ParticleEmitter
+local snow = model:WaitForChild("SnowEmitter"):WaitForChild("Snow") :: ParticleEmitter
+local thunder = model:FindFirstChild("Thunder") :: Sound?
+
+-- Most particles a second each rig emits at full intensity
+local RAIN_RATE = 400
+local SNOW_RATE = 150
+-- How close the fog comes in at full intensity, in studs
+local HEAVY_FOG_END = 250
+local HEAVY_ATMOSPHERE_DENSITY = 0.6
+
+local random = Random.new()
+local clearFogEnd = Lighting.FogEnd
+local atmosphere = Lighting:FindFirstChildOfClass("Atmosphere")
+local clearDensity = if atmosphere then atmosphere.Density else 0
+
+local function apply()
+	local weather = model:GetAttribute("Weather") or "clear"
+	local intensity = math.clamp(model:GetAttribute("Intensity") or 0.5, 0, 1)
+	local transition = TweenInfo.new(model:GetAttribute("TransitionTime") or 5)
+
+	local wet = weather == "rain" or weather == "storm"
+	local heaviness = if weather == "storm" then math.min(intensity + 0.3, 1) else intensity
+	rain.Enabled = wet
+	rain.Rate = RAIN_RATE * heaviness
+	snow.Enabled = weather == "snow"
+	snow.Rate = SNOW_RATE * intensity
+
+	if model:GetAttribute("Fog") ~= false then
+		local thickness = if weather == "clear" then 0 else heaviness
+		local fogEnd = clearFogEnd + (math.min(HEAVY_FOG_END, clearFogEnd) - clearFogEnd) * thickness
+		TweenService:Create(Lighting, transition, { FogEnd = fogEnd }):Play()
+		if atmosphere then
+			local density = clearDensity + (math.max(HEAVY_ATMOSPHERE_DENSITY, clearDensity) - clearDensity) * thickness
+			TweenService:Create(atmosphere, transition, { Density = density }):Play()
+		end
+	end
+end
+
+local function flash()
+	local brightness = Lighting.Brightness
+	Lighting.Brightness = brightness + 3
+	task.wait(0.1)
+	Lighting.Brightness = brightness
+	task.wait(random:NextNumber(0.2, 1.5))
+	if thunder then
+		thunder:Play()
+	end
+end
+
+model:GetAttributeChangedSignal("Weather"):Connect(apply)
+model:GetAttributeChangedSignal("Intensity"):Connect(apply)
+apply()
+
+task.spawn(function()
+	while true do
+		local low = model:GetAttribute("ThunderMin") or 8
+		local high = math.max(model:GetAttribute("ThunderMax") or 20, low)
+		task.wait(random:NextNumber(low, high))
+		if model:GetAttribute("Weather") == "storm" and model:GetAttribute("Thunder") ~= false then
+			flash()
+		end
+	end
+end)
+
+task.spawn(function()
+	while true do
+		local minutes = model:GetAttribute("CycleMinutes") or 0
+		if minutes <= 0 then
+			task.wait(5)
+			continue
+		end
+		task.wait(minutes * 60)
+		local weathers = string.split(model:GetAttribute("CycleWeathers") or "clear,rain", ",")
+		model:SetAttribute("Weather", weathers[random:NextInteger(1, #weathers)])
+	end
+end)
+"#;